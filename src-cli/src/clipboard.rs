@@ -0,0 +1,26 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::env;
+use std::io::{self, Write};
+
+/// Copies text to the clipboard of the terminal the user is looking at, using the OSC 52 escape
+/// sequence. This works over SSH and without a display server, as long as the terminal emulator
+/// supports OSC 52.
+///
+/// Inside tmux the sequence is wrapped in a DCS passthrough so tmux forwards it to the outer
+/// terminal.
+pub fn copy_with_osc52(text: &str, out: &mut impl Write) -> io::Result<()> {
+    let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+
+    if env::var_os("TMUX").is_some() {
+        write!(
+            out,
+            "\x1bPtmux;{}\x1b\\",
+            sequence.replace('\x1b', "\x1b\x1b")
+        )?;
+    } else {
+        write!(out, "{sequence}")?;
+    }
+
+    out.flush()
+}
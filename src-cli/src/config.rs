@@ -0,0 +1,59 @@
+use clap::Subcommand;
+use cognitio_core::config::{check_cognitio_yaml, locate_config, CONFIG_ENV_OVERRIDES};
+use std::env;
+use std::io;
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Print which config file is used and why
+    Path {},
+
+    /// Check the config file for errors and exit non-zero if there are any
+    Validate {},
+}
+
+pub fn run(command: &ConfigCommand) -> io::Result<()> {
+    match command {
+        ConfigCommand::Path {} => print_path(),
+        ConfigCommand::Validate {} => validate(),
+    }
+}
+
+fn print_path() -> io::Result<()> {
+    let location = locate_config();
+    println!("{}", location.path.display());
+
+    let exists = if location.path.is_file() {
+        ""
+    } else {
+        ", but the file doesn't exist"
+    };
+    eprintln!("Picked because {}{exists}", location.source);
+
+    for (variable, key) in CONFIG_ENV_OVERRIDES {
+        if env::var_os(variable).is_some_and(|value| !value.is_empty()) {
+            eprintln!("{variable} overrides {key}");
+        }
+    }
+    Ok(())
+}
+
+fn validate() -> io::Result<()> {
+    let check = check_cognitio_yaml();
+    for error in &check.errors {
+        println!("{error}");
+    }
+    for warning in &check.warnings {
+        println!("Warning: {warning}");
+    }
+
+    if check.is_valid() {
+        println!("{} is valid", locate_config().path.display());
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Found {} problem(s) in the config", check.errors.len()),
+        ))
+    }
+}
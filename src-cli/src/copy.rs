@@ -0,0 +1,37 @@
+use crate::clipboard::copy_with_osc52;
+use crate::fill::fill_code;
+use crate::resolve::{
+    join_code_blocks, resolve_cheatsheet_file, resolve_section, select_code_blocks,
+};
+use cognitio_core::snippet::load_cheatsheet_file;
+use std::io::{self, IsTerminal, Write};
+
+pub fn run(
+    id: &str,
+    section: &str,
+    nth: Option<usize>,
+    all: bool,
+    stdout: bool,
+    vars: &[(String, String)],
+) -> io::Result<()> {
+    let path = resolve_cheatsheet_file(id)?;
+    let cheatsheet = load_cheatsheet_file(&path)?;
+    let section = resolve_section(&cheatsheet, section)?;
+    let code = join_code_blocks(&select_code_blocks(section, nth, all)?);
+    let code = fill_code(&code, vars)?;
+
+    let mut out = io::stdout().lock();
+    // Without a terminal to talk to, for example when piping, the code is printed instead.
+    if stdout || !io::stdout().is_terminal() {
+        writeln!(out, "{code}")?;
+        return out.flush();
+    }
+
+    copy_with_osc52(&code, &mut out)?;
+    eprintln!(
+        "Copied {} line(s) from '{}' to the clipboard",
+        code.lines().count(),
+        section.title
+    );
+    Ok(())
+}
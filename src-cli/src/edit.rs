@@ -0,0 +1,28 @@
+use crate::resolve::{resolve_cheatsheet_file, resolve_section, resolve_shorthand_id};
+use cognitio_core::config::read_cognitio_yaml;
+use cognitio_core::editor::editor_command;
+use cognitio_core::snippet::load_cheatsheet_file;
+use std::io;
+use std::path::Path;
+
+/// Opens a cheatsheet or directory in the editor, at the heading of a section if one is given.
+/// The editor runs in the foreground, so terminal editors like `nvim` work.
+pub fn run(id: &str, section: Option<&str>) -> io::Result<()> {
+    let (path, line) = match section {
+        Some(query) => {
+            let path = resolve_cheatsheet_file(id)?;
+            let cheatsheet = load_cheatsheet_file(&path)?;
+            let line = resolve_section(&cheatsheet, query)?.start_line;
+            (path, Some(line))
+        }
+        None if Path::new(id).exists() => (id.to_string(), None),
+        None => (resolve_shorthand_id(id)?, None),
+    };
+
+    let conf = read_cognitio_yaml()?;
+    let status = editor_command(&conf, Path::new(&path), line)?.run()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("The editor exited with {status}")));
+    }
+    Ok(())
+}
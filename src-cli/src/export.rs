@@ -0,0 +1,36 @@
+use clap::Subcommand;
+use cognitio_core::config::read_cognitio_yaml;
+use cognitio_core::export::export_library;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Subcommand)]
+pub enum ExportCommand {
+    /// Export every cheatsheet as a static HTML site with navigation and search
+    Html {
+        /// Directory to write the site to. It is created if it doesn't exist
+        #[arg(long)]
+        out: PathBuf,
+    },
+}
+
+pub fn run(command: &ExportCommand) -> io::Result<()> {
+    match command {
+        ExportCommand::Html { out } => html(out),
+    }
+}
+
+fn html(out: &Path) -> io::Result<()> {
+    let conf = read_cognitio_yaml()?;
+    let summary = export_library(&conf, out)?;
+    for path in &summary.skipped {
+        eprintln!("Skipped {path}, it couldn't be read");
+    }
+    println!(
+        "Exported {} cheatsheets with {} sections to {}",
+        summary.cheatsheets,
+        summary.sections,
+        out.join("index.html").display()
+    );
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use cognitio_core::placeholder::{
+    fill_placeholders, find_placeholders, placeholder_history_path, Placeholder, PlaceholderHistory,
+};
+use log::warn;
+use std::collections::HashMap;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// How many recent values are offered in a prompt.
+const RECENT_IN_PROMPT: usize = 3;
+
+/// Parses a `--var name=value` argument.
+pub fn parse_var(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected NAME=VALUE, got '{arg}'")),
+    }
+}
+
+/// Fills in the placeholders of a snippet.
+///
+/// Values come from `--var` first. Others are asked for on the terminal, offering the default,
+/// the choices and recently used values. When stdin isn't a terminal the default is used, then
+/// the most recent value. Values used are remembered for the next time.
+pub fn fill_code(code: &str, vars: &[(String, String)]) -> io::Result<String> {
+    let placeholders = find_placeholders(code);
+    if placeholders.is_empty() {
+        return Ok(code.to_string());
+    }
+
+    let history_path = placeholder_history_path();
+    let mut history = PlaceholderHistory::load(&history_path).unwrap_or_else(|error| {
+        warn!(
+            "Failed to read {}, ignoring recent values: {error}",
+            history_path.display()
+        );
+        PlaceholderHistory::default()
+    });
+
+    let interactive = io::stdin().is_terminal();
+    let mut values = HashMap::new();
+    for placeholder in &placeholders {
+        let given = vars
+            .iter()
+            .rev()
+            .find(|(name, _)| name == &placeholder.name)
+            .map(|(_, value)| value.clone());
+        let recent = history.recent(&placeholder.name);
+
+        let value = match given {
+            Some(value) => Some(value),
+            None if interactive => Some(prompt(placeholder, recent)?),
+            None => placeholder
+                .default
+                .clone()
+                .or_else(|| recent.first().cloned()),
+        };
+
+        match value {
+            Some(value) => {
+                history.remember(&placeholder.name, &value);
+                values.insert(placeholder.name.clone(), value);
+            }
+            None => warn!(
+                "No value for {{{{{}}}}}, leaving it as is",
+                placeholder.name
+            ),
+        }
+    }
+
+    if let Err(error) = history.save(&history_path) {
+        warn!(
+            "Failed to save recent values to {}: {error}",
+            history_path.display()
+        );
+    }
+
+    Ok(fill_placeholders(code, &values))
+}
+
+/// Asks for a placeholder's value on stderr until one is given. An empty answer picks the
+/// default, or the most recent value, and a number picks a choice.
+fn prompt(placeholder: &Placeholder, recent: &[String]) -> io::Result<String> {
+    let fallback = placeholder
+        .default
+        .clone()
+        .or_else(|| recent.first().cloned());
+    let mut stderr = io::stderr().lock();
+
+    for (number, choice) in placeholder.choices.iter().enumerate() {
+        writeln!(stderr, "  {}) {choice}", number + 1)?;
+    }
+    let recent: Vec<&str> = recent
+        .iter()
+        .filter(|value| {
+            !placeholder.choices.contains(value) && placeholder.default.as_ref() != Some(*value)
+        })
+        .take(RECENT_IN_PROMPT)
+        .map(|value| value.as_str())
+        .collect();
+    if !recent.is_empty() {
+        writeln!(stderr, "  recent: {}", recent.join(", "))?;
+    }
+
+    loop {
+        match &fallback {
+            Some(fallback) => write!(stderr, "{} [{fallback}]: ", placeholder.name)?,
+            None => write!(stderr, "{}: ", placeholder.name)?,
+        }
+        stderr.flush()?;
+
+        let mut answer = String::new();
+        if io::stdin().lock().read_line(&mut answer)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("No value given for {}", placeholder.name),
+            ));
+        }
+        let answer = answer.trim_end_matches(['\r', '\n']);
+
+        if answer.is_empty() {
+            match &fallback {
+                Some(fallback) => return Ok(fallback.clone()),
+                None => continue,
+            }
+        }
+        if let Some(choice) = answer
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| placeholder.choices.get(number.wrapping_sub(1)))
+        {
+            return Ok(choice.clone());
+        }
+        return Ok(answer.to_string());
+    }
+}
@@ -0,0 +1,60 @@
+use clap::Subcommand;
+use cognitio_core::config::read_cognitio_yaml;
+use cognitio_core::navi::import_navi;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Subcommand)]
+pub enum ImportCommand {
+    /// Convert navi `.cheat` files into cheatsheets
+    ///
+    /// Each command becomes a section with a code block, titled with its description. Tags become
+    /// h2 headings and `<variables>` become `{{placeholders}}`.
+    Navi {
+        /// A `.cheat` file, or a directory to import every `.cheat` file below
+        path: PathBuf,
+        /// The cheatsheet source to write the cheatsheets to
+        #[arg(long)]
+        into: PathBuf,
+    },
+}
+
+pub fn run(command: &ImportCommand) -> io::Result<()> {
+    match command {
+        ImportCommand::Navi { path, into } => navi(path, into),
+    }
+}
+
+fn navi(path: &Path, into: &Path) -> io::Result<()> {
+    let summary = import_navi(path, into)?;
+    for skipped in &summary.skipped {
+        eprintln!("Skipped {skipped}");
+    }
+    println!(
+        "Imported {} cheatsheets with {} sections into {}",
+        summary.cheatsheets,
+        summary.sections,
+        into.display()
+    );
+    if !is_in_a_source(into) {
+        println!(
+            "{} isn't in a cheatsheet source yet, add it to `cheatsheets` in cognitio.yaml to see them",
+            into.display()
+        );
+    }
+    Ok(())
+}
+
+fn is_in_a_source(dir: &Path) -> bool {
+    let (Ok(conf), Ok(dir)) = (read_cognitio_yaml(), fs::canonicalize(dir)) else {
+        return false;
+    };
+    conf.cheatsheets.iter().any(|source| {
+        source
+            .expanded_path()
+            .ok()
+            .and_then(|path| fs::canonicalize(path).ok())
+            .is_some_and(|source| dir.starts_with(source))
+    })
+}
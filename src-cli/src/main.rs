@@ -1,48 +1,46 @@
 use clap::{Parser, Subcommand};
+use cognitio_core::config::{cognitio_home_dir, read_cognitio_yaml, set_config_flag};
+use cognitio_core::library::{self, sort_directories_and_files};
+use cognitio_core::shorthand::load_and_assign_shorthand_ids;
+use cognitio_core::Directory;
 use log::LevelFilter;
-use log::{error, info};
-use log4rs;
-use log4rs::append::console::ConsoleAppender;
+use log4rs::append::console::{ConsoleAppender, Target};
 use log4rs::append::file::FileAppender;
 use log4rs::config::{Appender, Config as Log4rsConfig, Root};
 use log4rs::encode::pattern::PatternEncoder;
-use serde::Deserialize;
-use serde::Serialize;
-use serde_yaml;
-use std::env;
-use std::fs;
-use std::fs::File;
-use std::io::Read;
-use std::path::Path;
+use log4rs::filter::threshold::ThresholdFilter;
+use std::io;
 use std::path::PathBuf;
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct DirectoryFile {
-    pub name: String,
-    pub path: String,
-    pub shorthand_id: String,
-}
-
-#[derive(Debug, Serialize, Clone)]
-pub struct Directory {
-    pub name: String,
-    pub path: String,
-    pub files: Vec<DirectoryFile>,
-    pub sub_directories: Vec<Directory>,
-    pub shorthand_id: String,
-}
+use std::process;
+
+mod clipboard;
+mod config;
+mod copy;
+mod edit;
+mod export;
+mod fill;
+mod import;
+mod render;
+mod resolve;
+mod run;
+mod search;
+mod sections;
+mod serve;
+mod show;
+mod sources;
+mod text;
+mod tui;
+mod watch;
 
 #[derive(Parser)]
-#[command(version, about, long_about = None)]
+#[command(name = "cognitio", version, about, long_about = None)]
 struct Cli {
-    /// Optional name to operate on
-    name: Option<String>,
-
-    /// Sets a custom config file
+    /// Config file to use instead of the one found through COGNITIO_CONFIG, COGNITIO_HOME,
+    /// XDG_CONFIG_HOME or ~/.config/cognitio
     #[arg(short, long, value_name = "FILE")]
     config: Option<PathBuf>,
 
-    /// Turn debugging information on
+    /// Print more log output to the terminal (-d for info, -dd for debug)
     #[arg(short, long, action = clap::ArgAction::Count)]
     debug: u8,
 
@@ -61,102 +59,290 @@ enum Commands {
 
     #[clap(alias = "ls")]
     List {},
-}
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
-pub struct CheatsheetInfo {
-    title: String,
-    path: String,
-}
+    /// List the h2 groups and h3 sections of a cheatsheet file
+    Sections {
+        /// Path to the cheatsheet markdown file
+        path: PathBuf,
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
-pub struct Styling {
-    menu: Option<Menu>,
-}
+        /// Print the parsed cheatsheet as JSON
+        #[arg(long)]
+        json: bool,
+    },
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
-pub struct Menu {
-    width: Option<String>,
-}
+    /// Search snippets in all cheatsheet sources
+    ///
+    /// Words match headings, prose and code and allow small typos. Use "exact phrase" for exact
+    /// matches, lang:<language> to filter on code block language and path:<text> to filter on
+    /// file path.
+    Search {
+        /// The search query
+        #[arg(required = true)]
+        query: Vec<String>,
+
+        /// Maximum number of hits to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+
+        /// Print the hits as JSON
+        #[arg(long)]
+        json: bool,
+    },
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
-pub struct CognitioConfig {
-    editor: Option<String>,
-    cheatsheets: Vec<CheatsheetData>,
-    styling: Option<Styling>,
-}
+    /// Copy the code of a section to the clipboard
+    ///
+    /// Uses the OSC 52 terminal escape sequence, which works over SSH as long as the terminal
+    /// supports it. When the output isn't a terminal the code is printed instead.
+    Copy {
+        /// Shorthand ID from `cognitio ls`, or a path to a cheatsheet file
+        id: String,
+
+        /// Section title, a unique part of it, or its number in the file
+        section: String,
+
+        /// Copy the Nth code block of the section (starting at 1) instead of the first
+        #[arg(short, long, value_name = "N", conflicts_with = "all")]
+        nth: Option<usize>,
+
+        /// Copy all code blocks of the section, separated by a blank line
+        #[arg(short, long)]
+        all: bool,
+
+        /// Print the code to stdout instead of copying it
+        #[arg(long)]
+        stdout: bool,
+
+        /// Value for a {{placeholder}}, as NAME=VALUE. Other placeholders are asked for
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = fill::parse_var)]
+        vars: Vec<(String, String)>,
+    },
+
+    /// Run the code of a section
+    ///
+    /// The runner is picked by the code block's language from runners: in cognitio.yaml, with
+    /// bash, sh, zsh and python working out of the box. The command is shown and has to be
+    /// confirmed before it runs. Exits with the exit code of the runner.
+    Run {
+        /// Shorthand ID from `cognitio ls`, or a path to a cheatsheet file
+        id: String,
+
+        /// Section title, a unique part of it, or its number in the file
+        section: String,
+
+        /// Run the Nth code block of the section (starting at 1) instead of the first
+        #[arg(short, long, value_name = "N", conflicts_with = "all")]
+        nth: Option<usize>,
+
+        /// Run all code blocks of the section together
+        #[arg(short, long)]
+        all: bool,
+
+        /// Show what would run without running it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Run without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Value for a {{placeholder}}, as NAME=VALUE. Other placeholders are asked for
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = fill::parse_var)]
+        vars: Vec<(String, String)>,
+    },
+
+    /// Print the full path of a cheatsheet file or directory by its shorthand ID
+    Resolve {
+        /// Shorthand ID from `cognitio ls`
+        id: String,
+    },
+
+    /// Inspect the config file
+    Config {
+        #[command(subcommand)]
+        command: config::ConfigCommand,
+    },
+
+    /// Manage cheatsheet sources
+    Sources {
+        #[command(subcommand)]
+        command: sources::SourcesCommand,
+    },
+
+    /// Browse cheatsheets in a full-screen terminal interface
+    Tui {},
+
+    /// Print a cheatsheet, or one of its sections, as cards
+    Show {
+        /// Shorthand ID from `cognitio ls`, or a path to a cheatsheet file
+        id: String,
+
+        /// Section title, a unique part of it, or its number in the file
+        section: Option<String>,
+
+        /// Print only the code blocks, without any decoration
+        #[arg(long)]
+        raw: bool,
+
+        /// Value for a {{placeholder}}, as NAME=VALUE. Used with --raw
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = fill::parse_var)]
+        vars: Vec<(String, String)>,
+    },
+
+    /// Export cheatsheets to other formats
+    Export {
+        #[command(subcommand)]
+        command: export::ExportCommand,
+    },
+
+    /// Import cheatsheets from other tools
+    Import {
+        #[command(subcommand)]
+        command: import::ImportCommand,
+    },
+
+    /// Print changes to cheatsheets and the config file as they happen
+    ///
+    /// Changes are printed after a short quiet period, like the app reloads them. A change to
+    /// the config file starts watching its new sources.
+    Watch {
+        /// Print each change as a JSON object on a line of its own
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Serve the library over HTTP, as a JSON API and pages to browse it
+    ///
+    /// The API is read-only unless --writable is given. Paths in the API are absolute file
+    /// paths, and only the cheatsheets and directories listed in the library are served, never
+    /// other files in the sources or in COGNITIO_HOME.
+    Serve {
+        /// Address and port to listen on. Use 0.0.0.0 to make the server reachable from other
+        /// machines
+        #[arg(long, default_value = "127.0.0.1:7777")]
+        bind: String,
+
+        /// Require this token from clients, as `Authorization: Bearer <token>`
+        #[arg(long, env = "COGNITIO_SERVE_TOKEN", hide_env_values = true)]
+        token: Option<String>,
+
+        /// Allow creating cheatsheets and changing sections through the API
+        #[arg(long)]
+        writable: bool,
+    },
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
-#[serde(untagged)]
-pub enum CheatsheetData {
-    Simple(String),
-    Info(CheatsheetInfo),
+    /// Open a cheatsheet or directory in the editor, at a section's heading if one is given
+    Edit {
+        /// Shorthand ID from `cognitio ls`, or a path to a cheatsheet file or directory
+        id: String,
+
+        /// Section title, a unique part of it, or its number in the file
+        section: Option<String>,
+    },
 }
 
 fn main() {
-    setup_logger();
-
     let cli = Cli::parse();
 
-    // You can check the value provided by positional arguments, or option arguments
-    if let Some(name) = cli.name.as_deref() {
-        println!("Value for name: {name}");
+    if let Some(config_path) = &cli.config {
+        set_config_flag(config_path.clone());
     }
 
-    if let Some(config_path) = cli.config.as_deref() {
-        println!("Value for config: {}", config_path.display());
-    }
-
-    // You can see how many times a particular flag or argument occurred
-    // Note, only flags can have multiple occurrences
-    match cli.debug {
-        0 => println!("Debug mode is off"),
-        1 => println!("Debug mode is kind of on"),
-        2 => println!("Debug mode is on"),
-        _ => println!("Don't be crazy"),
-    }
+    setup_logger(cli.debug);
 
-    // You can check for the existence of subcommands, and if found use their
-    // matches just as you would the top level cmd
-    match &cli.command {
+    let result = match &cli.command {
         Some(Commands::Test { list }) => {
             if *list {
                 println!("Printing testing lists...");
             } else {
                 println!("Not printing testing lists...");
             }
+            Ok(())
         }
-        Some(Commands::List {}) => {
-            list_tree_down_to_snippet_names();
-        }
-        None => {}
+        Some(Commands::List {}) => list_tree_down_to_snippet_names(),
+        Some(Commands::Sections { path, json }) => sections::run(path, *json),
+        Some(Commands::Search { query, limit, json }) => search::run(query, *limit, *json),
+        Some(Commands::Copy {
+            id,
+            section,
+            nth,
+            all,
+            stdout,
+            vars,
+        }) => copy::run(id, section, *nth, *all, *stdout, vars),
+        Some(Commands::Run {
+            id,
+            section,
+            nth,
+            all,
+            dry_run,
+            yes,
+            vars,
+        }) => match run::run(id, section, *nth, *all, *dry_run, *yes, vars) {
+            Ok(code) => process::exit(code),
+            Err(error) => Err(error),
+        },
+        Some(Commands::Resolve { id }) => resolve::run(id),
+        Some(Commands::Config { command }) => config::run(command),
+        Some(Commands::Sources { command }) => sources::run(command),
+        Some(Commands::Tui {}) => tui::run(),
+        Some(Commands::Show {
+            id,
+            section,
+            raw,
+            vars,
+        }) => show::run(id, section.as_deref(), *raw, vars),
+        Some(Commands::Edit { id, section }) => edit::run(id, section.as_deref()),
+        Some(Commands::Export { command }) => export::run(command),
+        Some(Commands::Import { command }) => import::run(command),
+        Some(Commands::Watch { json }) => watch::run(*json),
+        Some(Commands::Serve {
+            bind,
+            token,
+            writable,
+        }) => serve::run(bind, token.clone(), *writable),
+        None => Ok(()),
+    };
+
+    if let Err(error) = result {
+        eprintln!("Error: {error}");
+        process::exit(1);
     }
-
-    // Continued program logic goes here...
 }
 
-fn setup_logger() {
+fn setup_logger(debug: u8) {
     let home = cognitio_home_dir();
     let log_path = PathBuf::from(home).join("cognitio.log");
 
     let encoder = PatternEncoder::new("{h({d(%Y-%m-%d %H:%M:%S)(utc)} - {l}: {m}{n})}");
 
-    let stdout_appender = ConsoleAppender::builder()
+    // Log to stderr so that stdout only holds command output and can be piped.
+    let stderr_appender = ConsoleAppender::builder()
         .encoder(Box::new(encoder.clone()))
+        .target(Target::Stderr)
         .build();
 
+    let stderr_level = match debug {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        _ => LevelFilter::Debug,
+    };
+
     let file_appender = FileAppender::builder()
         .encoder(Box::new(encoder.clone()))
         .build(log_path)
         .unwrap();
 
     let root = Root::builder()
-        .appender("stdout_logger")
+        .appender("stderr_logger")
         .appender("file_logger")
         .build(LevelFilter::Debug);
 
     let config = Log4rsConfig::builder()
-        .appender(Appender::builder().build("stdout_logger", Box::new(stdout_appender)))
+        .appender(
+            Appender::builder()
+                .filter(Box::new(ThresholdFilter::new(stderr_level)))
+                .build("stderr_logger", Box::new(stderr_appender)),
+        )
         .appender(Appender::builder().build("file_logger", Box::new(file_appender)))
         .build(root)
         .unwrap();
@@ -164,200 +350,33 @@ fn setup_logger() {
     log4rs::init_config(config).unwrap();
 }
 
-fn list_tree_down_to_snippet_names() {
-    let dirs = list_cheatsheet_directories();
+fn list_tree_down_to_snippet_names() -> io::Result<()> {
+    let dirs = list_cheatsheet_directories()?;
     let mut sorted_dirs = sort_directories_and_files(&dirs);
-    enrich_directories_with_shorthand_ids(&mut sorted_dirs);
-    print_directory_tree(&dirs)
+    load_and_assign_shorthand_ids(&mut sorted_dirs)?;
+    print_directory_tree(&sorted_dirs);
+    Ok(())
 }
 
-fn print_directory_tree(dirs: &Vec<Directory>) {
+fn print_directory_tree(dirs: &[Directory]) {
     dirs.iter().for_each(|dir| {
-        println!("{}", dir.name);
-        dir.files.iter().for_each(|file| {
-            println!("  {}", file.name);
-        });
-        dir.sub_directories.iter().for_each(|sub_dir| {
-            println!(
-                "  {} ({})",
-                sub_dir.name,
-                make_short_id_from_two_first_letters_of_path_parts(&sub_dir.path, 2)
-            );
-            sub_dir.files.iter().for_each(|file| {
-                println!(
-                    "    {} ({})",
-                    file.name,
-                    make_short_id_from_two_first_letters_of_path_parts(&file.path, 3),
-                );
-            });
-        });
+        println!("{} ({})", dir.name, dir.shorthand_id);
+        print_directory_contents(dir, 1);
     });
 }
 
-fn enrich_directories_with_shorthand_ids(dirs: &mut Vec<Directory>) {
-    dirs.iter_mut().for_each(|dir| {
-        dir.shorthand_id = make_short_id_from_two_first_letters_of_path_parts(&dir.path, 2);
-        dir.files.iter_mut().for_each(|file| {
-            file.shorthand_id = make_short_id_from_two_first_letters_of_path_parts(&file.path, 3);
-        });
-        enrich_directories_with_shorthand_ids(&mut dir.sub_directories);
+fn print_directory_contents(dir: &Directory, depth: usize) {
+    let indent = "  ".repeat(depth);
+    dir.files.iter().for_each(|file| {
+        println!("{}{} ({})", indent, file.name, file.shorthand_id);
     });
-}
-
-fn make_short_id_from_two_first_letters_of_path_parts(
-    file_path: &str,
-    dirs_backwards: usize,
-) -> String {
-    let path = Path::new(file_path);
-    let mut id_parts = Vec::new();
-    let mut count = 0;
-
-    for component in path.components().rev() {
-        if count == dirs_backwards {
-            break;
-        }
-        if let Some(name) = component.as_os_str().to_str() {
-            // Take first two letters of name and push them to id_parts
-            id_parts.push(name.to_lowercase().chars().take(2).collect::<String>());
-            count += 1;
-        }
-    }
-
-    // Reverse the ID parts and join them together to the final ID-string
-    id_parts.reverse();
-    id_parts.join("")
-}
-
-fn sort_directories_and_files(dirs: &Vec<Directory>) -> Vec<Directory> {
-    let mut sorted_dirs = dirs.clone();
-    sorted_dirs.sort_by(|a, b| a.name.cmp(&b.name));
-    sorted_dirs.iter_mut().for_each(|dir| {
-        dir.files.sort_by(|a, b| a.name.cmp(&b.name));
-        dir.sub_directories = sort_directories_and_files(&dir.sub_directories);
+    dir.sub_directories.iter().for_each(|sub_dir| {
+        println!("{}{} ({})", indent, sub_dir.name, sub_dir.shorthand_id);
+        print_directory_contents(sub_dir, depth + 1);
     });
-    sorted_dirs.to_vec()
-}
-
-fn check_if_path_exists(path_str: &str) -> bool {
-    Path::new(path_str).exists()
-}
-
-fn read_file_to_string(file_path: &str) -> std::io::Result<String> {
-    let mut file = File::open(file_path)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    Ok(contents)
-}
-
-fn list_cheatsheet_directories() -> Vec<Directory> {
-    let conf = read_cognitio_yaml().unwrap();
-    let res: Vec<Directory> = conf
-        .cheatsheets
-        .iter()
-        .map(|cheatsheet_path| match cheatsheet_path {
-            CheatsheetData::Simple(path) => {
-                let name = Path::new(path)
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_str()
-                    .unwrap_or_default()
-                    .to_string();
-                Directory {
-                    name,
-                    path: path.to_string(),
-                    files: Vec::new(),
-                    sub_directories: list_subdirectories(&path),
-                    shorthand_id: "".to_string(),
-                }
-            }
-            CheatsheetData::Info(info) => {
-                let name = info.title.clone();
-                Directory {
-                    name,
-                    path: info.path.clone(),
-                    files: Vec::new(),
-                    sub_directories: list_subdirectories(&info.path),
-                    shorthand_id: "".to_string(),
-                }
-            }
-        })
-        .collect();
-    res
-}
-
-fn list_subdirectories(root_dir: &str) -> Vec<Directory> {
-    let mut directories = Vec::new();
-
-    if let Ok(entries) = fs::read_dir(root_dir) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.is_dir() {
-                    let name = path.file_name().unwrap().to_string_lossy().to_string();
-                    if name.starts_with(".") {
-                        continue;
-                    }
-                    let files = list_files_in_directory(path.to_str().unwrap());
-                    directories.push(Directory {
-                        name,
-                        path: path.to_str().unwrap().to_string(),
-                        files,
-                        sub_directories: Vec::new(),
-                        shorthand_id: "".to_string(),
-                    });
-                }
-            }
-        }
-    }
-
-    directories
 }
 
-fn list_files_in_directory(directory_path: &str) -> Vec<DirectoryFile> {
-    let mut files = Vec::new();
-
-    if let Ok(entries) = fs::read_dir(directory_path) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.is_file() && path.file_name().unwrap().to_str().unwrap().ends_with(".md") {
-                    let name = path.file_name().unwrap().to_string_lossy().to_string();
-                    files.push(DirectoryFile {
-                        name,
-                        path: path.to_str().unwrap().to_string(),
-                        shorthand_id: "".to_string(),
-                    });
-                }
-            }
-        }
-    }
-
-    files
-}
-
-fn read_cognitio_yaml() -> std::io::Result<CognitioConfig> {
-    let home = cognitio_home_dir();
-    let yaml_path = PathBuf::from(home).join("cognitio.yaml");
-    let mut file = File::open(yaml_path)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    let manifest: CognitioConfig = serde_yaml::from_str(&contents).unwrap();
-    Ok(manifest)
-}
-
-fn cognitio_home_dir() -> String {
-    let home_dir = env::var("HOME").unwrap();
-    if home_dir.is_empty() {
-        error!("Error reading environment variable HOME: Empty or not exist");
-    }
-
-    match env::var("COGNITIO_HOME") {
-        Ok(value) => value,
-        Err(_e) => PathBuf::from(home_dir)
-            .join(".config")
-            .join("cognitio")
-            .to_str()
-            .unwrap_or_default()
-            .to_string(),
-    }
+fn list_cheatsheet_directories() -> io::Result<Vec<Directory>> {
+    let conf = read_cognitio_yaml()?;
+    Ok(library::list_cheatsheet_directories(&conf))
 }
@@ -0,0 +1,246 @@
+use crate::text::{self, sanitize, truncate, wrap_words};
+use cognitio_core::snippet::{Block, Cheatsheet, CodeBlock, Snippet};
+use std::io::{self, Write};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Style, Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
+use unicode_width::UnicodeWidthChar;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const UNDERLINE: &str = "\x1b[4m";
+const MAGENTA: &str = "\x1b[35m";
+const THEME: &str = "base16-ocean.dark";
+
+/// Renders cheatsheets in the terminal the way the app shows them: every h3 section as a
+/// bordered card, with syntax highlighted code blocks.
+pub struct CardRenderer {
+    syntaxes: SyntaxSet,
+    theme: Theme,
+    width: usize,
+    color: bool,
+}
+
+impl CardRenderer {
+    /// `width` is the width of a card in columns. Without `color` no escape sequences are written.
+    pub fn new(width: usize, color: bool) -> CardRenderer {
+        let mut themes = ThemeSet::load_defaults();
+        CardRenderer {
+            syntaxes: SyntaxSet::load_defaults_newlines(),
+            theme: themes.themes.remove(THEME).unwrap_or_default(),
+            width: width.max(10),
+            color,
+        }
+    }
+
+    pub fn render_cheatsheet(
+        &self,
+        cheatsheet: &Cheatsheet,
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        let title = cheatsheet.title.as_ref().unwrap_or(&cheatsheet.name);
+        writeln!(out, "{}", self.paint(&sanitize(title), &[BOLD, MAGENTA]))?;
+        writeln!(out, "{}", self.paint(&sanitize(&cheatsheet.path), &[DIM]))?;
+        writeln!(out)?;
+
+        for group in &cheatsheet.groups {
+            if let Some(title) = &group.title {
+                writeln!(out, "{}", self.paint(&sanitize(title), &[BOLD, UNDERLINE]))?;
+                writeln!(out)?;
+            }
+            // Text and code before the first card aren't in a card of their own.
+            for block in &group.intro {
+                match block {
+                    Block::Prose(prose) => {
+                        for line in prose.markdown.lines() {
+                            for row in wrap_words(line, self.width) {
+                                writeln!(out, "{row}")?;
+                            }
+                        }
+                    }
+                    Block::Code(code) => {
+                        for (row, _) in self.code_rows(code, self.width) {
+                            writeln!(out, "{row}")?;
+                        }
+                    }
+                }
+                writeln!(out)?;
+            }
+            for section in &group.sections {
+                self.render_section(section, out)?;
+                writeln!(out)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn render_section(&self, section: &Snippet, out: &mut impl Write) -> io::Result<()> {
+        // Card text width: the card width minus the borders and one space padding on each side.
+        let text_width = self.width - 4;
+
+        let title = truncate(&sanitize(&section.title), self.width - 6);
+        let fill = self.width.saturating_sub(text::width(&title) + 5);
+        writeln!(
+            out,
+            "{}{}{}",
+            self.paint("╭─ ", &[DIM]),
+            self.paint(&title, &[BOLD]),
+            self.paint(&format!(" {}╮", "─".repeat(fill)), &[DIM])
+        )?;
+
+        for (index, block) in section.blocks.iter().enumerate() {
+            if index > 0 {
+                self.write_row(out, "", 0)?;
+            }
+            match block {
+                Block::Prose(prose) => {
+                    for line in prose.markdown.lines() {
+                        for row in wrap_words(line, text_width) {
+                            let width = text::width(&row);
+                            self.write_row(out, &row, width)?;
+                        }
+                    }
+                }
+                Block::Code(code) => {
+                    for (row, width) in self.code_rows(code, text_width) {
+                        self.write_row(out, &row, width)?;
+                    }
+                }
+            }
+        }
+
+        writeln!(
+            out,
+            "{}",
+            self.paint(&format!("╰{}╯", "─".repeat(self.width - 2)), &[DIM])
+        )
+    }
+
+    /// Writes one row of card content. `width` is the display width of `content` without escape
+    /// sequences, used to pad the row up to the right border.
+    fn write_row(&self, out: &mut impl Write, content: &str, width: usize) -> io::Result<()> {
+        let padding = (self.width - 4).saturating_sub(width);
+        writeln!(
+            out,
+            "{}{}{}{}",
+            self.paint("│ ", &[DIM]),
+            content,
+            " ".repeat(padding),
+            self.paint(" │", &[DIM])
+        )
+    }
+
+    /// Highlights a code block and breaks it into rows of at most `text_width` columns.
+    /// Returns each row with its display width.
+    fn code_rows(&self, code: &CodeBlock, text_width: usize) -> Vec<(String, usize)> {
+        let syntax = code
+            .language
+            .as_deref()
+            .and_then(|language| self.syntaxes.find_syntax_by_token(language))
+            .unwrap_or_else(|| self.syntaxes.find_syntax_plain_text());
+        let mut highlighter = HighlightLines::new(syntax, &self.theme);
+        let mut rows = Vec::new();
+
+        for line in LinesWithEndings::from(&code.code) {
+            let regions: Vec<(Style, String)> =
+                match highlighter.highlight_line(line, &self.syntaxes) {
+                    Ok(regions) => regions
+                        .into_iter()
+                        .map(|(style, text)| (style, sanitize(text)))
+                        .collect(),
+                    Err(_) => vec![(Style::default(), sanitize(line))],
+                };
+
+            for row in wrap_regions(regions, text_width) {
+                let width: usize = row.iter().map(|(_, text)| text::width(text)).sum();
+                let rendered = if self.color {
+                    let borrowed: Vec<(Style, &str)> = row
+                        .iter()
+                        .map(|(style, text)| (*style, text.as_str()))
+                        .collect();
+                    format!("{}{}", as_24_bit_terminal_escaped(&borrowed, false), RESET)
+                } else {
+                    row.iter().map(|(_, text)| text.as_str()).collect()
+                };
+                rows.push((rendered, width));
+            }
+        }
+
+        rows
+    }
+
+    fn paint(&self, text: &str, codes: &[&str]) -> String {
+        if self.color {
+            format!("{}{}{}", codes.concat(), text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// Breaks highlighted text into rows of at most `width` columns, keeping the style of each piece.
+fn wrap_regions(regions: Vec<(Style, String)>, width: usize) -> Vec<Vec<(Style, String)>> {
+    let mut rows = vec![Vec::new()];
+    let mut used = 0;
+
+    for (style, text) in regions {
+        let mut piece = String::new();
+        for c in text.chars() {
+            let w = c.width().unwrap_or(0);
+            if used + w > width && used > 0 {
+                let row = rows.last_mut().unwrap();
+                if !piece.is_empty() {
+                    row.push((style, std::mem::take(&mut piece)));
+                }
+                rows.push(Vec::new());
+                used = 0;
+            }
+            piece.push(c);
+            used += w;
+        }
+        if !piece.is_empty() {
+            rows.last_mut().unwrap().push((style, piece));
+        }
+    }
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cognitio_core::snippet::parse_cheatsheet;
+
+    fn render(markdown: &str) -> String {
+        let cheatsheet = parse_cheatsheet("/lib/docker.md", markdown);
+        let mut out = Vec::new();
+        CardRenderer::new(40, false)
+            .render_cheatsheet(&cheatsheet, &mut out)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn renders_code_before_the_first_card() {
+        let shown = render(
+            "# Docker\n\nSetup first:\n\n```bash\nexport DOCKER_HOST=unix:///run/docker.sock\n```\n\n\
+             ## Containers\n\n```bash\ndocker ps -a\n```\n\n### Run\n\n```bash\ndocker run -it ubuntu\n```\n",
+        );
+        let lines: Vec<&str> = shown.lines().collect();
+        assert_eq!(lines[0], "Docker");
+        assert_eq!(lines[1], "/lib/docker.md");
+        assert!(lines.contains(&"Setup first:"));
+        // Wrapped at the card width.
+        assert!(lines.contains(&"export DOCKER_HOST=unix:///run/docker.so"));
+        assert!(lines.contains(&"ck"));
+        assert!(lines.contains(&"docker ps -a"));
+
+        let card = shown.find("╭─ Run").unwrap();
+        assert!(shown.find("docker ps -a").unwrap() < card);
+        assert!(shown[card..].contains("│ docker run -it ubuntu"));
+        assert!(lines.iter().all(|line| text::width(line) <= 40));
+    }
+}
@@ -0,0 +1,145 @@
+use cognitio_core::config::read_cognitio_yaml;
+use cognitio_core::library::list_cheatsheet_directories;
+use cognitio_core::shorthand::load_and_assign_shorthand_ids;
+use cognitio_core::snippet::{Cheatsheet, CodeBlock, Snippet};
+use log::warn;
+use std::io;
+use std::path::Path;
+
+pub fn run(id: &str) -> io::Result<()> {
+    println!("{}", resolve_shorthand_id(id)?);
+    Ok(())
+}
+
+/// Finds the path of a cheatsheet file or directory by its shorthand ID, as printed by
+/// `cognitio ls`.
+pub fn resolve_shorthand_id(id: &str) -> io::Result<String> {
+    let conf = read_cognitio_yaml()?;
+    let mut dirs = list_cheatsheet_directories(&conf);
+    let ids = load_and_assign_shorthand_ids(&mut dirs)?;
+
+    ids.resolve(id).map(|path| path.to_string()).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No cheatsheet or directory with ID '{id}'"),
+        )
+    })
+}
+
+/// Finds the cheatsheet file for a shorthand ID or a path.
+pub fn resolve_cheatsheet_file(id_or_path: &str) -> io::Result<String> {
+    if Path::new(id_or_path).is_file() {
+        return Ok(id_or_path.to_string());
+    }
+
+    let path = resolve_shorthand_id(id_or_path).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No cheatsheet with ID or path '{id_or_path}'"),
+        )
+    })?;
+    if !Path::new(&path).is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("'{id_or_path}' is a directory, not a cheatsheet: {path}"),
+        ));
+    }
+    Ok(path)
+}
+
+/// Finds a section of a cheatsheet by its title, a unique part of its title or its 1-based
+/// position in the file.
+pub fn resolve_section<'a>(cheatsheet: &'a Cheatsheet, query: &str) -> io::Result<&'a Snippet> {
+    if let Some(section) = cheatsheet.find_section(query) {
+        return Ok(section);
+    }
+
+    let needle = query.trim().to_lowercase();
+    let candidates: Vec<&Snippet> = cheatsheet
+        .sections()
+        .filter(|section| section.title.to_lowercase().contains(&needle))
+        .collect();
+    match candidates.as_slice() {
+        [section] => return Ok(section),
+        [] => {}
+        _ => {
+            let titles: Vec<&str> = candidates
+                .iter()
+                .map(|section| section.title.as_str())
+                .collect();
+            return Err(io::Error::other(format!(
+                "'{}' matches several sections in {}:\n  {}",
+                query,
+                cheatsheet.path,
+                titles.join("\n  ")
+            )));
+        }
+    }
+
+    if let Ok(position) = query.trim().parse::<usize>() {
+        if let Some(section) = position
+            .checked_sub(1)
+            .and_then(|index| cheatsheet.sections().nth(index))
+        {
+            return Ok(section);
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("No section '{}' in {}", query, cheatsheet.path),
+    ))
+}
+
+/// Which code blocks of a section to use: the `nth` (1-based), all of them, or the first one.
+pub fn select_code_blocks(
+    section: &Snippet,
+    nth: Option<usize>,
+    all: bool,
+) -> io::Result<Vec<&CodeBlock>> {
+    let blocks: Vec<&CodeBlock> = section.code_blocks().collect();
+    if blocks.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Section '{}' has no code blocks", section.title),
+        ));
+    }
+
+    if all {
+        return Ok(blocks);
+    }
+
+    match nth {
+        Some(n) => match n.checked_sub(1).and_then(|index| blocks.get(index)) {
+            Some(block) => Ok(vec![*block]),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "Section '{}' has {} code block(s), there is no block {}",
+                    section.title,
+                    blocks.len(),
+                    n
+                ),
+            )),
+        },
+        None => {
+            if blocks.len() > 1 {
+                warn!(
+                    "Section '{}' has {} code blocks, using the first. Use --nth or --all to pick others.",
+                    section.title,
+                    blocks.len()
+                );
+            }
+            Ok(vec![blocks[0]])
+        }
+    }
+}
+
+/// Joins code blocks into one text, separated by a blank line and without a trailing newline.
+pub fn join_code_blocks(blocks: &[&CodeBlock]) -> String {
+    blocks
+        .iter()
+        .map(|block| block.code.trim_end_matches('\n'))
+        .collect::<Vec<&str>>()
+        .join("\n\n")
+}
@@ -0,0 +1,373 @@
+use crate::fill::fill_code;
+use crate::resolve::{
+    join_code_blocks, resolve_cheatsheet_file, resolve_section, select_code_blocks,
+};
+use cognitio_core::config::{read_cognitio_yaml, DEFAULT_RUNNERS};
+use cognitio_core::snippet::load_cheatsheet_file;
+use log::debug;
+use std::env;
+use std::fs::{self, DirBuilder, OpenOptions};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Runs the code of a section and returns the exit code of the runner.
+pub fn run(
+    id: &str,
+    section: &str,
+    nth: Option<usize>,
+    all: bool,
+    dry_run: bool,
+    yes: bool,
+    vars: &[(String, String)],
+) -> io::Result<i32> {
+    let path = resolve_cheatsheet_file(id)?;
+    let cheatsheet = load_cheatsheet_file(&path)?;
+    let section = resolve_section(&cheatsheet, section)?;
+    let blocks = select_code_blocks(section, nth, all)?;
+
+    let language = blocks[0].language.clone().unwrap_or_default();
+    if let Some(other) = blocks
+        .iter()
+        .find(|block| block.language.clone().unwrap_or_default() != language)
+    {
+        return Err(io::Error::other(format!(
+            "Code blocks in '{}' are in different languages ({} and {}), pick one with --nth",
+            section.title,
+            display_language(&language),
+            display_language(other.language.as_deref().unwrap_or_default()),
+        )));
+    }
+
+    let conf = read_cognitio_yaml()?;
+    let runner = conf.runner_for(&language).ok_or_else(|| {
+        let mut known: Vec<&str> = conf.runners.keys().map(|lang| lang.as_str()).collect();
+        known.extend(DEFAULT_RUNNERS.iter().map(|(lang, _)| *lang));
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "No runner for {}. Add one under runners: in cognitio.yaml. Runners exist for: {}",
+                display_language(&language),
+                known.join(", ")
+            ),
+        )
+    })?;
+
+    let code = fill_code(&join_code_blocks(&blocks), vars)?;
+    let invocation = Invocation::new(runner, &code, &language)?;
+
+    let mut stderr = io::stderr().lock();
+    writeln!(stderr, "$ {}", invocation.describe())?;
+    for line in code.lines() {
+        writeln!(stderr, "  {line}")?;
+    }
+    drop(stderr);
+
+    if dry_run {
+        return Ok(0);
+    }
+    if !yes && !confirm()? {
+        eprintln!("Not running");
+        return Ok(1);
+    }
+
+    let status = invocation.spawn()?;
+    Ok(exit_code(status))
+}
+
+/// How the code is handed to a runner.
+enum CodeInput {
+    /// As the last argument.
+    Argument,
+    /// On the runner's stdin.
+    Stdin,
+    /// In a temporary file whose path replaces `{file}`.
+    File(TempFile),
+}
+
+/// A file in a directory of its own in the temp directory, that only the current user can read.
+/// The directory is created with a name nobody can guess and claim first, and is removed with
+/// the file when this is dropped.
+struct TempFile {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl TempFile {
+    fn new(extension: Option<&str>) -> io::Result<TempFile> {
+        let mut builder = DirBuilder::new();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+            builder.mode(0o700);
+        }
+
+        let name = match extension {
+            Some(extension) => format!("snippet.{extension}"),
+            None => "snippet".to_string(),
+        };
+        let mut attempt = 0;
+        loop {
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.subsec_nanos());
+            let dir = env::temp_dir().join(format!(
+                "cognitio-run-{}-{nanos:x}-{attempt}",
+                std::process::id()
+            ));
+            match builder.create(&dir) {
+                Ok(()) => {
+                    return Ok(TempFile {
+                        path: dir.join(&name),
+                        dir,
+                    })
+                }
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists && attempt < 100 => {
+                    attempt += 1
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    fn write(&self, contents: &str) -> io::Result<()> {
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options.open(&self.path)?.write_all(contents.as_bytes())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// The file extension for code in `language`, for runners that go by it.
+fn extension_for(language: &str) -> Option<&str> {
+    let extension = match language {
+        "" => return None,
+        "bash" | "sh" | "shell" | "zsh" | "fish" => language,
+        "python" | "python3" => "py",
+        "javascript" | "node" => "js",
+        "typescript" => "ts",
+        "ruby" => "rb",
+        "rust" => "rs",
+        "perl" => "pl",
+        "powershell" | "pwsh" => "ps1",
+        other => other,
+    };
+    extension
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric())
+        .then_some(extension)
+}
+
+struct Invocation {
+    program: String,
+    args: Vec<String>,
+    input: CodeInput,
+    code: String,
+}
+
+impl Invocation {
+    fn new(runner: &str, code: &str, language: &str) -> io::Result<Invocation> {
+        let mut words = runner.split_whitespace().map(|word| word.to_string());
+        let program = words
+            .next()
+            .ok_or_else(|| io::Error::other(format!("The runner '{runner}' is empty")))?;
+        let mut args: Vec<String> = words.collect();
+
+        let input = if args.iter().any(|arg| arg.contains("{file}")) {
+            let file = TempFile::new(extension_for(language))?;
+            let file_path = file.path.to_string_lossy().to_string();
+            args.iter_mut()
+                .for_each(|arg| *arg = arg.replace("{file}", &file_path));
+            CodeInput::File(file)
+        } else if args.last().is_some_and(|arg| arg == "-") {
+            CodeInput::Stdin
+        } else {
+            CodeInput::Argument
+        };
+
+        Ok(Invocation {
+            program,
+            args,
+            input,
+            code: code.to_string(),
+        })
+    }
+
+    /// The command line as it will be run, with the code left out.
+    fn describe(&self) -> String {
+        let mut line = vec![self.program.clone()];
+        line.extend(self.args.iter().cloned());
+        match self.input {
+            CodeInput::Argument => line.push("<code>".to_string()),
+            CodeInput::Stdin => line.push("< <code>".to_string()),
+            CodeInput::File(_) => {}
+        }
+        line.join(" ")
+    }
+
+    fn spawn(&self) -> io::Result<ExitStatus> {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args);
+        debug!("Running {}", self.describe());
+
+        let status = match &self.input {
+            CodeInput::Argument => command.arg(&self.code).status(),
+            CodeInput::File(file) => {
+                file.write(&self.code)?;
+                command.status()
+            }
+            CodeInput::Stdin => {
+                let mut child = command.stdin(Stdio::piped()).spawn()?;
+                let written = child.stdin.take().map_or(Ok(()), |mut stdin| {
+                    stdin
+                        .write_all(self.code.as_bytes())
+                        .and_then(|()| stdin.write_all(b"\n"))
+                });
+                // The runner is waited for even when writing failed, so its exit code isn't lost.
+                // A runner that exits without reading all of its stdin closes the pipe early.
+                let status = child.wait();
+                match written {
+                    Err(error) if error.kind() != io::ErrorKind::BrokenPipe => {
+                        status.and(Err(error))
+                    }
+                    _ => status,
+                }
+            }
+        };
+
+        status.map_err(|error| {
+            io::Error::new(
+                error.kind(),
+                format!("Failed to run {}: {error}", self.program),
+            )
+        })
+    }
+}
+
+/// Asks whether to run. Without a terminal to ask on, `--yes` is required, since stdin belongs to
+/// the runner.
+fn confirm() -> io::Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Err(io::Error::other(
+            "Not asking for confirmation since stdin isn't a terminal, use --yes to run anyway",
+        ));
+    }
+
+    eprint!("Run? [y/N] ");
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+fn exit_code(status: ExitStatus) -> i32 {
+    if let Some(code) = status.code() {
+        return code;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    1
+}
+
+fn display_language(language: &str) -> String {
+    if language.is_empty() {
+        "code blocks without a language".to_string()
+    } else {
+        format!("'{language}'")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for the output of the code run by a test.
+    fn setup(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("cognitio-run-test-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn run(runner: &str, code: &str) -> i32 {
+        let invocation = Invocation::new(runner, code, "sh").unwrap();
+        exit_code(invocation.spawn().unwrap())
+    }
+
+    #[test]
+    fn passes_code_as_the_last_argument() {
+        let dir = setup("argument");
+        let out = dir.join("out");
+        let code = format!("echo \"$0\" > '{}'; exit 3", out.display());
+        let invocation = Invocation::new("sh -c", &code, "sh").unwrap();
+        assert_eq!(invocation.describe(), "sh -c <code>");
+        assert!(matches!(invocation.input, CodeInput::Argument));
+
+        assert_eq!(run("sh -c", &code), 3);
+        assert_eq!(fs::read_to_string(out).unwrap(), "sh\n");
+    }
+
+    #[test]
+    fn passes_code_on_stdin() {
+        let dir = setup("stdin");
+        let out = dir.join("out");
+        let invocation = Invocation::new("sh -", "true", "sh").unwrap();
+        assert_eq!(invocation.describe(), "sh - < <code>");
+        assert!(matches!(invocation.input, CodeInput::Stdin));
+
+        let code = format!("echo piped > '{}'\nexit 4", out.display());
+        assert_eq!(run("sh -", &code), 4);
+        assert_eq!(fs::read_to_string(out).unwrap(), "piped\n");
+    }
+
+    #[test]
+    fn keeps_the_exit_code_when_stdin_is_not_read() {
+        // More than a pipe holds, so writing fails once the runner has exited.
+        let code = "#".repeat(1 << 20);
+        assert_eq!(run("false -", &code), 1);
+        assert_eq!(run("true -", &code), 0);
+    }
+
+    #[test]
+    fn passes_code_in_a_file() {
+        let dir = setup("file");
+        let out = dir.join("out");
+        let invocation = Invocation::new("sh {file}", "true", "bash").unwrap();
+        let CodeInput::File(file) = &invocation.input else {
+            panic!("not a file invocation");
+        };
+        let file_dir = file.dir.clone();
+        assert_eq!(file.path.extension().unwrap(), "bash");
+        assert_eq!(invocation.args, [file.path.to_string_lossy()]);
+        assert_eq!(invocation.describe(), format!("sh {}", file.path.display()));
+        drop(invocation);
+        // The file is removed with its directory.
+        assert!(!file_dir.exists());
+
+        let code = format!("echo \"$0\" > '{}'\nexit 6", out.display());
+        assert_eq!(run("sh {file}", &code), 6);
+        let script = fs::read_to_string(out).unwrap();
+        assert!(script.trim_end().ends_with("snippet.sh"));
+    }
+
+    #[test]
+    fn reports_signals_like_a_shell() {
+        assert_eq!(run("sh -c", "kill -TERM $$"), 128 + 15);
+    }
+}
@@ -0,0 +1,81 @@
+use cognitio_core::config::read_cognitio_yaml;
+use cognitio_core::search::{SearchHit, SearchIndex, SearchMatch};
+use std::io::{self, IsTerminal};
+
+/// How many matching lines to show below each hit.
+const MAX_LINES_PER_HIT: usize = 3;
+
+pub fn run(query: &[String], limit: usize, json: bool) -> io::Result<()> {
+    let conf = read_cognitio_yaml()?;
+    let index = SearchIndex::build(&conf);
+    let hits = index.search(&query.join(" "), limit);
+
+    if json {
+        let output = serde_json::to_string_pretty(&hits).map_err(io::Error::other)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    if hits.is_empty() {
+        eprintln!("No snippets matched");
+        return Ok(());
+    }
+
+    let highlight = io::stdout().is_terminal();
+    for hit in &hits {
+        print_hit(hit, highlight);
+    }
+
+    Ok(())
+}
+
+fn print_hit(hit: &SearchHit, highlight: bool) {
+    match &hit.group {
+        Some(group) => println!("{}:{}  {} › {}", hit.path, hit.line, group, hit.section),
+        None => println!("{}:{}  {}", hit.path, hit.line, hit.section),
+    }
+
+    let mut printed_lines = Vec::new();
+    for m in &hit.matches {
+        let Some(line) = m.line else { continue };
+        if line == hit.line || printed_lines.contains(&line) {
+            continue;
+        }
+        if printed_lines.len() == MAX_LINES_PER_HIT {
+            break;
+        }
+        printed_lines.push(line);
+
+        let spans: Vec<&SearchMatch> = hit
+            .matches
+            .iter()
+            .filter(|other| other.line == Some(line) && other.field == m.field)
+            .collect();
+        println!(
+            "  {:>5}: {}",
+            line,
+            mark_matches(&m.text, &spans, highlight)
+        );
+    }
+}
+
+fn mark_matches(text: &str, spans: &[&SearchMatch], highlight: bool) -> String {
+    if !highlight {
+        return text.trim().to_string();
+    }
+
+    let mut marked = String::new();
+    let mut position = 0;
+    for span in spans {
+        if span.start < position || span.end > text.len() {
+            continue;
+        }
+        marked.push_str(&text[position..span.start]);
+        marked.push_str("\x1b[1;33m");
+        marked.push_str(&text[span.start..span.end]);
+        marked.push_str("\x1b[0m");
+        position = span.end;
+    }
+    marked.push_str(&text[position..]);
+    marked.trim().to_string()
+}
@@ -0,0 +1,59 @@
+use cognitio_core::snippet::{load_cheatsheet_file, Block, Cheatsheet};
+use std::io;
+use std::path::Path;
+
+pub fn run(path: &Path, json: bool) -> io::Result<()> {
+    let cheatsheet = load_cheatsheet_file(&path.to_string_lossy())?;
+
+    if json {
+        let output = serde_json::to_string_pretty(&cheatsheet).map_err(io::Error::other)?;
+        println!("{output}");
+    } else {
+        print_sections(&cheatsheet);
+    }
+
+    Ok(())
+}
+
+fn print_sections(cheatsheet: &Cheatsheet) {
+    println!(
+        "{}",
+        cheatsheet.title.as_deref().unwrap_or(&cheatsheet.name)
+    );
+
+    for group in &cheatsheet.groups {
+        let indent = match (&group.title, group.line) {
+            (Some(title), Some(line)) => {
+                println!("  {title} (line {line})");
+                "    "
+            }
+            _ => "  ",
+        };
+
+        for section in &group.sections {
+            let languages: Vec<&str> = section
+                .blocks
+                .iter()
+                .filter_map(|block| match block {
+                    Block::Code(code) => code.language.as_deref(),
+                    Block::Prose(_) => None,
+                })
+                .collect();
+
+            if languages.is_empty() {
+                println!(
+                    "{indent}{} (lines {}-{})",
+                    section.title, section.start_line, section.end_line
+                );
+            } else {
+                println!(
+                    "{indent}{} (lines {}-{}) [{}]",
+                    section.title,
+                    section.start_line,
+                    section.end_line,
+                    languages.join(", ")
+                );
+            }
+        }
+    }
+}
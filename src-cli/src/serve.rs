@@ -0,0 +1,33 @@
+use cognitio_core::config::read_cognitio_yaml;
+use cognitio_core::serve::{ServeOptions, Server};
+use std::io::{self, Write};
+
+pub fn run(bind: &str, token: Option<String>, writable: bool) -> io::Result<()> {
+    let conf = read_cognitio_yaml()?;
+    let has_token = token.is_some();
+    let server = Server::bind(
+        conf,
+        bind,
+        ServeOptions {
+            token,
+            writable,
+            ..ServeOptions::default()
+        },
+    )?;
+    let addr = server.local_addr()?;
+
+    println!("Serving Cognitio on http://{addr}");
+    if has_token {
+        println!("Browsers need ?token=<token> on the first page they open");
+    } else if !addr.ip().is_loopback() {
+        eprintln!(
+            "Warning: anyone who can reach {addr} can read the cheatsheets, consider --token"
+        );
+    }
+    if writable {
+        println!("Cheatsheets can be changed through the API");
+    }
+    io::stdout().flush()?;
+
+    server.run()
+}
@@ -0,0 +1,55 @@
+use crate::fill::fill_code;
+use crate::render::CardRenderer;
+use crate::resolve::{resolve_cheatsheet_file, resolve_section};
+use cognitio_core::snippet::load_cheatsheet_file;
+use ratatui::crossterm::terminal;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+
+/// Card width when the output isn't a terminal.
+const DEFAULT_WIDTH: usize = 80;
+
+pub fn run(
+    id: &str,
+    section: Option<&str>,
+    raw: bool,
+    vars: &[(String, String)],
+) -> io::Result<()> {
+    let path = resolve_cheatsheet_file(id)?;
+    let cheatsheet = load_cheatsheet_file(&path)?;
+    let mut out = io::stdout().lock();
+
+    if raw {
+        let code: Vec<&str> = match section {
+            Some(query) => resolve_section(&cheatsheet, query)?
+                .code_blocks()
+                .map(|block| block.code.as_str())
+                .collect(),
+            None => cheatsheet
+                .sections()
+                .flat_map(|section| section.code_blocks())
+                .map(|block| block.code.as_str())
+                .collect(),
+        };
+        let code: String = code.concat();
+        write!(out, "{}", fill_code(&code, vars)?)?;
+        return out.flush();
+    }
+
+    let is_terminal = io::stdout().is_terminal();
+    let color = is_terminal && env::var_os("NO_COLOR").is_none();
+    let width = if is_terminal {
+        terminal::size()
+            .map(|(columns, _)| columns as usize)
+            .unwrap_or(DEFAULT_WIDTH)
+    } else {
+        DEFAULT_WIDTH
+    };
+    let renderer = CardRenderer::new(width, color);
+
+    match section {
+        Some(query) => renderer.render_section(resolve_section(&cheatsheet, query)?, &mut out)?,
+        None => renderer.render_cheatsheet(&cheatsheet, &mut out)?,
+    }
+    out.flush()
+}
@@ -0,0 +1,47 @@
+use clap::Subcommand;
+use cognitio_core::config::read_cognitio_yaml;
+use cognitio_core::sources::{sync_sources, SyncOutcome};
+use std::io;
+
+#[derive(Subcommand)]
+pub enum SourcesCommand {
+    /// Clone git sources that aren't cloned yet and fast-forward the others
+    Sync {},
+}
+
+pub fn run(command: &SourcesCommand) -> io::Result<()> {
+    match command {
+        SourcesCommand::Sync {} => sync(),
+    }
+}
+
+fn sync() -> io::Result<()> {
+    let conf = read_cognitio_yaml()?;
+    let results = sync_sources(&conf);
+    if results.is_empty() {
+        println!("There are no git sources in the config");
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for result in &results {
+        let status = match &result.outcome {
+            SyncOutcome::Cloned { commit } => format!("cloned at {commit}"),
+            SyncOutcome::Updated { from, to } => format!("updated {from}..{to}"),
+            SyncOutcome::UpToDate { commit } => format!("up to date at {commit}"),
+            SyncOutcome::Failed { message } => {
+                failed += 1;
+                format!("failed: {message}")
+            }
+        };
+        println!("{} ({}): {status}", result.title, result.git);
+    }
+
+    if failed > 0 {
+        return Err(io::Error::other(format!(
+            "{failed} of {} source(s) failed to sync",
+            results.len()
+        )));
+    }
+    Ok(())
+}
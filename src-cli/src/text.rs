@@ -0,0 +1,93 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+const TAB_WIDTH: usize = 4;
+
+/// Makes text safe to print in a terminal: tabs become spaces and other control characters,
+/// which could move the cursor or change terminal state, are dropped.
+pub fn sanitize(text: &str) -> String {
+    let mut sanitized = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == '\t' {
+            sanitized.push_str(&" ".repeat(TAB_WIDTH));
+        } else if !c.is_control() {
+            sanitized.push(c);
+        }
+    }
+    sanitized
+}
+
+/// Display width of text in terminal columns.
+pub fn width(text: &str) -> usize {
+    text.width()
+}
+
+/// Cuts text to at most `max_width` columns.
+pub fn truncate(text: &str, max_width: usize) -> String {
+    let mut truncated = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > max_width {
+            break;
+        }
+        used += w;
+        truncated.push(c);
+    }
+    truncated
+}
+
+/// Breaks a line of text into rows of at most `width` columns, at any character. Used for code,
+/// where every character matters.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut rows = Vec::new();
+    let mut current = String::new();
+    let mut used = 0;
+
+    for c in sanitize(text).chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > width && !current.is_empty() {
+            rows.push(std::mem::take(&mut current));
+            used = 0;
+        }
+        current.push(c);
+        used += w;
+    }
+    rows.push(current);
+    rows
+}
+
+/// Breaks a line of text into rows of at most `width` columns between words. Words longer than a
+/// row are broken with [`wrap`].
+pub fn wrap_words(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let text = sanitize(text);
+    let indent: String = text.chars().take_while(|c| *c == ' ').collect();
+    let mut rows = Vec::new();
+    let mut current = indent.clone();
+
+    for word in text.split_whitespace() {
+        let separator = if current.trim().is_empty() { "" } else { " " };
+        if current.width() + separator.len() + word.width() <= width {
+            current.push_str(separator);
+            current.push_str(word);
+            continue;
+        }
+
+        if !current.trim().is_empty() {
+            rows.push(std::mem::replace(&mut current, indent.clone()));
+        }
+        if current.width() + word.width() <= width {
+            current.push_str(word);
+        } else {
+            let mut pieces = wrap(word, width.saturating_sub(current.width()).max(1));
+            current.push_str(&pieces.remove(0));
+            for piece in pieces {
+                rows.push(std::mem::replace(&mut current, piece));
+            }
+        }
+    }
+
+    rows.push(current);
+    rows
+}
@@ -0,0 +1,363 @@
+use cognitio_core::library::sort_directories_and_files;
+use cognitio_core::snippet::{load_cheatsheet_file, Cheatsheet, CodeBlock};
+use cognitio_core::Directory;
+use log::error;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Focus {
+    Tree,
+    Cards,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Normal,
+    Filter,
+}
+
+/// Something the event loop has to do outside of the app state.
+pub enum Action {
+    Copy(String),
+}
+
+/// A visible row in the source/directory tree.
+pub struct TreeRow {
+    pub depth: usize,
+    pub name: String,
+    pub path: String,
+    pub is_dir: bool,
+    pub expanded: bool,
+}
+
+pub struct App {
+    sources: Vec<Directory>,
+    expanded: HashSet<String>,
+    pub rows: Vec<TreeRow>,
+    pub selected: usize,
+    pub filter: String,
+    pub mode: Mode,
+    pub focus: Focus,
+    /// Cheatsheets shown on the right: the selected file, or every file in the selected directory.
+    pub cheatsheets: Vec<Cheatsheet>,
+    /// Index of the focused code block among all code blocks in `cheatsheets`.
+    pub focused_block: usize,
+    /// First visible line of the cards pane.
+    pub scroll: usize,
+    pub page_height: usize,
+    pub status: Option<String>,
+    pub should_quit: bool,
+}
+
+impl App {
+    pub fn new(sources: Vec<Directory>) -> App {
+        let sources = sort_directories_and_files(&sources);
+        let expanded = sources.iter().map(|source| source.path.clone()).collect();
+        let mut app = App {
+            sources,
+            expanded,
+            rows: Vec::new(),
+            selected: 0,
+            filter: String::new(),
+            mode: Mode::Normal,
+            focus: Focus::Tree,
+            cheatsheets: Vec::new(),
+            focused_block: 0,
+            scroll: 0,
+            page_height: 0,
+            status: None,
+            should_quit: false,
+        };
+        app.rebuild_rows();
+        app.load_selected();
+        app
+    }
+
+    pub fn code_blocks(&self) -> Vec<&CodeBlock> {
+        self.cheatsheets
+            .iter()
+            .flat_map(|cheatsheet| cheatsheet.sections())
+            .flat_map(|section| section.code_blocks())
+            .collect()
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.should_quit = true;
+            return None;
+        }
+
+        self.status = None;
+        match self.mode {
+            Mode::Filter => {
+                self.handle_filter_key(key);
+                None
+            }
+            Mode::Normal => self.handle_normal_key(key),
+        }
+    }
+
+    fn handle_filter_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.filter.clear();
+                self.mode = Mode::Normal;
+                self.rebuild_rows();
+                self.load_selected();
+            }
+            KeyCode::Enter => self.mode = Mode::Normal,
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.rebuild_rows();
+                self.load_selected();
+            }
+            KeyCode::Char(c) => {
+                self.filter.push(c);
+                self.rebuild_rows();
+                self.load_selected();
+            }
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Up => self.move_selection(-1),
+            _ => {}
+        }
+    }
+
+    fn handle_normal_key(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Esc if !self.filter.is_empty() => {
+                self.filter.clear();
+                self.rebuild_rows();
+                self.load_selected();
+            }
+            KeyCode::Char('/') => {
+                self.mode = Mode::Filter;
+                self.focus = Focus::Tree;
+            }
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Tree => Focus::Cards,
+                    Focus::Cards => Focus::Tree,
+                }
+            }
+            KeyCode::Char('j') | KeyCode::Down => self.move_down(1),
+            KeyCode::Char('k') | KeyCode::Up => self.move_up(1),
+            KeyCode::Char('g') | KeyCode::Home => self.move_up(usize::MAX),
+            KeyCode::Char('G') | KeyCode::End => self.move_down(usize::MAX),
+            KeyCode::PageDown => self.scroll_cards(self.page_height as isize),
+            KeyCode::PageUp => self.scroll_cards(-(self.page_height as isize)),
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.scroll_cards(self.page_height as isize / 2)
+            }
+            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.scroll_cards(-(self.page_height as isize / 2))
+            }
+            KeyCode::Enter | KeyCode::Char('l') | KeyCode::Right => self.open_selected(),
+            KeyCode::Char('h') | KeyCode::Left => self.close_selected(),
+            KeyCode::Char('y') | KeyCode::Char('c') => return self.copy_focused_block(),
+            _ => {}
+        }
+        None
+    }
+
+    fn move_down(&mut self, steps: usize) {
+        match self.focus {
+            Focus::Tree => self.move_selection(steps.min(isize::MAX as usize) as isize),
+            Focus::Cards => {
+                let count = self.code_blocks().len();
+                if count > 0 {
+                    self.focused_block = self.focused_block.saturating_add(steps).min(count - 1);
+                }
+            }
+        }
+    }
+
+    fn move_up(&mut self, steps: usize) {
+        match self.focus {
+            Focus::Tree => self.move_selection(-(steps.min(isize::MAX as usize) as isize)),
+            Focus::Cards => self.focused_block = self.focused_block.saturating_sub(steps),
+        }
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let last = self.rows.len() - 1;
+        let selected = if delta < 0 {
+            self.selected.saturating_sub(delta.unsigned_abs())
+        } else {
+            self.selected.saturating_add(delta as usize).min(last)
+        };
+        if selected != self.selected {
+            self.selected = selected;
+            self.load_selected();
+        }
+    }
+
+    fn scroll_cards(&mut self, delta: isize) {
+        self.scroll = if delta < 0 {
+            self.scroll.saturating_sub(delta.unsigned_abs())
+        } else {
+            self.scroll.saturating_add(delta as usize)
+        };
+    }
+
+    fn open_selected(&mut self) {
+        match self.focus {
+            Focus::Cards => {}
+            Focus::Tree => {
+                let Some(row) = self.rows.get(self.selected) else {
+                    return;
+                };
+                if row.is_dir && !row.expanded && self.filter.is_empty() {
+                    self.expanded.insert(row.path.clone());
+                    self.rebuild_rows();
+                } else if !self.cheatsheets.is_empty() {
+                    self.focus = Focus::Cards;
+                }
+            }
+        }
+    }
+
+    fn close_selected(&mut self) {
+        if self.focus == Focus::Cards {
+            self.focus = Focus::Tree;
+            return;
+        }
+
+        let Some(row) = self.rows.get(self.selected) else {
+            return;
+        };
+        if row.is_dir && row.expanded && self.filter.is_empty() {
+            self.expanded.remove(&row.path);
+            self.rebuild_rows();
+            return;
+        }
+
+        // Jump to the parent directory.
+        let depth = row.depth;
+        if let Some(parent) = self.rows[..self.selected]
+            .iter()
+            .rposition(|candidate| candidate.depth < depth)
+        {
+            self.selected = parent;
+            self.load_selected();
+        }
+    }
+
+    fn copy_focused_block(&mut self) -> Option<Action> {
+        let code = self
+            .code_blocks()
+            .get(self.focused_block)
+            .map(|block| block.code.trim_end_matches('\n').to_string());
+
+        match code {
+            Some(code) => {
+                self.status = Some(format!("Copied {} line(s)", code.lines().count()));
+                Some(Action::Copy(code))
+            }
+            None => {
+                self.status = Some("No code block to copy".to_string());
+                None
+            }
+        }
+    }
+
+    fn load_selected(&mut self) {
+        self.cheatsheets.clear();
+        self.focused_block = 0;
+        self.scroll = 0;
+
+        let Some(row) = self.rows.get(self.selected) else {
+            return;
+        };
+
+        let paths: Vec<String> = if row.is_dir {
+            find_directory(&self.sources, &row.path)
+                .map(|dir| dir.files.iter().map(|file| file.path.clone()).collect())
+                .unwrap_or_default()
+        } else {
+            vec![row.path.clone()]
+        };
+
+        for path in paths {
+            match load_cheatsheet_file(&path) {
+                Ok(cheatsheet) => self.cheatsheets.push(cheatsheet),
+                Err(error) => error!("Failed to load cheatsheet {}: {:?}", path, error),
+            }
+        }
+    }
+
+    fn rebuild_rows(&mut self) {
+        let filter = self.filter.to_lowercase();
+        let mut rows = Vec::new();
+        for source in &self.sources {
+            push_rows(&mut rows, source, 0, &filter, &self.expanded);
+        }
+        self.rows = rows;
+        self.selected = self.selected.min(self.rows.len().saturating_sub(1));
+    }
+}
+
+/// Adds the rows for `dir` and its contents. With a filter, only entries whose name contains the
+/// filter, and the directories leading to them, are added, and every directory is expanded.
+/// Returns whether anything was added.
+fn push_rows(
+    rows: &mut Vec<TreeRow>,
+    dir: &Directory,
+    depth: usize,
+    filter: &str,
+    expanded: &HashSet<String>,
+) -> bool {
+    let dir_matches = filter.is_empty() || dir.name.to_lowercase().contains(filter);
+    let is_expanded = !filter.is_empty() || expanded.contains(&dir.path);
+    let dir_index = rows.len();
+    rows.push(TreeRow {
+        depth,
+        name: dir.name.clone(),
+        path: dir.path.clone(),
+        is_dir: true,
+        expanded: is_expanded,
+    });
+
+    let mut has_matches = false;
+    if is_expanded {
+        // Everything below a matching directory is shown.
+        let child_filter = if dir_matches { "" } else { filter };
+
+        for file in &dir.files {
+            let name = file.name.trim_end_matches(".md");
+            if child_filter.is_empty() || name.to_lowercase().contains(child_filter) {
+                has_matches = true;
+                rows.push(TreeRow {
+                    depth: depth + 1,
+                    name: name.to_string(),
+                    path: file.path.clone(),
+                    is_dir: false,
+                    expanded: false,
+                });
+            }
+        }
+        for sub_dir in &dir.sub_directories {
+            has_matches |= push_rows(rows, sub_dir, depth + 1, child_filter, expanded);
+        }
+    }
+
+    if !dir_matches && !has_matches {
+        rows.truncate(dir_index);
+        return false;
+    }
+    true
+}
+
+fn find_directory<'a>(dirs: &'a [Directory], path: &str) -> Option<&'a Directory> {
+    dirs.iter().find_map(|dir| {
+        if dir.path == path {
+            Some(dir)
+        } else {
+            find_directory(&dir.sub_directories, path)
+        }
+    })
+}
@@ -0,0 +1,37 @@
+mod app;
+mod ui;
+
+use crate::clipboard::copy_with_osc52;
+use app::{Action, App};
+use cognitio_core::config::read_cognitio_yaml;
+use cognitio_core::library::list_cheatsheet_directories;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use std::io;
+
+/// Runs the full-screen terminal browser until the user quits.
+pub fn run() -> io::Result<()> {
+    let conf = read_cognitio_yaml()?;
+    let mut app = App::new(list_cheatsheet_directories(&conf));
+
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut app);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(terminal: &mut ratatui::DefaultTerminal, app: &mut App) -> io::Result<()> {
+    while !app.should_quit {
+        terminal.draw(|frame| ui::draw(frame, app))?;
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match app.handle_key(key) {
+                Some(Action::Copy(text)) => copy_with_osc52(&text, &mut io::stdout())?,
+                None => {}
+            }
+        }
+    }
+    Ok(())
+}
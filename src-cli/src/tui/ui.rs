@@ -0,0 +1,233 @@
+use super::app::{App, Focus, Mode};
+use crate::text::{self, truncate, wrap, wrap_words};
+use cognitio_core::snippet::{Block, Cheatsheet};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block as Border, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [main, status] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+    let [tree, cards] =
+        Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)]).areas(main);
+
+    draw_tree(frame, app, tree);
+    draw_cards(frame, app, cards);
+    draw_status(frame, app, status);
+}
+
+fn pane_border(title: &str, focused: bool) -> Border<'_> {
+    let style = if focused {
+        Style::default().fg(Color::Cyan)
+    } else {
+        Style::default().fg(Color::DarkGray)
+    };
+    Border::default()
+        .borders(Borders::ALL)
+        .border_style(style)
+        .title(title)
+}
+
+fn draw_tree(frame: &mut Frame, app: &App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .rows
+        .iter()
+        .map(|row| {
+            let indent = "  ".repeat(row.depth);
+            let (marker, style) = match (row.is_dir, row.expanded) {
+                (true, true) => ("▾ ", Style::default().add_modifier(Modifier::BOLD)),
+                (true, false) => ("▸ ", Style::default().add_modifier(Modifier::BOLD)),
+                (false, _) => ("  ", Style::default()),
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw(indent),
+                Span::raw(marker),
+                Span::styled(text::sanitize(&row.name), style),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(pane_border(" Cheatsheets ", app.focus == Focus::Tree))
+        .highlight_style(Style::default().bg(Color::DarkGray));
+
+    let mut state = ListState::default().with_selected(Some(app.selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_cards(frame: &mut Frame, app: &mut App, area: Rect) {
+    let border = pane_border(" Snippets ", app.focus == Focus::Cards);
+    let inner = border.inner(area);
+    frame.render_widget(border, area);
+
+    let focused = (app.focus == Focus::Cards).then_some(app.focused_block);
+    let (lines, block_offsets) = card_lines(&app.cheatsheets, inner.width as usize, focused);
+
+    app.page_height = inner.height as usize;
+    let focused_offsets = focused.and_then(|index| block_offsets.get(index));
+    if let Some(&(start, end)) = focused_offsets {
+        // Keep the focused code block, and the card heading above it when possible, in view.
+        let start = start.saturating_sub(2);
+        if start < app.scroll {
+            app.scroll = start;
+        } else if end >= app.scroll + app.page_height {
+            app.scroll = (end + 1).saturating_sub(app.page_height).min(start);
+        }
+    }
+    app.scroll = app.scroll.min(lines.len().saturating_sub(1));
+
+    let paragraph = Paragraph::new(lines).scroll((app.scroll as u16, 0));
+    frame.render_widget(paragraph, inner);
+}
+
+fn draw_status(frame: &mut Frame, app: &App, area: Rect) {
+    let line = match (app.mode, &app.status) {
+        (Mode::Filter, _) => Line::from(vec![
+            Span::styled("/", Style::default().fg(Color::Yellow)),
+            Span::raw(app.filter.clone()),
+            Span::styled("█", Style::default().fg(Color::DarkGray)),
+        ]),
+        (Mode::Normal, Some(status)) => Line::from(Span::styled(
+            status.clone(),
+            Style::default().fg(Color::Green),
+        )),
+        (Mode::Normal, None) => {
+            let mut help = String::from(
+                "q quit  / filter  tab switch pane  j/k move  enter open  h back  y copy code",
+            );
+            if !app.filter.is_empty() {
+                help = format!("filter: {}  (esc clears)  {}", app.filter, help);
+            }
+            Line::from(Span::styled(help, Style::default().fg(Color::DarkGray)))
+        }
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+/// Lays out cheatsheets as bordered cards, one per h3 section.
+///
+/// Returns the lines and the first and last line of every code block, so the view can scroll to
+/// the focused block.
+fn card_lines(
+    cheatsheets: &[Cheatsheet],
+    width: usize,
+    focused_block: Option<usize>,
+) -> (Vec<Line<'static>>, Vec<(usize, usize)>) {
+    let mut lines: Vec<Line<'static>> = Vec::new();
+    let mut block_offsets = Vec::new();
+
+    if cheatsheets.is_empty() {
+        lines.push(Line::styled(
+            "Nothing to show",
+            Style::default().fg(Color::DarkGray),
+        ));
+        return (lines, block_offsets);
+    }
+
+    // Inner text width of a card: width minus the borders and one space padding on each side.
+    let text_width = width.saturating_sub(4).max(1);
+    let border_style = Style::default().fg(Color::DarkGray);
+    let prose_style = Style::default();
+    let code_style = Style::default().fg(Color::Cyan);
+    let focused_style = Style::default().fg(Color::Black).bg(Color::Yellow);
+
+    for cheatsheet in cheatsheets {
+        let title = cheatsheet.title.as_ref().unwrap_or(&cheatsheet.name);
+        lines.push(Line::styled(
+            text::sanitize(title),
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::BOLD),
+        ));
+        lines.push(Line::raw(""));
+
+        for group in &cheatsheet.groups {
+            if let Some(title) = &group.title {
+                lines.push(Line::styled(
+                    text::sanitize(title),
+                    Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                ));
+                lines.push(Line::raw(""));
+            }
+            for block in &group.intro {
+                if let Block::Prose(prose) = block {
+                    for text in prose.markdown.lines() {
+                        for wrapped in wrap_words(text, width) {
+                            lines.push(Line::raw(wrapped));
+                        }
+                    }
+                    lines.push(Line::raw(""));
+                }
+            }
+
+            for section in &group.sections {
+                lines.push(card_top(&section.title, width, border_style));
+
+                for (index, block) in section.blocks.iter().enumerate() {
+                    if index > 0 {
+                        lines.push(card_row("", text_width, prose_style, border_style));
+                    }
+                    match block {
+                        Block::Prose(prose) => {
+                            for text in prose.markdown.lines() {
+                                for wrapped in wrap_words(text, text_width) {
+                                    lines.push(card_row(
+                                        &wrapped,
+                                        text_width,
+                                        prose_style,
+                                        border_style,
+                                    ));
+                                }
+                            }
+                        }
+                        Block::Code(code) => {
+                            let is_focused = focused_block == Some(block_offsets.len());
+                            let style = if is_focused {
+                                focused_style
+                            } else {
+                                code_style
+                            };
+                            let start = lines.len();
+                            for text in code.code.trim_end_matches('\n').lines() {
+                                for wrapped in wrap(text, text_width) {
+                                    lines.push(card_row(&wrapped, text_width, style, border_style));
+                                }
+                            }
+                            block_offsets.push((start, lines.len().saturating_sub(1)));
+                        }
+                    }
+                }
+
+                lines.push(card_bottom(width, border_style));
+                lines.push(Line::raw(""));
+            }
+        }
+    }
+
+    (lines, block_offsets)
+}
+
+fn card_top(title: &str, width: usize, style: Style) -> Line<'static> {
+    let title: String = truncate(&text::sanitize(title), width.saturating_sub(6));
+    let fill = width.saturating_sub(text::width(&title) + 5);
+    Line::from(vec![
+        Span::styled("╭─ ", style),
+        Span::styled(title, Style::default().add_modifier(Modifier::BOLD)),
+        Span::styled(format!(" {}╮", "─".repeat(fill)), style),
+    ])
+}
+
+fn card_row(text: &str, text_width: usize, style: Style, border_style: Style) -> Line<'static> {
+    let padding = text_width.saturating_sub(text::width(text));
+    Line::from(vec![
+        Span::styled("│ ", border_style),
+        Span::styled(format!("{}{}", text, " ".repeat(padding)), style),
+        Span::styled(" │", border_style),
+    ])
+}
+
+fn card_bottom(width: usize, style: Style) -> Line<'static> {
+    Line::styled(format!("╰{}╯", "─".repeat(width.saturating_sub(2))), style)
+}
@@ -0,0 +1,65 @@
+use cognitio_core::config::read_cognitio_yaml;
+use cognitio_core::watch::{
+    config_debounce, config_watch_roots, library_events, ChangeEvent, LibraryEvent, WatchService,
+};
+use std::io::{self, Write};
+use std::sync::mpsc::channel;
+
+pub fn run(json: bool) -> io::Result<()> {
+    let conf = read_cognitio_yaml()?;
+    let (sender, receiver) = channel();
+    let watcher = WatchService::start(sender, config_debounce(&conf)).map_err(io::Error::other)?;
+    watcher.set_roots(config_watch_roots(&conf));
+    eprintln!(
+        "Watching {} cheatsheet sources and the config file, press Ctrl+C to stop",
+        conf.cheatsheets.len()
+    );
+
+    let mut stdout = io::stdout().lock();
+    for batch in receiver.iter() {
+        for event in library_events(&batch, &watcher) {
+            match print_event(&mut stdout, event, json) {
+                // Whoever read the output has stopped, like `head` does.
+                Err(error) if error.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+                result => result?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Prints every change as a line of its own. A changed config is printed as `config_changed`,
+/// and its errors, if any, go to stderr since the last good config stays in use.
+fn print_event(out: &mut impl Write, event: LibraryEvent, json: bool) -> io::Result<()> {
+    let changes = match event {
+        LibraryEvent::ConfigChanged { path, .. } => vec![ChangeEvent::ConfigChanged { path }],
+        LibraryEvent::ConfigInvalid { path, errors } => {
+            for error in &errors {
+                eprintln!("Invalid config, keeping the last good one: {error}");
+            }
+            vec![ChangeEvent::ConfigChanged { path }]
+        }
+        LibraryEvent::FilesChanged { events } => events,
+    };
+
+    for change in &changes {
+        if json {
+            let line = serde_json::to_string(change).map_err(io::Error::other)?;
+            writeln!(out, "{line}")?;
+        } else {
+            writeln!(out, "{}", describe(change))?;
+        }
+    }
+    out.flush()
+}
+
+fn describe(change: &ChangeEvent) -> String {
+    match change {
+        ChangeEvent::ConfigChanged { path } => format!("config     {path}"),
+        ChangeEvent::CheatsheetCreated { path } => format!("created    {path}"),
+        ChangeEvent::CheatsheetModified { path } => format!("modified   {path}"),
+        ChangeEvent::CheatsheetRemoved { path } => format!("removed    {path}"),
+        ChangeEvent::CheatsheetRenamed { from, to } => format!("renamed    {from} -> {to}"),
+        ChangeEvent::DirectoryChanged { path } => format!("directory  {path}"),
+    }
+}
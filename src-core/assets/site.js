@@ -0,0 +1,83 @@
+// Copy buttons, search and live reload for cheatsheets exported with `cognitio export html` or
+// viewed with `cognitio serve`.
+(function () {
+	document.querySelectorAll('.mk-copy-btn').forEach((button) => {
+		button.addEventListener('click', () => {
+			const code = button.parentElement.querySelector('code');
+			if (!code) {
+				return;
+			}
+			navigator.clipboard.writeText(code.innerText).then(() => {
+				button.value = 'Copied';
+				setTimeout(() => (button.value = 'Copy'), 1000);
+			});
+		});
+	});
+
+	// Pages served by `cognitio serve` reload when their cheatsheet, the menu or the config changes.
+	if (document.body.dataset.live !== undefined && window.EventSource) {
+		const path = new URLSearchParams(location.search).get('path');
+		const events = new EventSource((document.body.dataset.root || '') + 'api/events');
+		events.addEventListener('cognitio_config_changed', () => location.reload());
+		events.addEventListener('file_changed', (message) => {
+			const changes = JSON.parse(message.data).events;
+			const affected = changes.some(
+				(change) => change.type !== 'cheatsheet_modified' || change.path === path
+			);
+			if (affected) {
+				location.reload();
+			}
+		});
+	}
+
+	const input = document.getElementById('search');
+	const results = document.getElementById('search-results');
+	if (!input || !results) {
+		return;
+	}
+
+	// Links in the index are relative to the root of the site.
+	const root = document.body.dataset.root || '';
+	let entries;
+
+	function loadEntries() {
+		if (!entries) {
+			entries = fetch(root + 'search-index.json')
+				.then((response) => response.json())
+				.catch(() => {
+					results.textContent = 'Search only works when the site is served over HTTP';
+					return [];
+				});
+		}
+		return entries;
+	}
+
+	input.addEventListener('input', () => {
+		const words = input.value.toLowerCase().split(/\s+/).filter(Boolean);
+		loadEntries().then((all) => {
+			if (all.length === 0 && results.textContent) {
+				return;
+			}
+			results.replaceChildren();
+			if (words.length === 0) {
+				return;
+			}
+
+			all
+				.filter((entry) => {
+					const text = `${entry.title} ${entry.cheatsheet} ${entry.text}`.toLowerCase();
+					return words.every((word) => text.includes(word));
+				})
+				.slice(0, 20)
+				.forEach((entry) => {
+					const link = document.createElement('a');
+					link.href = root + entry.url;
+					link.textContent = entry.title;
+					const cheatsheet = document.createElement('span');
+					cheatsheet.textContent = entry.cheatsheet;
+					link.appendChild(cheatsheet);
+					results.appendChild(link);
+				});
+		});
+	});
+})();
@@ -0,0 +1,210 @@
+//! Checking that paths from the frontend stay inside the cheatsheet sources.
+//!
+//! The app reads, writes and opens paths it gets from the webview. A path is only used when it
+//! is inside one of the configured sources or [`cognitio_home_dir`] after symlinks and `..` are
+//! resolved, so a buggy or compromised page can't reach the rest of the file system.
+
+use crate::config::{cognitio_home_dir, CognitioConfig};
+use log::warn;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A path that is outside every allowed root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccessDenied {
+    pub path: String,
+}
+
+impl fmt::Display for AccessDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Access denied to {}: it is outside the cheatsheet sources and COGNITIO_HOME",
+            self.path
+        )
+    }
+}
+
+impl Error for AccessDenied {}
+
+impl From<AccessDenied> for io::Error {
+    fn from(denied: AccessDenied) -> io::Error {
+        io::Error::new(io::ErrorKind::PermissionDenied, denied)
+    }
+}
+
+/// The canonicalized directories paths have to be inside.
+#[derive(Clone, Debug, Default)]
+pub struct AllowedRoots {
+    roots: Vec<PathBuf>,
+}
+
+impl AllowedRoots {
+    /// Canonicalizes the roots. Roots that don't exist can't contain anything and are left out.
+    pub fn new(roots: impl IntoIterator<Item = PathBuf>) -> AllowedRoots {
+        AllowedRoots {
+            roots: roots
+                .into_iter()
+                .filter_map(|root| fs::canonicalize(root).ok())
+                .collect(),
+        }
+    }
+
+    /// The expanded path of every cheatsheet source in the config, and [`cognitio_home_dir`].
+    pub fn from_config(conf: &CognitioConfig) -> AllowedRoots {
+        let mut roots = AllowedRoots::sources(conf);
+        roots
+            .roots
+            .extend(AllowedRoots::new([PathBuf::from(cognitio_home_dir())]).roots);
+        roots
+    }
+
+    /// The expanded path of every cheatsheet source in the config, without the files Cognitio
+    /// keeps in [`cognitio_home_dir`].
+    pub fn sources(conf: &CognitioConfig) -> AllowedRoots {
+        let sources = conf
+            .cheatsheets
+            .iter()
+            .filter_map(|source| match source.expanded_path() {
+                Ok(path) => Some(path),
+                Err(error) => {
+                    warn!(
+                        "Cheatsheet source {} can't be expanded: {error}",
+                        source.path()
+                    );
+                    None
+                }
+            });
+        AllowedRoots::new(sources)
+    }
+
+    /// Canonicalizes an absolute path and returns it if it is inside one of the roots.
+    ///
+    /// A path that doesn't exist yet, like a file about to be created, is checked by its parent
+    /// directory. Relative paths are denied since what they point to depends on the working
+    /// directory.
+    pub fn check(&self, path: impl AsRef<Path>) -> Result<PathBuf, AccessDenied> {
+        let path = path.as_ref();
+        let denied = || AccessDenied {
+            path: path.to_string_lossy().to_string(),
+        };
+        if !path.is_absolute() {
+            return Err(denied());
+        }
+
+        let canonical = match fs::canonicalize(path) {
+            Ok(canonical) => canonical,
+            Err(_) => {
+                let parent = path.parent().ok_or_else(denied)?;
+                let file_name = path.file_name().ok_or_else(denied)?;
+                fs::canonicalize(parent)
+                    .map_err(|_| denied())?
+                    .join(file_name)
+            }
+        };
+
+        if self.roots.iter().any(|root| canonical.starts_with(root)) {
+            Ok(canonical)
+        } else {
+            Err(denied())
+        }
+    }
+
+    /// Like [`AllowedRoots::check`], for code that works with `io::Result` and string paths.
+    pub fn check_str(&self, path: &str) -> io::Result<String> {
+        let canonical = self.check(path)?;
+        Ok(canonical.to_string_lossy().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process;
+
+    /// A fresh directory with a `source` root holding `cheat.md`, and a `secret` file outside it.
+    fn setup(name: &str) -> (PathBuf, AllowedRoots) {
+        let dir = std::env::temp_dir().join(format!("cognitio-access-{}-{name}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("source/sub")).unwrap();
+        fs::write(dir.join("source/cheat.md"), "### Cheat\n").unwrap();
+        fs::write(dir.join("secret"), "secret").unwrap();
+        let roots = AllowedRoots::new([dir.join("source")]);
+        (dir, roots)
+    }
+
+    #[test]
+    fn allows_paths_inside_a_root() {
+        let (dir, roots) = setup("inside");
+        let canonical = fs::canonicalize(dir.join("source/cheat.md")).unwrap();
+        assert_eq!(roots.check(dir.join("source/cheat.md")), Ok(canonical));
+        assert!(roots.check(dir.join("source/sub")).is_ok());
+        assert!(roots.check(dir.join("source")).is_ok());
+    }
+
+    #[test]
+    fn allows_new_files_in_a_root() {
+        let (dir, roots) = setup("new-file");
+        assert!(roots.check(dir.join("source/sub/new.md")).is_ok());
+        assert!(roots.check(dir.join("new.md")).is_err());
+    }
+
+    #[test]
+    fn denies_dot_dot_escapes() {
+        let (dir, roots) = setup("dot-dot");
+        assert!(roots.check(dir.join("source/../secret")).is_err());
+        assert!(roots.check(dir.join("source/sub/../../secret")).is_err());
+        assert!(roots.check(dir.join("source/sub/../cheat.md")).is_ok());
+    }
+
+    #[test]
+    fn denies_absolute_paths_outside_the_roots() {
+        let (dir, roots) = setup("absolute");
+        assert!(roots.check(dir.join("secret")).is_err());
+        assert!(roots.check("/etc/passwd").is_err());
+        assert!(roots.check("/").is_err());
+    }
+
+    #[test]
+    fn denies_siblings_sharing_a_prefix() {
+        let (dir, roots) = setup("prefix");
+        fs::create_dir_all(dir.join("source-other")).unwrap();
+        fs::write(dir.join("source-other/cheat.md"), "").unwrap();
+        assert!(roots.check(dir.join("source-other/cheat.md")).is_err());
+    }
+
+    #[test]
+    fn denies_relative_paths() {
+        let (_dir, roots) = setup("relative");
+        assert!(roots.check("cheat.md").is_err());
+        assert!(roots.check("../secret").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn denies_symlinks_out_of_a_root() {
+        let (dir, roots) = setup("symlink");
+        std::os::unix::fs::symlink(dir.join("secret"), dir.join("source/link.md")).unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("source/sub/up")).unwrap();
+        assert!(roots.check(dir.join("source/link.md")).is_err());
+        assert!(roots.check(dir.join("source/sub/up/secret")).is_err());
+        assert!(roots
+            .check(dir.join("source/sub/up/source/cheat.md"))
+            .is_ok());
+    }
+
+    #[test]
+    fn denied_paths_are_permission_denied_io_errors() {
+        let (dir, roots) = setup("io-error");
+        let error = roots
+            .check_str(&dir.join("secret").to_string_lossy())
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert!(error
+            .get_ref()
+            .is_some_and(|inner| inner.is::<AccessDenied>()));
+    }
+}
@@ -0,0 +1,754 @@
+use log::error;
+use serde::{Deserialize, Serialize};
+use serde_yaml;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Read;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+
+/// The contents of `cognitio.yaml`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct CognitioConfig {
+    /// Command used to open files and directories for editing. See [`crate::editor`] for the
+    /// `{path}` and `{line}` placeholders.
+    pub editor: Option<String>,
+    /// Editor commands for particular file extensions, used instead of `editor`. See
+    /// [`CognitioConfig::editor_for`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub editors: BTreeMap<String, String>,
+    /// The cheatsheet sources to show.
+    pub cheatsheets: Vec<CheatsheetData>,
+    pub styling: Option<Styling>,
+    /// Commands that run code blocks, by fence language. See [`CognitioConfig::runner_for`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub runners: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watch: Option<WatchSettings>,
+}
+
+/// A cheatsheet source, either given as a plain path or as a path with a title.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum CheatsheetData {
+    Simple(String),
+    Git(GitSource),
+    Info(CheatsheetInfo),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CheatsheetInfo {
+    pub title: String,
+    pub path: String,
+    /// How many directory levels below the source root to scan.
+    /// Defaults to [`DEFAULT_MAX_DEPTH`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+}
+
+/// A cheatsheet source in a git repository, cloned into [`crate::sources::sources_cache_dir`] and
+/// updated with `cognitio sources sync`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GitSource {
+    pub title: String,
+    /// Anything `git clone` accepts, including `file://` URLs and paths to local repositories.
+    pub git: String,
+    /// Branch to check out. Defaults to the repository's default branch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Directory inside the repository holding the cheatsheets. Defaults to the repository root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subdir: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+}
+
+/// How many directory levels below a source root are scanned when the source doesn't say.
+pub const DEFAULT_MAX_DEPTH: usize = 10;
+
+impl CheatsheetData {
+    /// The path as written in the config. For git sources this is the repository URL.
+    pub fn path(&self) -> &str {
+        match self {
+            CheatsheetData::Simple(path) => path,
+            CheatsheetData::Git(git) => &git.git,
+            CheatsheetData::Info(info) => &info.path,
+        }
+    }
+
+    /// The source's path as an absolute path, see [`expand_path`]. Relative paths are relative to
+    /// the directory of the config file found by [`locate_config`].
+    pub fn expanded_path(&self) -> Result<PathBuf, String> {
+        self.expanded_path_from(&config_dir())
+    }
+
+    /// Like [`CheatsheetData::expanded_path`], with relative paths relative to `base_dir`. For
+    /// git sources this is the directory in the checkout holding the cheatsheets.
+    pub fn expanded_path_from(&self, base_dir: &Path) -> Result<PathBuf, String> {
+        match self {
+            CheatsheetData::Git(git) => {
+                let checkout = git.checkout_dir();
+                match &git.subdir {
+                    Some(subdir) => Ok(normalize(&checkout.join(subdir.trim_matches('/')))),
+                    None => Ok(checkout),
+                }
+            }
+            _ => expand_path(self.path(), base_dir),
+        }
+    }
+
+    pub fn max_depth(&self) -> usize {
+        let max_depth = match self {
+            CheatsheetData::Simple(_) => None,
+            CheatsheetData::Git(git) => git.max_depth,
+            CheatsheetData::Info(info) => info.max_depth,
+        };
+        max_depth.unwrap_or(DEFAULT_MAX_DEPTH)
+    }
+}
+
+/// Runners used for languages that aren't in the `runners` map.
+pub const DEFAULT_RUNNERS: &[(&str, &str)] = &[
+    ("bash", "bash -c"),
+    ("sh", "sh -c"),
+    ("zsh", "zsh -c"),
+    ("python", "python3 -"),
+    ("py", "python3 -"),
+];
+
+impl CognitioConfig {
+    /// The command that runs code blocks of a fence language, ignoring case. The `runners` map
+    /// wins over [`DEFAULT_RUNNERS`].
+    ///
+    /// The code is passed as the last argument, like `bash -c <code>`. A runner ending in `-`,
+    /// like `python3 -`, gets the code on stdin instead, and `{file}` in a runner is replaced with
+    /// the path of a temporary file holding the code, with the language as its extension.
+    pub fn runner_for(&self, language: &str) -> Option<&str> {
+        self.runners
+            .iter()
+            .find(|(lang, _)| lang.eq_ignore_ascii_case(language))
+            .map(|(_, runner)| runner.as_str())
+            .or_else(|| {
+                DEFAULT_RUNNERS
+                    .iter()
+                    .find(|(lang, _)| lang.eq_ignore_ascii_case(language))
+                    .map(|(_, runner)| *runner)
+            })
+    }
+
+    /// The editor command for a path: the entry in `editors` for its extension, ignoring case and
+    /// a leading dot, or else `editor`.
+    pub fn editor_for(&self, path: &Path) -> Option<&str> {
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        extension
+            .and_then(|extension| {
+                self.editors
+                    .iter()
+                    .find(|(ext, _)| ext.trim_start_matches('.').eq_ignore_ascii_case(extension))
+            })
+            .map(|(_, editor)| editor.as_str())
+            .or(self.editor.as_deref())
+    }
+}
+
+/// How the app watches cheatsheet sources for changes.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WatchSettings {
+    /// How long to wait for more changes before reloading, in milliseconds.
+    pub debounce_ms: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Styling {
+    pub menu: Option<Menu>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Menu {
+    pub width: Option<String>,
+}
+
+/// A problem with a config file, pointing to where in the file it is when that is known.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ConfigError {
+    pub file: String,
+    /// 1-based line.
+    pub line: Option<usize>,
+    /// 1-based column.
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "{}:{line}:{column}: ", self.file)?,
+            (Some(line), None) => write!(f, "{}:{line}: ", self.file)?,
+            _ => write!(f, "{}: ", self.file)?,
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<ConfigError> for std::io::Error {
+    fn from(error: ConfigError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, error)
+    }
+}
+
+/// The result of checking a config file.
+#[derive(Clone, Debug)]
+pub struct ConfigCheck {
+    /// The parsed config, unless the file couldn't be read or parsed.
+    pub config: Option<CognitioConfig>,
+    pub errors: Vec<ConfigError>,
+    /// Problems that don't keep the config from being used, like git sources that aren't cloned
+    /// yet.
+    pub warnings: Vec<ConfigError>,
+}
+
+impl ConfigCheck {
+    pub fn is_valid(&self) -> bool {
+        self.config.is_some() && self.errors.is_empty()
+    }
+}
+
+/// Where the config file was found, see [`locate_config`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigLocation {
+    pub path: PathBuf,
+    pub source: ConfigSource,
+}
+
+/// The step of the discovery chain that picked the config file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// The `--config` command line flag.
+    Flag,
+    /// The `COGNITIO_CONFIG` environment variable.
+    ConfigEnv,
+    /// `cognitio.yaml` in the `COGNITIO_HOME` directory.
+    HomeEnv,
+    /// `cognitio/cognitio.yaml` in `XDG_CONFIG_HOME`.
+    XdgConfigHome,
+    /// `~/.config/cognitio/cognitio.yaml`.
+    Default,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            ConfigSource::Flag => "given with --config".to_string(),
+            ConfigSource::ConfigEnv => "COGNITIO_CONFIG is set".to_string(),
+            ConfigSource::HomeEnv => "COGNITIO_HOME is set".to_string(),
+            ConfigSource::XdgConfigHome => {
+                "XDG_CONFIG_HOME is set and neither COGNITIO_CONFIG nor COGNITIO_HOME is"
+                    .to_string()
+            }
+            ConfigSource::Default => {
+                "none of COGNITIO_CONFIG, COGNITIO_HOME and XDG_CONFIG_HOME are set".to_string()
+            }
+        };
+        write!(f, "{reason}")
+    }
+}
+
+/// Environment variables that override single keys of the config file.
+///
+/// `COGNITIO_CHEATSHEETS` holds a list of paths separated like `PATH` and replaces the
+/// `cheatsheets` list.
+pub const CONFIG_ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("COGNITIO_EDITOR", "editor"),
+    ("COGNITIO_CHEATSHEETS", "cheatsheets"),
+    ("COGNITIO_MENU_WIDTH", "styling.menu.width"),
+];
+
+const CONFIG_FILE_NAME: &str = "cognitio.yaml";
+
+/// The config file given with `--config`, which wins over everything else.
+static CONFIG_FLAG: OnceLock<PathBuf> = OnceLock::new();
+
+/// Makes [`locate_config`] pick this file. Meant to be called once at startup with the value of
+/// a `--config` flag; later calls are ignored.
+pub fn set_config_flag(path: PathBuf) {
+    let _ = CONFIG_FLAG.set(path);
+}
+
+/// Finds the config file. The first of these that is set wins:
+///
+/// 1. the `--config` flag, see [`set_config_flag`],
+/// 2. the `COGNITIO_CONFIG` environment variable, pointing to the file,
+/// 3. `cognitio.yaml` in the `COGNITIO_HOME` directory,
+/// 4. `cognitio/cognitio.yaml` in `XDG_CONFIG_HOME`,
+/// 5. `~/.config/cognitio/cognitio.yaml`.
+pub fn locate_config() -> ConfigLocation {
+    if let Some(path) = CONFIG_FLAG.get() {
+        return ConfigLocation {
+            path: path.clone(),
+            source: ConfigSource::Flag,
+        };
+    }
+    if let Some(path) = non_empty_env("COGNITIO_CONFIG") {
+        return ConfigLocation {
+            path: PathBuf::from(path),
+            source: ConfigSource::ConfigEnv,
+        };
+    }
+    if let Some(home) = non_empty_env("COGNITIO_HOME") {
+        return ConfigLocation {
+            path: PathBuf::from(home).join(CONFIG_FILE_NAME),
+            source: ConfigSource::HomeEnv,
+        };
+    }
+    if let Some(xdg) = non_empty_env("XDG_CONFIG_HOME") {
+        return ConfigLocation {
+            path: PathBuf::from(xdg).join("cognitio").join(CONFIG_FILE_NAME),
+            source: ConfigSource::XdgConfigHome,
+        };
+    }
+    ConfigLocation {
+        path: default_cognitio_dir().join(CONFIG_FILE_NAME),
+        source: ConfigSource::Default,
+    }
+}
+
+/// Reads the config file found by [`locate_config`] and applies [`CONFIG_ENV_OVERRIDES`].
+pub fn read_cognitio_yaml() -> std::io::Result<CognitioConfig> {
+    let mut conf = read_config_file(cognitio_yaml_path())?;
+    apply_env_overrides(&mut conf);
+    Ok(conf)
+}
+
+/// Reads and parses a Cognitio config file at the given path.
+pub fn read_config_file(yaml_path: PathBuf) -> std::io::Result<CognitioConfig> {
+    let mut file = File::open(&yaml_path)?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    let manifest = parse_config(&yaml_path, &contents)?;
+    Ok(manifest)
+}
+
+/// Parses the contents of a config file. `path` is only used in the error.
+pub fn parse_config(path: &Path, contents: &str) -> Result<CognitioConfig, ConfigError> {
+    serde_yaml::from_str(contents).map_err(|error| {
+        let location = error.location();
+        ConfigError {
+            file: path.to_string_lossy().to_string(),
+            line: location.as_ref().map(|location| location.line()),
+            column: location.as_ref().map(|location| location.column()),
+            message: strip_location(&error.to_string()),
+        }
+    })
+}
+
+/// Reads the config file found by [`locate_config`], applies [`CONFIG_ENV_OVERRIDES`] and checks
+/// that every cheatsheet source exists.
+pub fn check_cognitio_yaml() -> ConfigCheck {
+    let path = cognitio_yaml_path();
+    let mut check = check_config_file(&path);
+    if let Some(conf) = check.config.as_mut() {
+        apply_env_overrides(conf);
+        let contents = fs::read_to_string(&path).unwrap_or_default();
+        check.errors = check_config_sources(&path, &contents, conf);
+        check.warnings = check_git_checkouts(&path, &contents, conf);
+    }
+    check
+}
+
+/// Reads and parses a config file and checks that every cheatsheet source exists.
+pub fn check_config_file(path: &Path) -> ConfigCheck {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) => {
+            return ConfigCheck {
+                config: None,
+                errors: vec![ConfigError {
+                    file: path.to_string_lossy().to_string(),
+                    line: None,
+                    column: None,
+                    message: format!("Failed to read the config file: {error}"),
+                }],
+                warnings: Vec::new(),
+            }
+        }
+    };
+
+    match parse_config(path, &contents) {
+        Ok(conf) => ConfigCheck {
+            errors: check_config_sources(path, &contents, &conf),
+            warnings: check_git_checkouts(path, &contents, &conf),
+            config: Some(conf),
+        },
+        Err(error) => ConfigCheck {
+            config: None,
+            errors: vec![error],
+            warnings: Vec::new(),
+        },
+    }
+}
+
+/// Checks that every cheatsheet source of a config is an existing directory. Errors point to
+/// where the source's path is written in `contents`, when it can be found.
+///
+/// Git sources are only checked for a path that can be expanded, since a source that was just
+/// added can't be cloned before the config with it is loaded, see [`check_git_checkouts`].
+pub fn check_config_sources(
+    path: &Path,
+    contents: &str,
+    conf: &CognitioConfig,
+) -> Vec<ConfigError> {
+    conf.cheatsheets
+        .iter()
+        .filter_map(|source| {
+            let base_dir = path.parent().unwrap_or(Path::new(""));
+            let problem = match source.expanded_path_from(base_dir) {
+                Err(error) => format!("can't be expanded: {error}"),
+                Ok(_) if matches!(source, CheatsheetData::Git(_)) => return None,
+                Ok(expanded) => {
+                    let shown = if expanded.as_os_str() == source.path() {
+                        String::new()
+                    } else {
+                        format!(" ({})", expanded.display())
+                    };
+                    if !expanded.exists() {
+                        format!("does not exist{shown}")
+                    } else if !expanded.is_dir() {
+                        format!("is not a directory{shown}")
+                    } else {
+                        return None;
+                    }
+                }
+            };
+
+            Some(source_error(path, contents, source, &problem))
+        })
+        .collect()
+}
+
+/// Warns about git sources that aren't cloned yet, or whose checkout lacks their `subdir`.
+pub fn check_git_checkouts(path: &Path, contents: &str, conf: &CognitioConfig) -> Vec<ConfigError> {
+    conf.cheatsheets
+        .iter()
+        .filter(|source| matches!(source, CheatsheetData::Git(_)))
+        .filter_map(|source| {
+            let base_dir = path.parent().unwrap_or(Path::new(""));
+            let expanded = source.expanded_path_from(base_dir).ok()?;
+            if expanded.is_dir() {
+                return None;
+            }
+            let problem = format!(
+                "is not cloned yet or has no {}, run `cognitio sources sync`",
+                expanded.display()
+            );
+            Some(source_error(path, contents, source, &problem))
+        })
+        .collect()
+}
+
+fn source_error(
+    path: &Path,
+    contents: &str,
+    source: &CheatsheetData,
+    problem: &str,
+) -> ConfigError {
+    let (line, column) = find_in_contents(contents, source.path()).unzip();
+    ConfigError {
+        file: path.to_string_lossy().to_string(),
+        line,
+        column,
+        message: format!("Cheatsheet source {} {problem}", source.path()),
+    }
+}
+
+/// Expands `~` at the start of a path to the home directory and `$VAR` or `${VAR}` to the value
+/// of an environment variable, and makes a relative path absolute by joining it to `base_dir`.
+/// `.` and `..` components are resolved without following symlinks.
+///
+/// Fails when the home directory or a variable isn't set.
+pub fn expand_path(raw: &str, base_dir: &Path) -> Result<PathBuf, String> {
+    let mut expanded = String::with_capacity(raw.len());
+    let mut rest = raw.trim();
+
+    if rest == "~" || rest.starts_with("~/") || rest.starts_with("~\\") {
+        let home = home_dir().ok_or("the home directory is not known (HOME is not set)")?;
+        expanded.push_str(&home);
+        rest = &rest[1..];
+    }
+
+    while let Some(index) = rest.find('$') {
+        expanded.push_str(&rest[..index]);
+        let after = &rest[index + 1..];
+        let (name, remaining) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], &braced[end + 1..]),
+                None => return Err(format!("missing }} after ${{ in {raw}")),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+
+        if name.is_empty() {
+            expanded.push('$');
+        } else {
+            let value =
+                env::var(name).map_err(|_| format!("environment variable {name} is not set"))?;
+            expanded.push_str(&value);
+        }
+        rest = remaining;
+    }
+    expanded.push_str(rest);
+
+    let path = PathBuf::from(expanded);
+    let absolute = if path.is_absolute() {
+        path
+    } else if base_dir.is_absolute() {
+        base_dir.join(path)
+    } else {
+        env::current_dir()
+            .map_err(|error| format!("the current directory is not known: {error}"))?
+            .join(base_dir)
+            .join(path)
+    };
+    Ok(normalize(&absolute))
+}
+
+/// Removes `.` and resolves `..` components.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// The directory of the config file found by [`locate_config`], which relative source paths
+/// are relative to.
+pub fn config_dir() -> PathBuf {
+    cognitio_yaml_path()
+        .parent()
+        .map(|dir| dir.to_path_buf())
+        .unwrap_or_default()
+}
+
+fn home_dir() -> Option<String> {
+    non_empty_env("HOME").or_else(|| non_empty_env("USERPROFILE"))
+}
+
+/// Drops the " at line 3 column 5" that serde_yaml adds to its messages, since [`ConfigError`]
+/// has the location in fields of its own. Scanner errors have two, like "did not find expected
+/// ',' or ']' at line 4 column 1, while parsing a flow sequence at line 3 column 11".
+fn strip_location(message: &str) -> String {
+    let mut stripped = message.to_string();
+    while let Some(location) = find_location(&stripped) {
+        stripped.replace_range(location, "");
+    }
+    stripped
+}
+
+/// Where the first " at line 3 column 5" in `message` is.
+fn find_location(message: &str) -> Option<Range<usize>> {
+    let leading_digits = |text: &str| text.chars().take_while(char::is_ascii_digit).count();
+    message
+        .match_indices(" at line ")
+        .find_map(|(start, marker)| {
+            let after = &message[start + marker.len()..];
+            let line = leading_digits(after);
+            let column_text = after[line..].strip_prefix(" column ")?;
+            let column = leading_digits(column_text);
+            (line > 0 && column > 0).then(|| start..message.len() - column_text.len() + column)
+        })
+}
+
+/// The 1-based line and column of the first occurrence of `text`.
+fn find_in_contents(contents: &str, text: &str) -> Option<(usize, usize)> {
+    contents.lines().enumerate().find_map(|(index, line)| {
+        line.find(text)
+            .map(|offset| (index + 1, line[..offset].chars().count() + 1))
+    })
+}
+
+/// Overrides config keys with the [`CONFIG_ENV_OVERRIDES`] environment variables that are set.
+pub fn apply_env_overrides(conf: &mut CognitioConfig) {
+    if let Some(editor) = non_empty_env("COGNITIO_EDITOR") {
+        conf.editor = Some(editor);
+    }
+    if let Some(cheatsheets) = env::var_os("COGNITIO_CHEATSHEETS").filter(|v| !v.is_empty()) {
+        conf.cheatsheets = env::split_paths(&cheatsheets)
+            .map(|path| CheatsheetData::Simple(path.to_string_lossy().to_string()))
+            .collect();
+    }
+    if let Some(width) = non_empty_env("COGNITIO_MENU_WIDTH") {
+        let styling = conf.styling.get_or_insert(Styling { menu: None });
+        styling.menu = Some(Menu { width: Some(width) });
+    }
+}
+
+/// Path to the config file found by [`locate_config`].
+pub fn cognitio_yaml_path() -> PathBuf {
+    locate_config().path
+}
+
+/// The directory holding the log file and other files Cognitio writes.
+///
+/// This is `COGNITIO_HOME` when set, otherwise `cognitio` in `XDG_CONFIG_HOME`, otherwise
+/// `~/.config/cognitio`.
+pub fn cognitio_home_dir() -> String {
+    let dir = match (
+        non_empty_env("COGNITIO_HOME"),
+        non_empty_env("XDG_CONFIG_HOME"),
+    ) {
+        (Some(home), _) => PathBuf::from(home),
+        (None, Some(xdg)) => PathBuf::from(xdg).join("cognitio"),
+        (None, None) => default_cognitio_dir(),
+    };
+    dir.to_str().unwrap_or_default().to_string()
+}
+
+fn default_cognitio_dir() -> PathBuf {
+    let home_dir = env::var("HOME").unwrap_or_default();
+    if home_dir.is_empty() {
+        error!("Error reading environment variable HOME: Empty or not exist");
+    }
+    PathBuf::from(home_dir).join(".config").join("cognitio")
+}
+
+fn non_empty_env(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process;
+
+    #[test]
+    fn locates_yaml_errors() {
+        let path = Path::new("/etc/cognitio.yaml");
+        let error = parse_config(
+            path,
+            "cheatsheets:\n  - title: Docker\n    path: [unclosed\n",
+        )
+        .unwrap_err();
+        assert_eq!((error.line, error.column), (Some(4), Some(1)));
+        assert_eq!(
+            error.message,
+            "did not find expected ',' or ']', while parsing a flow sequence"
+        );
+        assert!(error.to_string().starts_with("/etc/cognitio.yaml:4:1: "));
+
+        let error = parse_config(path, "editor: vim\ncheatsheets: 5\n").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(14)));
+        assert!(error.message.contains("cheatsheets"), "{}", error.message);
+
+        // Only locations are dropped from messages.
+        assert_eq!(
+            strip_location("expected a map at line 2 column 3"),
+            "expected a map"
+        );
+        let unchanged = "unknown source \"stop at line 2\"";
+        assert_eq!(strip_location(unchanged), unchanged);
+    }
+
+    #[test]
+    fn expands_paths() {
+        env::set_var("COGNITIO_TEST_CHEATS", "/srv/cheats");
+        env::remove_var("COGNITIO_TEST_UNSET");
+        let home = home_dir().unwrap();
+        let base = Path::new("/etc/cognitio");
+        let cases = [
+            ("/abs/path", "/abs/path".to_string()),
+            ("~", home.clone()),
+            ("~/cheats", format!("{home}/cheats")),
+            ("~user/cheats", "/etc/cognitio/~user/cheats".to_string()),
+            (
+                "$COGNITIO_TEST_CHEATS/docker",
+                "/srv/cheats/docker".to_string(),
+            ),
+            (
+                "${COGNITIO_TEST_CHEATS}docker",
+                "/srv/cheatsdocker".to_string(),
+            ),
+            ("/a$/b", "/a$/b".to_string()),
+            ("cheats", "/etc/cognitio/cheats".to_string()),
+            ("./cheats/../more/", "/etc/cognitio/more".to_string()),
+            ("../shared", "/etc/shared".to_string()),
+            ("  /padded  ", "/padded".to_string()),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(expand_path(raw, base), Ok(PathBuf::from(expected)), "{raw}");
+        }
+
+        let errors = [
+            (
+                "$COGNITIO_TEST_UNSET/cheats",
+                "COGNITIO_TEST_UNSET is not set",
+            ),
+            ("${COGNITIO_TEST_UNSET}", "COGNITIO_TEST_UNSET is not set"),
+            ("${COGNITIO_TEST_CHEATS", "missing }"),
+        ];
+        for (raw, expected) in errors {
+            let error = expand_path(raw, base).unwrap_err();
+            assert!(error.contains(expected), "{raw}: {error}");
+        }
+
+        // A relative base directory is relative to the current directory.
+        let current = env::current_dir().unwrap();
+        assert_eq!(
+            expand_path("cheats", Path::new("config")),
+            Ok(current.join("config/cheats"))
+        );
+    }
+
+    #[test]
+    fn locates_missing_sources() {
+        let dir = env::temp_dir().join(format!("cognitio-config-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("present")).unwrap();
+        fs::write(dir.join("file.md"), "").unwrap();
+        let path = dir.join("cognitio.yaml");
+        fs::write(
+            &path,
+            "cheatsheets:\n  - present\n  - title: Gone\n    path: gone\n  - file.md\n",
+        )
+        .unwrap();
+
+        let check = check_config_file(&path);
+        assert!(!check.is_valid());
+        let errors: Vec<_> = check
+            .errors
+            .iter()
+            .map(|error| (error.line, error.column))
+            .collect();
+        assert_eq!(errors, [(Some(4), Some(11)), (Some(5), Some(5))]);
+        assert!(check.errors[0].message.contains("gone does not exist"));
+        assert!(check.errors[1]
+            .message
+            .contains("file.md is not a directory"));
+
+        let error = ConfigError {
+            file: "cognitio.yaml".to_string(),
+            line: Some(3),
+            column: None,
+            message: "broken".to_string(),
+        };
+        assert_eq!(error.to_string(), "cognitio.yaml:3: broken");
+        let missing = check_config_file(&dir.join("missing.yaml"));
+        assert!(missing.config.is_none());
+        assert_eq!(missing.errors[0].line, None);
+    }
+}
@@ -0,0 +1,39 @@
+use crate::library::DirectoryFile;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Reads the markdown of each file, keyed by file name without the `.md` extension.
+/// Files that can't be read are given empty content.
+pub fn load_cheatsheet(files: &[DirectoryFile]) -> HashMap<String, String> {
+    let mut cheatsheet: HashMap<String, String> = HashMap::new();
+    for file in files {
+        let file_content = read_file_to_string(&file.path).unwrap_or_default();
+        let clean_name = file.name.replace(".md", "");
+        cheatsheet.insert(clean_name, file_content);
+    }
+    cheatsheet
+}
+
+/// Reads the markdown of a single file, keyed by file name without the `.md` extension.
+pub fn load_cheatsheet_section(path: &str) -> HashMap<String, String> {
+    let mut cheatsheet: HashMap<String, String> = HashMap::new();
+    let file_content = read_file_to_string(path).unwrap_or_default();
+    let file_name = Path::new(path)
+        .file_name()
+        .unwrap_or_default()
+        .to_str()
+        .unwrap_or_default()
+        .to_string()
+        .replace(".md", "");
+    cheatsheet.insert(file_name, file_content);
+    cheatsheet
+}
+
+pub fn read_file_to_string(file_path: &str) -> std::io::Result<String> {
+    let mut file = File::open(file_path)?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    Ok(contents)
+}
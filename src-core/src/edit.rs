@@ -0,0 +1,418 @@
+//! Changing cheatsheet files in place.
+//!
+//! Edits only touch the lines of the section they are about, using the line numbers from
+//! [`parse_cheatsheet`], so the rest of the file keeps its content and formatting. Files are
+//! written to a temporary file next to them first and then renamed over the original, so a
+//! cheatsheet is never left half written. Every edit returns the cheatsheet as it is afterwards.
+
+use crate::snippet::{parse_cheatsheet, Cheatsheet, Snippet};
+use log::info;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Creates an empty cheatsheet called `name` in `directory`. `.md` is added to the name unless
+/// it is there already. Fails if the file exists.
+pub fn create_cheatsheet(directory: &str, name: &str) -> io::Result<Cheatsheet> {
+    let name = name.trim();
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("'{name}' is not a valid cheatsheet name"),
+        ));
+    }
+    if !Path::new(directory).is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{directory} is not a directory"),
+        ));
+    }
+
+    let file_name = if name.ends_with(".md") {
+        name.to_string()
+    } else {
+        format!("{name}.md")
+    };
+    let path = Path::new(directory).join(file_name);
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .map_err(|error| match error.kind() {
+            io::ErrorKind::AlreadyExists => io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", path.display()),
+            ),
+            _ => error,
+        })?;
+
+    info!("Created cheatsheet {}", path.display());
+    let path = path.to_string_lossy();
+    Ok(parse_cheatsheet(&path, ""))
+}
+
+/// Adds an h3 section with `heading` and `body` at the end of a cheatsheet.
+pub fn append_section(path: &str, heading: &str, body: &str) -> io::Result<Cheatsheet> {
+    let heading = check_heading(heading)?;
+    let (markdown, cheatsheet) = load(path)?;
+    check_unused(&cheatsheet, heading, None)?;
+    let newline = newline_of(&markdown);
+
+    let mut updated = markdown.clone();
+    if !updated.is_empty() && !updated.ends_with('\n') {
+        updated.push_str(newline);
+    }
+    if !updated.trim().is_empty() && !updated.ends_with(&format!("{newline}{newline}")) {
+        updated.push_str(newline);
+    }
+    updated.push_str(&format!("### {heading}{newline}"));
+    updated.push_str(&body_lines(body, newline));
+
+    save(path, &updated)
+}
+
+/// Replaces everything between a section's heading and the next heading, keeping the blank lines
+/// before the next heading.
+pub fn replace_section_body(path: &str, heading: &str, body: &str) -> io::Result<Cheatsheet> {
+    let (markdown, cheatsheet) = load(path)?;
+    let section = find(&cheatsheet, heading)?;
+    let newline = newline_of(&markdown);
+    let lines = lines_of(&markdown);
+
+    let mut updated: String = lines[..section.start_line].concat();
+    if !updated.ends_with('\n') {
+        updated.push_str(newline);
+    }
+    updated.push_str(&body_lines(body, newline));
+    updated.push_str(&lines[section.end_line..].concat());
+
+    save(path, &updated)
+}
+
+/// Changes the text of a section's heading, keeping its level.
+pub fn rename_section(path: &str, heading: &str, new_heading: &str) -> io::Result<Cheatsheet> {
+    let new_heading = check_heading(new_heading)?;
+    let (markdown, cheatsheet) = load(path)?;
+    let section = find(&cheatsheet, heading)?;
+    check_unused(&cheatsheet, new_heading, Some(section))?;
+    let mut lines = lines_of(&markdown);
+
+    let line = lines[section.start_line - 1];
+    let content = line.trim_end_matches(['\r', '\n']);
+    let ending = &line[content.len()..];
+    let indent = &content[..content.len() - content.trim_start().len()];
+    let hashes = content
+        .trim_start()
+        .chars()
+        .take_while(|c| *c == '#')
+        .count();
+    let renamed = format!("{indent}{} {new_heading}{ending}", "#".repeat(hashes));
+    lines[section.start_line - 1] = &renamed;
+
+    save(path, &lines.concat())
+}
+
+/// Removes a section with its heading and the blank lines after it.
+pub fn delete_section(path: &str, heading: &str) -> io::Result<Cheatsheet> {
+    let (markdown, cheatsheet) = load(path)?;
+    let section = find(&cheatsheet, heading)?;
+    let lines = lines_of(&markdown);
+
+    let mut end = section.end_line;
+    while end < lines.len() && lines[end].trim().is_empty() {
+        end += 1;
+    }
+    let mut start = section.start_line - 1;
+    // Without anything after the section, the blank lines before it would end the file.
+    if end == lines.len() {
+        while start > 0 && lines[start - 1].trim().is_empty() {
+            start -= 1;
+        }
+    }
+
+    let updated = [&lines[..start], &lines[end..]].concat().concat();
+    save(path, &updated)
+}
+
+fn load(path: &str) -> io::Result<(String, Cheatsheet)> {
+    let markdown = fs::read_to_string(path)?;
+    let cheatsheet = parse_cheatsheet(path, &markdown);
+    Ok((markdown, cheatsheet))
+}
+
+fn save(path: &str, markdown: &str) -> io::Result<Cheatsheet> {
+    write_atomically(Path::new(path), markdown)?;
+    info!("Updated cheatsheet {path}");
+    Ok(parse_cheatsheet(path, markdown))
+}
+
+/// Writes to a hidden temporary file in the same directory and renames it over `path`. The file
+/// a symlink points to is replaced rather than the symlink, and the permissions are kept.
+///
+/// The temporary file is named after the process and a counter, and is only ever created new, so
+/// writers in other processes or threads, like the app and `cognitio serve`, don't write to each
+/// other's temporary files. The last rename wins.
+pub fn write_atomically(path: &Path, contents: &str) -> io::Result<()> {
+    let path = fs::canonicalize(path).unwrap_or(path.to_path_buf());
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "The path has no file name"))?
+        .to_string_lossy()
+        .to_string();
+
+    let mut attempt = 0;
+    let (temp, file) = loop {
+        let number = TEMP_FILES.fetch_add(1, Ordering::Relaxed);
+        let temp = path.with_file_name(format!(
+            ".{file_name}.{}-{number}.cognitio-tmp",
+            std::process::id()
+        ));
+        match OpenOptions::new().write(true).create_new(true).open(&temp) {
+            Ok(file) => break (temp, file),
+            // Left behind by an earlier process with the same id.
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists && attempt < 100 => {
+                attempt += 1
+            }
+            Err(error) => return Err(error),
+        }
+    };
+
+    let result = write_and_rename(file, &temp, &path, contents);
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Numbers the temporary files of [`write_atomically`].
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+fn write_and_rename(mut file: File, temp: &Path, path: &Path, contents: &str) -> io::Result<()> {
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    if let Ok(metadata) = fs::metadata(path) {
+        fs::set_permissions(temp, metadata.permissions())?;
+    }
+    fs::rename(temp, path)
+}
+
+fn find<'a>(cheatsheet: &'a Cheatsheet, heading: &str) -> io::Result<&'a Snippet> {
+    cheatsheet.find_section(heading).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("There is no section \"{heading}\" in {}", cheatsheet.path),
+        )
+    })
+}
+
+fn check_heading(heading: &str) -> io::Result<&str> {
+    let heading = heading.trim();
+    if heading.is_empty() || heading.contains(['\n', '\r']) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "A section heading must be a single line of text",
+        ));
+    }
+    Ok(heading)
+}
+
+/// Section titles are how sections are found, so two sections can't share one. `except` is the
+/// section being renamed, which may keep its title with different case.
+fn check_unused(
+    cheatsheet: &Cheatsheet,
+    heading: &str,
+    except: Option<&Snippet>,
+) -> io::Result<()> {
+    match cheatsheet.find_section(heading) {
+        Some(existing) if except.map(|section| section.start_line) != Some(existing.start_line) => {
+            Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "There already is a section \"{}\" in {}",
+                    existing.title, cheatsheet.path
+                ),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The lines of a section body: a blank line after the heading, then the body without leading
+/// and trailing blank lines. Nothing for an empty body.
+fn body_lines(body: &str, newline: &str) -> String {
+    let body = body.trim_matches(['\r', '\n']);
+    if body.trim().is_empty() {
+        return String::new();
+    }
+    let lines: Vec<&str> = body.lines().collect();
+    format!("{newline}{}{newline}", lines.join(newline))
+}
+
+/// Keeps Windows line endings in files that use them.
+fn newline_of(markdown: &str) -> &'static str {
+    if markdown.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+/// The lines of the markdown with their line endings, so that joining them gives it back.
+fn lines_of(markdown: &str) -> Vec<&str> {
+    markdown.split_inclusive('\n').collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::process;
+
+    const MARKDOWN: &str =
+        "# Docker\n\n### First\n\nfirst\n\n### Middle\n\nmiddle\n\n### Last\n\nlast\n";
+
+    /// A fresh directory holding `cheat.md` with `markdown`, and the path of the file.
+    fn setup(name: &str, markdown: &str) -> (PathBuf, String) {
+        let dir = std::env::temp_dir().join(format!("cognitio-edit-{}-{name}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cheat.md");
+        fs::write(&path, markdown).unwrap();
+        (dir, path.to_string_lossy().to_string())
+    }
+
+    fn read(path: &str) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn edits_the_first_and_last_section() {
+        let (_dir, path) = setup("first-last", MARKDOWN);
+        replace_section_body(&path, "First", "one").unwrap();
+        replace_section_body(&path, "last", "three\n").unwrap();
+        assert_eq!(
+            read(&path),
+            "# Docker\n\n### First\n\none\n\n### Middle\n\nmiddle\n\n### Last\n\nthree\n"
+        );
+
+        delete_section(&path, "First").unwrap();
+        let cheatsheet = delete_section(&path, "Last").unwrap();
+        assert_eq!(read(&path), "# Docker\n\n### Middle\n\nmiddle\n");
+        let titles: Vec<_> = cheatsheet
+            .sections()
+            .map(|section| &section.title)
+            .collect();
+        assert_eq!(titles, ["Middle"]);
+    }
+
+    #[test]
+    fn edits_a_section_without_a_newline_at_the_end() {
+        let (_dir, path) = setup("no-newline", "### A\n\na\n\n### B\n\nb");
+        rename_section(&path, "B", "Bee").unwrap();
+        assert_eq!(read(&path), "### A\n\na\n\n### Bee\n\nb");
+        replace_section_body(&path, "Bee", "bee").unwrap();
+        assert_eq!(read(&path), "### A\n\na\n\n### Bee\n\nbee\n");
+
+        fs::write(&path, "### A\n\na").unwrap();
+        append_section(&path, "B", "b").unwrap();
+        assert_eq!(read(&path), "### A\n\na\n\n### B\n\nb\n");
+    }
+
+    #[test]
+    fn finds_sections_again_after_the_file_changed() {
+        let (_dir, path) = setup("stale", MARKDOWN);
+        let stale = parse_cheatsheet(&path, &read(&path));
+        let stale_middle = stale.find_section("Middle").unwrap().start_line;
+
+        // Someone else adds a section above, so the line numbers the caller saw are off.
+        fs::write(&path, format!("### New\n\nnew\n\n{MARKDOWN}")).unwrap();
+        let cheatsheet = replace_section_body(&path, "Middle", "changed").unwrap();
+        assert_ne!(
+            cheatsheet.find_section("Middle").unwrap().start_line,
+            stale_middle
+        );
+        assert_eq!(
+            read(&path),
+            "### New\n\nnew\n\n# Docker\n\n### First\n\nfirst\n\n### Middle\n\nchanged\n\n\
+             ### Last\n\nlast\n"
+        );
+
+        let error = delete_section(&path, "Gone").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn keeps_windows_line_endings() {
+        let (_dir, path) = setup("crlf", "### A\r\n\r\na\r\n");
+        append_section(&path, "B", "b").unwrap();
+        assert_eq!(read(&path), "### A\r\n\r\na\r\n\r\n### B\r\n\r\nb\r\n");
+    }
+
+    #[test]
+    fn checks_names_and_headings() {
+        let (dir, path) = setup("names", MARKDOWN);
+        let directory = dir.to_string_lossy();
+        for name in ["", "  ", "a/b", "a\\b", ".hidden", "../up"] {
+            let error = create_cheatsheet(&directory, name).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput, "{name:?}");
+        }
+        assert!(create_cheatsheet(&directory, "git").is_ok());
+        assert!(dir.join("git.md").is_file());
+        assert!(create_cheatsheet(&directory, "notes.md").is_ok());
+        assert!(dir.join("notes.md").is_file());
+        let error = create_cheatsheet(&directory, "git").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+
+        let error = append_section(&path, "Two\nlines", "").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        let error = append_section(&path, "middle", "").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        let error = rename_section(&path, "First", "Last").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        // A section may change the case of its own title.
+        rename_section(&path, "First", "FIRST").unwrap();
+    }
+
+    /// The names of the files in a directory, sorted.
+    fn list(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn cleans_up_when_writing_fails() {
+        let (dir, path) = setup("failed-write", MARKDOWN);
+        // A file can't be renamed over a directory.
+        fs::create_dir(dir.join("taken.md")).unwrap();
+        assert!(write_atomically(&dir.join("taken.md"), "lost").is_err());
+        assert!(dir.join("taken.md").is_dir());
+        assert_eq!(read(&path), MARKDOWN);
+        assert_eq!(list(&dir), ["cheat.md", "taken.md"]);
+    }
+
+    #[test]
+    fn writes_from_several_threads_at_once() {
+        let (dir, path) = setup("concurrent", MARKDOWN);
+        let contents: Vec<String> = (0..8)
+            .map(|number| format!("### Writer {number}\n\n{}\n", "x".repeat(10_000)))
+            .collect();
+        std::thread::scope(|scope| {
+            for contents in &contents {
+                let path = Path::new(&path);
+                scope.spawn(move || {
+                    for _ in 0..10 {
+                        write_atomically(path, contents).unwrap();
+                    }
+                });
+            }
+        });
+        // Every write was whole, and no temporary file is left.
+        assert!(contents.contains(&read(&path)));
+        assert_eq!(list(&dir), ["cheat.md"]);
+    }
+}
@@ -0,0 +1,200 @@
+//! Opening files in the configured editor.
+//!
+//! An editor is configured as a command template like `code --goto {path}:{line}` or
+//! `kitty nvim +{line} {path}`. `{path}` is replaced with the path to open and `{line}` with the
+//! line to jump to, or 1 when there is none. A template without `{path}`, like `code --wait`, gets
+//! the path as its last argument.
+
+use crate::config::CognitioConfig;
+use crate::snippet::load_cheatsheet_file;
+use log::{debug, error};
+use std::io;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+
+/// An editor command with its placeholders filled in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EditorCommand {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl EditorCommand {
+    /// Fills in an editor template for a path and an optional line.
+    pub fn new(template: &str, path: &Path, line: Option<usize>) -> io::Result<EditorCommand> {
+        let path = path.to_string_lossy();
+        let line = line.unwrap_or(1).to_string();
+        let mut words = template
+            .split_whitespace()
+            .map(|word| word.replace("{path}", &path).replace("{line}", &line));
+
+        let program = words.next().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The editor command in the Cognitio config file is empty",
+            )
+        })?;
+        let mut args: Vec<String> = words.collect();
+        if !template.contains("{path}") {
+            args.push(path.to_string());
+        }
+
+        Ok(EditorCommand { program, args })
+    }
+
+    /// Starts the editor without waiting for it or sharing stdin and stdout with it, so a command
+    /// like `code --wait` doesn't block the caller.
+    pub fn spawn_detached(&self) -> io::Result<()> {
+        debug!("Spawning editor {self:?}");
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        // A process group of its own keeps the editor running when the terminal that started
+        // Cognitio sends it a signal.
+        #[cfg(unix)]
+        std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+        let mut child = command.spawn()?;
+        // Waiting on another thread reaps the editor when it exits.
+        let program = self.program.clone();
+        std::thread::spawn(move || {
+            if let Err(error) = child.wait() {
+                error!("Failed to wait for editor {program}: {error:?}");
+            }
+        });
+        Ok(())
+    }
+
+    /// Runs the editor in the foreground with the terminal, for editors like `nvim`.
+    pub fn run(&self) -> io::Result<ExitStatus> {
+        debug!("Running editor {self:?}");
+        Command::new(&self.program).args(&self.args).status()
+    }
+}
+
+/// The editor command for a path from the config, see [`CognitioConfig::editor_for`].
+pub fn editor_command(
+    conf: &CognitioConfig,
+    path: &Path,
+    line: Option<usize>,
+) -> io::Result<EditorCommand> {
+    let template = conf.editor_for(path).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "Editor is not configured in Cognitio config file",
+        )
+    })?;
+    EditorCommand::new(template, path, line)
+}
+
+/// Opens a path in the configured editor without waiting for it.
+pub fn open_in_editor(conf: &CognitioConfig, path: &Path, line: Option<usize>) -> io::Result<()> {
+    editor_command(conf, path, line)?.spawn_detached()
+}
+
+/// The line of a section's heading in a cheatsheet, found like
+/// [`crate::snippet::Cheatsheet::find_section`].
+pub fn section_line(path: &str, heading: &str) -> io::Result<usize> {
+    let cheatsheet = load_cheatsheet_file(path)?;
+    cheatsheet
+        .find_section(heading)
+        .map(|section| section.start_line)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("There is no section \"{heading}\" in {path}"),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process;
+
+    fn command(template: &str, path: &str, line: Option<usize>) -> (String, Vec<String>) {
+        let command = EditorCommand::new(template, Path::new(path), line).unwrap();
+        (command.program, command.args)
+    }
+
+    #[test]
+    fn fills_in_path_and_line() {
+        assert_eq!(
+            command("code --goto {path}:{line}", "/lib/docker.md", Some(12)),
+            (
+                "code".into(),
+                vec!["--goto".into(), "/lib/docker.md:12".into()]
+            )
+        );
+        assert_eq!(
+            command("kitty  nvim +{line} {path}", "/lib/docker.md", None),
+            (
+                "kitty".into(),
+                vec!["nvim".into(), "+1".into(), "/lib/docker.md".into()]
+            )
+        );
+        // Without `{path}` the path is the last argument.
+        assert_eq!(
+            command("code --wait", "/lib/docker.md", Some(3)),
+            (
+                "code".into(),
+                vec!["--wait".into(), "/lib/docker.md".into()]
+            )
+        );
+        assert_eq!(
+            command("vi", "/lib/docker.md", None),
+            ("vi".into(), vec!["/lib/docker.md".into()])
+        );
+    }
+
+    #[test]
+    fn keeps_paths_with_spaces_in_one_argument() {
+        let path = "/home/me/My Cheats/git notes.md";
+        assert_eq!(
+            command("nvim +{line} {path}", path, Some(7)),
+            ("nvim".into(), vec!["+7".into(), path.into()])
+        );
+        assert_eq!(
+            command("code", path, None),
+            ("code".into(), vec![path.into()])
+        );
+    }
+
+    #[test]
+    fn picks_the_editor_from_the_config() {
+        let error = EditorCommand::new("  ", Path::new("/lib/a.md"), None).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+        let mut conf = CognitioConfig::default();
+        let error = editor_command(&conf, Path::new("/lib/a.md"), None).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+
+        conf.editor = Some("vi".to_string());
+        conf.editors
+            .insert(".MD".to_string(), "typora {path}".to_string());
+        let markdown = editor_command(&conf, Path::new("/lib/a.md"), None).unwrap();
+        assert_eq!(markdown.program, "typora");
+        let directory = editor_command(&conf, Path::new("/lib/docker"), None).unwrap();
+        assert_eq!(directory.program, "vi");
+    }
+
+    #[test]
+    fn finds_the_line_of_a_section() {
+        let dir = std::env::temp_dir().join(format!("cognitio-editor-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("docker.md");
+        fs::write(&path, "# Docker\n\n### Run\n\nrun\n\n### List containers\n").unwrap();
+        let path = path.to_string_lossy();
+
+        assert_eq!(section_line(&path, "Run").unwrap(), 3);
+        assert_eq!(section_line(&path, " list CONTAINERS ").unwrap(), 7);
+        let error = section_line(&path, "Stop").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(section_line(&dir.join("missing.md").to_string_lossy(), "Run").is_err());
+    }
+}
@@ -0,0 +1,541 @@
+//! Exporting cheatsheets as a static HTML site, for people who don't run the app.
+//!
+//! [`export_library`] writes a page per cheatsheet with a navigation tree like the app's menu:
+//! one part per source, listing every directory that has cheatsheets. Pages only link to each
+//! other with relative links, so the site works from any directory on a static host. Search runs
+//! in the browser from `search-index.json`, one entry per section.
+
+use crate::config::CognitioConfig;
+use crate::html::{escape, section_anchors, slug, HtmlRenderer};
+use crate::library::{list_cheatsheet_directories, sort_directories_and_files, Directory};
+use crate::snippet::{load_cheatsheet_file, Block, Cheatsheet};
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub(crate) const SITE_CSS: &str = include_str!("../assets/site.css");
+pub(crate) const SITE_JS: &str = include_str!("../assets/site.js");
+
+/// What [`export_library`] wrote.
+#[derive(Clone, Debug, Serialize)]
+pub struct ExportSummary {
+    pub cheatsheets: usize,
+    pub sections: usize,
+    /// Cheatsheets that couldn't be read, and were left out.
+    pub skipped: Vec<String>,
+}
+
+/// One section in `search-index.json`.
+#[derive(Clone, Debug, Serialize)]
+pub struct SearchEntry {
+    /// The section title.
+    pub title: String,
+    /// The cheatsheet name and where it is in the menu.
+    pub cheatsheet: String,
+    /// Link to the section, relative to the root of the site.
+    pub url: String,
+    /// The prose and code of the section.
+    pub text: String,
+}
+
+/// A part of the navigation tree: a source.
+pub(crate) struct NavSection {
+    pub(crate) title: String,
+    pub(crate) items: Vec<NavItem>,
+}
+
+/// A directory with cheatsheets, titled like in the app's menu: `dir / sub`.
+pub(crate) struct NavItem {
+    pub(crate) title: String,
+    pub(crate) pages: Vec<Page>,
+}
+
+pub(crate) struct Page {
+    /// The cheatsheet file.
+    pub(crate) source: String,
+    pub(crate) name: String,
+    /// Path segments of the page below the site root, the last one being the `.html` file.
+    pub(crate) segments: Vec<String>,
+}
+
+impl Page {
+    fn url(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| encode_path_segment(segment))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// The relative path from this page back to the site root, like `../../`.
+    fn root(&self) -> String {
+        "../".repeat(self.segments.len().saturating_sub(1))
+    }
+}
+
+/// Exports every cheatsheet in the configured sources to `out`, with `index.html` as the start
+/// page. Files already in `out` are overwritten but not removed.
+pub fn export_library(conf: &CognitioConfig, out: &Path) -> io::Result<ExportSummary> {
+    let directories = sort_directories_and_files(&list_cheatsheet_directories(conf));
+    let nav = plan_site(&directories);
+    let renderer = HtmlRenderer::new(true);
+    let mut summary = ExportSummary {
+        cheatsheets: 0,
+        sections: 0,
+        skipped: Vec::new(),
+    };
+    let mut search_index = Vec::new();
+
+    fs::create_dir_all(out.join("assets"))?;
+    fs::write(out.join("assets/cognitio.css"), site_css(&renderer))?;
+    fs::write(out.join("assets/cognitio.js"), SITE_JS)?;
+
+    for section in &nav {
+        for item in &section.items {
+            for page in &item.pages {
+                let cheatsheet = match load_cheatsheet_file(&page.source) {
+                    Ok(cheatsheet) => cheatsheet,
+                    Err(error) => {
+                        warn!("Skipping {} in the export: {error}", page.source);
+                        summary.skipped.push(page.source.clone());
+                        continue;
+                    }
+                };
+
+                let content = cheatsheet_content(&renderer, &cheatsheet, &item.title);
+                let root = page.root();
+                let menu = nav_html(&nav, Some(&page.source), |page| {
+                    format!("{root}{}", page.url())
+                });
+                let html = site_page(&page.name, &content, &menu, &root, false);
+                let path = page
+                    .segments
+                    .iter()
+                    .fold(out.to_path_buf(), |path, segment| path.join(segment));
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&path, html)?;
+
+                search_index.extend(search_entries(&cheatsheet, &page.url(), &item.title));
+                summary.cheatsheets += 1;
+                summary.sections += cheatsheet.sections().count();
+            }
+        }
+    }
+
+    let start = format!(
+        "<h1 class=\"page-title\">Cheatsheets</h1>\n\
+         <p class=\"page-path\">{} cheatsheets with {} sections. Pick one in the menu or search for \
+         a section.</p>\n",
+        summary.cheatsheets, summary.sections
+    );
+    fs::write(
+        out.join("index.html"),
+        site_page(
+            "Cheatsheets",
+            &start,
+            &nav_html(&nav, None, Page::url),
+            "",
+            false,
+        ),
+    )?;
+    fs::write(
+        out.join("search-index.json"),
+        serde_json::to_string(&search_index).map_err(io::Error::other)?,
+    )?;
+
+    info!(
+        "Exported {} cheatsheets to {}",
+        summary.cheatsheets,
+        out.display()
+    );
+    Ok(summary)
+}
+
+/// Exports a single cheatsheet as a page that needs no other files. `dest` is either a
+/// directory, which gets `<name>.html`, or the `.html` file to write. Returns the path written.
+/// The page shows where the cheatsheet is in the menu rather than its path, since it's meant to
+/// be shared.
+pub fn export_cheatsheet(conf: &CognitioConfig, path: &str, dest: &Path) -> io::Result<PathBuf> {
+    let cheatsheet = load_cheatsheet_file(path)?;
+    let out = if dest.is_dir() {
+        dest.join(format!("{}.html", cheatsheet.name))
+    } else if dest
+        .extension()
+        .is_some_and(|extension| extension == "html")
+    {
+        dest.to_path_buf()
+    } else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} is neither a directory nor an .html file",
+                dest.display()
+            ),
+        ));
+    };
+
+    let renderer = HtmlRenderer::new(true);
+    let content = cheatsheet_content(&renderer, &cheatsheet, &menu_title(conf, path));
+    let html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>\n{SITE_CSS}\n{}</style>\n</head>\n<body>\n\
+         <main class=\"content\">\n{content}</main>\n<script>\n{SITE_JS}</script>\n</body>\n</html>\n",
+        escape(&cheatsheet.name),
+        renderer.code_css(),
+    );
+    fs::write(&out, html)?;
+
+    info!("Exported {path} to {}", out.display());
+    Ok(out)
+}
+
+/// The title of the menu item with a cheatsheet, like in [`export_library`]. A cheatsheet that
+/// isn't in the sources gets the name of its directory.
+fn menu_title(conf: &CognitioConfig, path: &str) -> String {
+    let canonical = |path: &str| fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+    let target = canonical(path);
+    let directories = sort_directories_and_files(&list_cheatsheet_directories(conf));
+    plan_site(&directories)
+        .into_iter()
+        .flat_map(|section| section.items)
+        .find(|item| {
+            item.pages
+                .iter()
+                .any(|page| canonical(&page.source) == target)
+        })
+        .map(|item| item.title)
+        .unwrap_or_else(|| {
+            let directory = Path::new(path).parent().and_then(Path::file_name);
+            directory
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+}
+
+/// Lays out the site like the app's menu. Each source gets a directory named after it, with the
+/// cheatsheets in the same directories as in the source.
+pub(crate) fn plan_site(directories: &[Directory]) -> Vec<NavSection> {
+    // `assets` holds the stylesheet and script.
+    let mut used_names = HashSet::from(["assets".to_string()]);
+    directories
+        .iter()
+        .map(|directory| {
+            let base = slug(&directory.name);
+            let mut name = base.clone();
+            let mut number = 2;
+            while !used_names.insert(name.clone()) {
+                name = format!("{base}-{number}");
+                number += 1;
+            }
+
+            // Like in the menu, directories below a source aren't prefixed with its name.
+            let mut items = Vec::new();
+            add_nav_item(
+                directory,
+                directory.name.clone(),
+                vec![name.clone()],
+                &mut items,
+            );
+            for sub_directory in &directory.sub_directories {
+                let segments = vec![name.clone(), sub_directory.name.clone()];
+                add_nav_items(
+                    sub_directory,
+                    sub_directory.name.clone(),
+                    segments,
+                    &mut items,
+                );
+            }
+            NavSection {
+                title: directory.name.clone(),
+                items,
+            }
+        })
+        .collect()
+}
+
+/// Adds a directory and then its sub directories, titled `title / sub`.
+fn add_nav_items(
+    directory: &Directory,
+    title: String,
+    segments: Vec<String>,
+    items: &mut Vec<NavItem>,
+) {
+    add_nav_item(directory, title.clone(), segments.clone(), items);
+    for sub_directory in &directory.sub_directories {
+        let mut sub_segments = segments.clone();
+        sub_segments.push(sub_directory.name.clone());
+        let sub_title = format!("{title} / {}", sub_directory.name);
+        add_nav_items(sub_directory, sub_title, sub_segments, items);
+    }
+}
+
+/// Adds a directory with its cheatsheets. Directories without cheatsheets aren't in the menu.
+fn add_nav_item(
+    directory: &Directory,
+    title: String,
+    segments: Vec<String>,
+    items: &mut Vec<NavItem>,
+) {
+    if directory.files.is_empty() {
+        return;
+    }
+    let pages = directory
+        .files
+        .iter()
+        .map(|file| {
+            let name = file
+                .name
+                .strip_suffix(".md")
+                .unwrap_or(&file.name)
+                .to_string();
+            let mut page_segments = segments.clone();
+            page_segments.push(format!("{name}.html"));
+            Page {
+                source: file.path.clone(),
+                name,
+                segments: page_segments,
+            }
+        })
+        .collect();
+    items.push(NavItem { title, pages });
+}
+
+pub(crate) fn cheatsheet_content(
+    renderer: &HtmlRenderer,
+    cheatsheet: &Cheatsheet,
+    location: &str,
+) -> String {
+    format!(
+        "<h1 class=\"page-title\">{}</h1>\n<p class=\"page-path\">{}</p>\n\
+         <div class=\"cheatsheet-html-root\">\n{}</div>\n",
+        escape(&cheatsheet.name),
+        escape(location),
+        renderer.render_cheatsheet(cheatsheet)
+    )
+}
+
+/// The search index entries of a cheatsheet's sections. `url` is the page of the cheatsheet.
+pub(crate) fn search_entries(
+    cheatsheet: &Cheatsheet,
+    url: &str,
+    location: &str,
+) -> Vec<SearchEntry> {
+    cheatsheet
+        .sections()
+        .zip(section_anchors(cheatsheet))
+        .map(|(section, anchor)| {
+            let text: Vec<&str> = section
+                .blocks
+                .iter()
+                .map(|block| match block {
+                    Block::Prose(prose) => prose.markdown.as_str(),
+                    Block::Code(code) => code.code.as_str(),
+                })
+                .collect();
+            SearchEntry {
+                title: section.title.clone(),
+                cheatsheet: format!("{} in {location}", cheatsheet.name),
+                url: format!("{url}#{anchor}"),
+                text: text.join("\n"),
+            }
+        })
+        .collect()
+}
+
+/// The menu of a page. `current` is the file of the cheatsheet on the page, and `href` gives the
+/// link to a page.
+pub(crate) fn nav_html(
+    nav: &[NavSection],
+    current: Option<&str>,
+    href: impl Fn(&Page) -> String,
+) -> String {
+    let mut html = String::new();
+    for section in nav {
+        let _ = writeln!(html, "<h2>{}</h2>", escape(&section.title));
+        for item in &section.items {
+            let _ = writeln!(html, "<h3>{}</h3>\n<ul>", escape(&item.title));
+            for page in &item.pages {
+                let active = if current == Some(page.source.as_str()) {
+                    " class=\"active\""
+                } else {
+                    ""
+                };
+                let _ = writeln!(
+                    html,
+                    "<li><a href=\"{}\"{active}>{}</a></li>",
+                    escape(&href(page)),
+                    escape(&page.name)
+                );
+            }
+            html.push_str("</ul>\n");
+        }
+    }
+    html
+}
+
+/// A page of the site with the menu and search. `root` is the relative path to the site root,
+/// and `live` pages reload when `cognitio serve` sees their cheatsheet or the library change.
+pub(crate) fn site_page(title: &str, content: &str, nav: &str, root: &str, live: bool) -> String {
+    let live = if live { " data-live" } else { "" };
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n\
+         <link rel=\"stylesheet\" href=\"{root}assets/cognitio.css\">\n</head>\n\
+         <body data-root=\"{root}\"{live}>\n<nav class=\"menu\">\n\
+         <a class=\"menu-home\" href=\"{root}index.html\">Cognitio</a>\n\
+         <input id=\"search\" type=\"search\" placeholder=\"Search\" autocomplete=\"off\">\n\
+         <div id=\"search-results\"></div>\n{nav}</nav>\n\
+         <main class=\"content\">\n{content}</main>\n\
+         <script src=\"{root}assets/cognitio.js\"></script>\n</body>\n</html>\n",
+        title = escape(title),
+    )
+}
+
+/// The stylesheet of the site, with the colors of the highlighting theme.
+pub(crate) fn site_css(renderer: &HtmlRenderer) -> String {
+    format!("{SITE_CSS}\n{}", renderer.code_css())
+}
+
+/// Percent-encodes a file or directory name for use in a URL path.
+fn encode_path_segment(segment: &str) -> String {
+    let mut encoded = String::new();
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CheatsheetData;
+    use std::process;
+
+    #[test]
+    fn shows_the_menu_title_on_a_single_page() {
+        let dir = std::env::temp_dir().join(format!("cognitio-export-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("source/docker/compose")).unwrap();
+        fs::create_dir_all(dir.join("elsewhere/notes")).unwrap();
+        let cheatsheet = dir.join("source/docker/compose/up.md");
+        fs::write(
+            &cheatsheet,
+            "### Start\n\n```bash\ndocker compose up\n```\n",
+        )
+        .unwrap();
+        let outside = dir.join("elsewhere/notes/misc.md");
+        fs::write(&outside, "### Misc\n").unwrap();
+        let conf = CognitioConfig {
+            cheatsheets: vec![CheatsheetData::Simple(
+                dir.join("source").to_string_lossy().to_string(),
+            )],
+            ..CognitioConfig::default()
+        };
+        let location = |path: &Path| {
+            let out = export_cheatsheet(&conf, &path.to_string_lossy(), &dir).unwrap();
+            let html = fs::read_to_string(out).unwrap();
+            let start = html.find("<p class=\"page-path\">").unwrap();
+            html[start..html[start..].find("</p>").unwrap() + start].to_string()
+        };
+
+        assert_eq!(
+            location(&cheatsheet),
+            "<p class=\"page-path\">docker / compose"
+        );
+        // The canonical path the app checked finds the same menu item.
+        assert_eq!(
+            location(&fs::canonicalize(&cheatsheet).unwrap()),
+            "<p class=\"page-path\">docker / compose"
+        );
+        assert_eq!(location(&outside), "<p class=\"page-path\">notes");
+    }
+
+    #[test]
+    fn exports_a_site_with_relative_links() {
+        let dir = std::env::temp_dir().join(format!("cognitio-export-site-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let source = dir.join("source");
+        fs::create_dir_all(source.join("docker/compose")).unwrap();
+        fs::create_dir_all(source.join("empty")).unwrap();
+        fs::write(source.join("root.md"), "### Root\n\nAt the top.\n").unwrap();
+        fs::write(
+            source.join("docker/run.md"),
+            "### Run\n\nSee [compose](compose/up.md#up).\n\n```bash\ndocker run -it ubuntu\n```\n",
+        )
+        .unwrap();
+        fs::write(
+            source.join("docker/compose/up.md"),
+            "### Up\n\n```bash\ndocker compose up\n```\n\n### Up\n\nAgain, detached.\n",
+        )
+        .unwrap();
+        let conf = CognitioConfig {
+            cheatsheets: vec![CheatsheetData::Simple(source.to_string_lossy().to_string())],
+            ..CognitioConfig::default()
+        };
+        let site = dir.join("site");
+
+        let summary = export_library(&conf, &site).unwrap();
+        assert_eq!((summary.cheatsheets, summary.sections), (3, 4));
+        assert!(summary.skipped.is_empty());
+
+        // One directory per source, with the cheatsheets where they are in the source.
+        let read = |path: &str| fs::read_to_string(site.join(path)).unwrap();
+        assert_eq!(read("assets/cognitio.js"), SITE_JS);
+        assert!(read("assets/cognitio.css").starts_with(SITE_CSS));
+        assert!(site.join("source/root.html").is_file());
+        assert!(site.join("source/docker/run.html").is_file());
+        assert!(!site.join("source/empty").exists());
+
+        // Pages only link relatively: to the root, to each other and to other cheatsheets.
+        let up = read("source/docker/compose/up.html");
+        assert!(up.contains("data-root=\"../../../\""));
+        assert!(up.contains("href=\"../../../index.html\""));
+        assert!(up.contains("href=\"../../../assets/cognitio.css\""));
+        assert!(up.contains("href=\"../../../source/docker/run.html\""));
+        assert!(up.contains("<p class=\"page-path\">docker / compose</p>"));
+        assert!(up.contains("id=\"up\"") && up.contains("id=\"up-2\""));
+        let run = read("source/docker/run.html");
+        assert!(run.contains("href=\"compose/up.html#up\""));
+        let index = read("index.html");
+        assert!(index.contains("href=\"source/docker/compose/up.html\""));
+        assert!(index.contains("3 cheatsheets with 4 sections"));
+        assert!(!index.contains(&dir.to_string_lossy().to_string()));
+
+        let entries: Vec<serde_json::Value> =
+            serde_json::from_str(&read("search-index.json")).unwrap();
+        let entry = |url: &str| {
+            entries
+                .iter()
+                .find(|entry| entry["url"] == url)
+                .unwrap_or_else(|| panic!("no search entry for {url}"))
+        };
+        assert_eq!(entries.len(), 4);
+        assert_eq!(
+            entry("source/root.html#root")["cheatsheet"],
+            "root in source"
+        );
+        let run = entry("source/docker/run.html#run");
+        assert_eq!(run["title"], "Run");
+        assert_eq!(run["cheatsheet"], "run in docker");
+        assert!(run["text"]
+            .as_str()
+            .unwrap()
+            .contains("docker run -it ubuntu"));
+        assert_eq!(
+            entry("source/docker/compose/up.html#up-2")["text"],
+            "Again, detached."
+        );
+    }
+}
@@ -0,0 +1,563 @@
+//! Rendering cheatsheets to HTML the way the app shows them.
+//!
+//! Every h3 section becomes a card, `<div class="mk-section">`, with its prose in
+//! `mk-text-block` divs and each code block in an `mk-code-block` with a copy button. The `mk-`
+//! classes are the ones styled by `Cheatsheet.css` in the app.
+//!
+//! Code is highlighted here rather than in the browser, and everything from the markdown is
+//! escaped. Raw HTML in prose is shown as written, except for a few tags without attributes that
+//! cheatsheets use for collapsible parts and keys, see [`ALLOWED_TAGS`].
+
+use crate::snippet::{Block, Cheatsheet, CodeBlock, Snippet};
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use std::collections::HashSet;
+use std::fmt::Write;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Color, Theme, ThemeSet};
+use syntect::html::{styled_line_to_highlighted_html, IncludeBackground};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+const THEME: &str = "base16-ocean.dark";
+
+/// Raw HTML tags kept in prose. Only `<details open>` may have an attribute.
+pub const ALLOWED_TAGS: [&str; 6] = ["details", "summary", "kbd", "sub", "sup", "br"];
+
+/// Renders cheatsheets to HTML with syntax highlighted code blocks.
+pub struct HtmlRenderer {
+    syntaxes: SyntaxSet,
+    theme: Theme,
+    html_links: bool,
+}
+
+impl HtmlRenderer {
+    /// With `html_links`, relative links to other `.md` files point to `.html` files instead, for
+    /// cheatsheets exported as a site.
+    pub fn new(html_links: bool) -> HtmlRenderer {
+        let mut themes = ThemeSet::load_defaults();
+        HtmlRenderer {
+            syntaxes: SyntaxSet::load_defaults_newlines(),
+            theme: themes.themes.remove(THEME).unwrap_or_default(),
+            html_links,
+        }
+    }
+
+    /// Renders a whole cheatsheet: its title, the h2 groups with their intro text, and the cards.
+    pub fn render_cheatsheet(&self, cheatsheet: &Cheatsheet) -> String {
+        let id_prefix = id_prefix(&cheatsheet.path);
+        let mut anchors = section_anchors(cheatsheet).into_iter();
+        let mut html = String::new();
+
+        if let Some(title) = &cheatsheet.title {
+            let _ = writeln!(html, "<h1 class=\"mk-h1\">{}</h1>", escape(title));
+        }
+        for (group_index, group) in cheatsheet.groups.iter().enumerate() {
+            if let Some(title) = &group.title {
+                let _ = writeln!(html, "<h2 class=\"mk-h2\">{}</h2>", escape(title));
+            }
+            html.push_str(&self.render_blocks(&group.intro, &format!("{id_prefix}g{group_index}")));
+            for section in &group.sections {
+                let anchor = anchors.next().unwrap_or_default();
+                html.push_str(&self.render_section(section, &anchor, &id_prefix));
+            }
+        }
+        html
+    }
+
+    /// Renders one section as a card. `anchor` is the card's id, and `id_prefix` keeps the ids of
+    /// code blocks unique when several cheatsheets are on one page.
+    pub fn render_section(&self, section: &Snippet, anchor: &str, id_prefix: &str) -> String {
+        format!(
+            "<div class=\"mk-section\" id=\"{}\">\n<h3 class=\"mk-h3\">{}</h3>\n{}</div>\n",
+            escape(anchor),
+            escape(&section.title),
+            self.render_blocks(
+                &section.blocks,
+                &format!("{id_prefix}s{}", section.start_line)
+            )
+        )
+    }
+
+    /// Consecutive prose goes in one `mk-text-block`, code blocks each get an `mk-code-block`.
+    /// Ids are made from `id_prefix` and the block's position, without dashes since the app finds
+    /// a copy button's code by the part of its id after the last dash.
+    fn render_blocks(&self, blocks: &[Block], id_prefix: &str) -> String {
+        let mut html = String::new();
+        let mut prose = String::new();
+
+        for (index, block) in blocks.iter().enumerate() {
+            match block {
+                Block::Prose(block) => prose.push_str(&self.render_markdown(&block.markdown)),
+                Block::Code(code) => {
+                    flush_text_block(&mut html, &mut prose);
+                    html.push_str(&self.render_code_block(code, &format!("{id_prefix}b{index}")));
+                }
+            }
+        }
+        flush_text_block(&mut html, &mut prose);
+        html
+    }
+
+    fn render_code_block(&self, code: &CodeBlock, id: &str) -> String {
+        format!(
+            "<div class=\"mk-code-block\">\
+             <div id=\"mk-code-boundary-{id}\" class=\"mk-code-boundary\">\
+             <pre id=\"mk-pre-{id}\" class=\"mk-pre\">{}</pre>\
+             </div>\
+             <input id=\"mk-copy-btn-{id}\" class=\"mk-copy-btn\" type=\"button\" value=\"Copy\">\
+             </div>\n",
+            self.render_code(&code.code, code.language.as_deref())
+        )
+    }
+
+    /// A `<code>` element with the highlighted code. Unknown languages are shown as plain text.
+    fn render_code(&self, code: &str, language: Option<&str>) -> String {
+        let code = code.strip_suffix('\n').unwrap_or(code);
+        let class = match language {
+            Some(language) => format!("hljs language-{}", escape(language)),
+            None => "hljs".to_string(),
+        };
+        let syntax = language.and_then(|language| self.syntaxes.find_syntax_by_token(language));
+
+        let body = match syntax {
+            Some(syntax) => {
+                let mut highlighter = HighlightLines::new(syntax, &self.theme);
+                LinesWithEndings::from(code)
+                    .map(|line| {
+                        highlighter
+                            .highlight_line(line, &self.syntaxes)
+                            .and_then(|regions| {
+                                styled_line_to_highlighted_html(&regions, IncludeBackground::No)
+                            })
+                            .unwrap_or_else(|_| escape(line))
+                    })
+                    .collect()
+            }
+            None => escape(code),
+        };
+        format!("<code class=\"{class}\">{body}</code>")
+    }
+
+    /// Renders prose markdown with the `mk-` classes.
+    pub fn render_markdown(&self, markdown: &str) -> String {
+        let options =
+            Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+        let mut writer = MarkdownWriter {
+            renderer: self,
+            html: String::new(),
+            code: None,
+            image_depth: 0,
+            html_block: None,
+            open_tags: Vec::new(),
+            alignments: Vec::new(),
+            in_head: false,
+            cell: 0,
+        };
+        for event in Parser::new_ext(markdown, options) {
+            writer.event(event);
+        }
+        while let Some(tag) = writer.open_tags.pop() {
+            let _ = writeln!(writer.html, "</{tag}>");
+        }
+        writer.html
+    }
+
+    /// CSS for the background and default text color of code blocks, from the highlighting theme.
+    pub fn code_css(&self) -> String {
+        let background = self.theme.settings.background.map(css_color);
+        let foreground = self.theme.settings.foreground.map(css_color);
+        format!(
+            ".mk-pre {{ background-color: {}; color: {}; }}\n",
+            background.as_deref().unwrap_or("#2b303b"),
+            foreground.as_deref().unwrap_or("#c0c5ce")
+        )
+    }
+
+    /// Links are kept when they are `http`, `https` or `mailto` links or relative, other schemes
+    /// become `#`. Browsers drop tabs, newlines and other control characters from URLs, so the
+    /// scheme is looked for without them and without whitespace.
+    fn link_href(&self, url: &str) -> String {
+        let url: String = url
+            .trim_matches(|c: char| c.is_whitespace() || c.is_control())
+            .chars()
+            .filter(|c| !c.is_control())
+            .collect();
+        let compact: String = url.chars().filter(|c| !c.is_whitespace()).collect();
+        let scheme = compact
+            .split_once(':')
+            .map(|(scheme, _)| scheme)
+            .filter(|scheme| !scheme.contains(['/', '?', '#']))
+            .map(str::to_ascii_lowercase);
+        match scheme.as_deref() {
+            Some("http" | "https" | "mailto") => return url,
+            Some(_) => return "#".to_string(),
+            None => {}
+        }
+        if !self.html_links || url.starts_with(['/', '#']) {
+            return url;
+        }
+
+        let (path, fragment) = match url.split_once('#') {
+            Some((path, fragment)) => (path, Some(fragment)),
+            None => (url.as_str(), None),
+        };
+        match (path.strip_suffix(".md"), fragment) {
+            (Some(stem), Some(fragment)) => format!("{stem}.html#{fragment}"),
+            (Some(stem), None) => format!("{stem}.html"),
+            (None, _) => url.clone(),
+        }
+    }
+}
+
+impl Default for HtmlRenderer {
+    fn default() -> HtmlRenderer {
+        HtmlRenderer::new(false)
+    }
+}
+
+/// Writes the events of prose markdown as HTML.
+struct MarkdownWriter<'a> {
+    renderer: &'a HtmlRenderer,
+    html: String,
+    /// The language and code of the code block being written, for code nested in lists and quotes.
+    code: Option<(Option<String>, String)>,
+    /// Inside an image, text goes into the `alt` attribute.
+    image_depth: usize,
+    /// The raw HTML of the HTML block being written.
+    html_block: Option<String>,
+    /// The allowed raw HTML tags that are open. Closing tags without an open tag are escaped, and
+    /// tags left open are closed at the end.
+    open_tags: Vec<String>,
+    alignments: Vec<Alignment>,
+    /// Cells of the header row are `<th>`.
+    in_head: bool,
+    cell: usize,
+}
+
+impl MarkdownWriter<'_> {
+    fn event(&mut self, event: Event) {
+        if let Some((_, code)) = self.code.as_mut() {
+            match event {
+                Event::Text(text) => code.push_str(&text),
+                Event::End(TagEnd::CodeBlock) => {
+                    let (language, code) = self.code.take().unwrap_or_default();
+                    let code = self.renderer.render_code(&code, language.as_deref());
+                    let _ = writeln!(self.html, "<pre class=\"mk-pre\">{code}</pre>");
+                }
+                _ => {}
+            }
+            return;
+        }
+        if self.image_depth > 0 {
+            match event {
+                Event::Start(Tag::Image { .. }) => self.image_depth += 1,
+                Event::End(TagEnd::Image) => {
+                    self.image_depth -= 1;
+                    if self.image_depth == 0 {
+                        self.html.push_str("\">");
+                    }
+                }
+                Event::Text(text)
+                | Event::Code(text)
+                | Event::Html(text)
+                | Event::InlineHtml(text) => self.html.push_str(&escape(&text)),
+                _ => {}
+            }
+            return;
+        }
+        if let Some(block) = self.html_block.as_mut() {
+            match event {
+                Event::Html(text) | Event::Text(text) => block.push_str(&text),
+                Event::End(TagEnd::HtmlBlock) => {
+                    let block = self.html_block.take().unwrap_or_default();
+                    let (html, starts_with_tag) = self.raw_html(&block);
+                    // A block opening a `<details>` is left unwrapped, so that the markdown up to
+                    // its closing tag ends up inside it.
+                    if starts_with_tag {
+                        let _ = writeln!(self.html, "{}", html.trim_end());
+                    } else {
+                        let _ = writeln!(self.html, "<p class=\"mk-p\">{html}</p>");
+                    }
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => self.html.push_str(&escape(&text)),
+            Event::Html(text) | Event::InlineHtml(text) => {
+                let html = self.raw_html(&text).0;
+                self.html.push_str(&html);
+            }
+            Event::Code(text) => {
+                let _ = write!(
+                    self.html,
+                    "<code class=\"mk-inline-code\">{}</code>",
+                    escape(&text)
+                );
+            }
+            Event::SoftBreak => self.html.push('\n'),
+            Event::HardBreak => self.html.push_str("<br>\n"),
+            Event::Rule => self.html.push_str("<hr class=\"mk-hr\">\n"),
+            Event::TaskListMarker(checked) => {
+                let checked = if checked { " checked" } else { "" };
+                let _ = write!(self.html, "<input type=\"checkbox\" disabled{checked}> ");
+            }
+            Event::InlineMath(text) | Event::DisplayMath(text) => {
+                self.html.push_str(&escape(&text))
+            }
+            Event::FootnoteReference(label) => {
+                let _ = write!(self.html, "[{}]", escape(&label));
+            }
+        }
+    }
+
+    /// Escapes raw HTML except for the [`ALLOWED_TAGS`], and tells whether it starts with one of
+    /// them.
+    fn raw_html(&mut self, text: &str) -> (String, bool) {
+        let mut html = String::new();
+        let mut rest = text;
+        let mut starts_with_tag = false;
+        while let Some(start) = rest.find('<') {
+            let tag = rest[start..].find('>').and_then(|length| {
+                let end = start + length + 1;
+                let tag = self.keep_tag(&rest[start..end])?;
+                Some((tag, end))
+            });
+            match tag {
+                Some((tag, end)) => {
+                    starts_with_tag |= html.is_empty() && rest[..start].trim().is_empty();
+                    html.push_str(&escape(&rest[..start]));
+                    html.push_str(&tag);
+                    rest = &rest[end..];
+                }
+                None => {
+                    html.push_str(&escape(&rest[..=start]));
+                    rest = &rest[start + 1..];
+                }
+            }
+        }
+        html.push_str(&escape(rest));
+        (html, starts_with_tag)
+    }
+
+    /// The HTML to keep for a raw tag, if it's allowed. A closing tag also closes the tags opened
+    /// after its opening tag.
+    fn keep_tag(&mut self, tag: &str) -> Option<String> {
+        let (name, closing, attribute) = allowed_tag(tag)?;
+        if !closing {
+            let html = format!("<{name}{attribute}>");
+            if name != "br" {
+                self.open_tags.push(name);
+            }
+            return Some(html);
+        }
+        let index = self.open_tags.iter().rposition(|open| *open == name)?;
+        Some(
+            self.open_tags
+                .drain(index..)
+                .rev()
+                .map(|name| format!("</{name}>"))
+                .collect(),
+        )
+    }
+
+    fn start(&mut self, tag: Tag) {
+        let html = &mut self.html;
+        match tag {
+            Tag::Paragraph => html.push_str("<p class=\"mk-p\">"),
+            Tag::HtmlBlock => self.html_block = Some(String::new()),
+            Tag::Heading { level, .. } => {
+                let _ = write!(html, "<{level} class=\"mk-{level}\">");
+            }
+            Tag::BlockQuote(_) => html.push_str("<blockquote class=\"mk-blockquote\">\n"),
+            Tag::CodeBlock(kind) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => info
+                        .split_whitespace()
+                        .next()
+                        .map(|language| language.to_string()),
+                    CodeBlockKind::Indented => None,
+                };
+                self.code = Some((language, String::new()));
+            }
+            Tag::List(Some(1)) => html.push_str("<ol class=\"mk-ol\">\n"),
+            Tag::List(Some(start)) => {
+                let _ = writeln!(html, "<ol class=\"mk-ol\" start=\"{start}\">");
+            }
+            Tag::List(None) => html.push_str("<ul class=\"mk-ul\">\n"),
+            Tag::Item => html.push_str("<li>"),
+            Tag::Table(alignments) => {
+                self.alignments = alignments;
+                html.push_str("<table class=\"mk-table\">");
+            }
+            Tag::TableHead => {
+                self.in_head = true;
+                self.cell = 0;
+                html.push_str("<thead class=\"mk-table-head\"><tr class=\"mk-table-row\">");
+            }
+            Tag::TableRow => {
+                self.cell = 0;
+                html.push_str("<tr class=\"mk-table-row\">");
+            }
+            Tag::TableCell => {
+                let align = match self.alignments.get(self.cell) {
+                    Some(Alignment::Left) => " style=\"text-align: left;\"",
+                    Some(Alignment::Center) => " style=\"text-align: center;\"",
+                    Some(Alignment::Right) => " style=\"text-align: right;\"",
+                    _ => "",
+                };
+                let element = if self.in_head { "th" } else { "td" };
+                let _ = write!(html, "<{element} class=\"mk-table-cell\"{align}>");
+            }
+            Tag::Emphasis => html.push_str("<em>"),
+            Tag::Strong => html.push_str("<strong>"),
+            Tag::Strikethrough => html.push_str("<del>"),
+            Tag::Link {
+                dest_url, title, ..
+            } => {
+                let href = self.renderer.link_href(&dest_url);
+                let _ = write!(html, "<a href=\"{}\" class=\"mk-link\"", escape(&href));
+                if !title.is_empty() {
+                    let _ = write!(html, " title=\"{}\"", escape(&title));
+                }
+                html.push('>');
+            }
+            Tag::Image {
+                dest_url, title, ..
+            } => {
+                let _ = write!(html, "<img src=\"{}\"", escape(&dest_url));
+                if !title.is_empty() {
+                    let _ = write!(html, " title=\"{}\"", escape(&title));
+                }
+                html.push_str(" alt=\"");
+                self.image_depth = 1;
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        let html = &mut self.html;
+        match tag {
+            TagEnd::Paragraph => html.push_str("</p>\n"),
+            TagEnd::Heading(level) => {
+                let _ = writeln!(html, "</{level}>");
+            }
+            TagEnd::BlockQuote(_) => html.push_str("</blockquote>\n"),
+            TagEnd::List(true) => html.push_str("</ol>\n"),
+            TagEnd::List(false) => html.push_str("</ul>\n"),
+            TagEnd::Item => html.push_str("</li>\n"),
+            TagEnd::Table => html.push_str("</tbody></table>\n"),
+            TagEnd::TableHead => {
+                self.in_head = false;
+                html.push_str("</tr></thead><tbody class=\"mk-table-body\">");
+            }
+            TagEnd::TableRow => html.push_str("</tr>"),
+            TagEnd::TableCell => {
+                self.cell += 1;
+                html.push_str(if self.in_head { "</th>" } else { "</td>" });
+            }
+            TagEnd::Emphasis => html.push_str("</em>"),
+            TagEnd::Strong => html.push_str("</strong>"),
+            TagEnd::Strikethrough => html.push_str("</del>"),
+            TagEnd::Link => html.push_str("</a>"),
+            _ => {}
+        }
+    }
+}
+
+fn flush_text_block(html: &mut String, prose: &mut String) {
+    if !prose.is_empty() {
+        let _ = write!(html, "<div class=\"mk-text-block\">\n{prose}</div>\n");
+        prose.clear();
+    }
+}
+
+/// The id of every section's card, in file order: the title as a slug, with a number added to
+/// titles that are used more than once.
+pub fn section_anchors(cheatsheet: &Cheatsheet) -> Vec<String> {
+    let mut used = HashSet::new();
+    cheatsheet
+        .sections()
+        .map(|section| {
+            let slug = slug(&section.title);
+            let mut anchor = slug.clone();
+            let mut number = 2;
+            while !used.insert(anchor.clone()) {
+                anchor = format!("{slug}-{number}");
+                number += 1;
+            }
+            anchor
+        })
+        .collect()
+}
+
+/// The name of an allowed tag, whether it's a closing tag, and its attribute, for tags like
+/// `<kbd>`, `</kbd>`, `<br/>` and `<details open>`.
+fn allowed_tag(tag: &str) -> Option<(String, bool, &'static str)> {
+    let inner = tag.strip_prefix('<')?.strip_suffix('>')?;
+    let (inner, closing) = match inner.strip_prefix('/') {
+        Some(inner) => (inner, true),
+        None => (inner.trim_end().trim_end_matches('/'), false),
+    };
+    let mut words = inner.split_ascii_whitespace();
+    let name = words.next()?.to_ascii_lowercase();
+    if !ALLOWED_TAGS.contains(&name.as_str()) || (closing && name == "br") {
+        return None;
+    }
+    match (words.next(), words.next()) {
+        (None, _) => Some((name, closing, "")),
+        (Some("open"), None) if !closing && name == "details" => Some((name, closing, " open")),
+        _ => None,
+    }
+}
+
+/// Lowercase letters and digits, with everything else turned into single dashes.
+pub fn slug(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "section".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+/// Escapes text for use in HTML content and attribute values.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A short id for a cheatsheet, so element ids don't clash between cheatsheets on one page.
+fn id_prefix(path: &str) -> String {
+    let hash = path.bytes().fold(0x811c9dc5u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x01000193)
+    });
+    format!("c{hash:08x}")
+}
+
+fn css_color(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
@@ -0,0 +1,55 @@
+//! Shared building blocks for Cognitio.
+//!
+//! Both the desktop app (`src-tauri`) and the command line tool (`src-cli`) are built on this
+//! crate, so they agree on how `cognitio.yaml` is read, how cheatsheet sources are scanned and
+//! how cheatsheet content is loaded.
+//!
+//! - [`config`]: locating and reading `cognitio.yaml`.
+//! - [`library`]: scanning the configured cheatsheet sources into a [`Directory`] tree.
+//! - [`sources`]: cloning and updating git cheatsheet sources.
+//! - [`access`]: keeping paths from the frontend inside the cheatsheet sources.
+//! - [`content`]: reading cheatsheet files.
+//! - [`snippet`]: parsing cheatsheet files into sections and code blocks.
+//! - [`edit`]: creating cheatsheets and changing their sections.
+//! - [`search`]: full-text search over snippets.
+//! - [`placeholder`]: `{{name}}` placeholders in snippet code.
+//! - [`editor`]: opening files in the configured editor.
+//! - [`navi`]: importing navi `.cheat` files as cheatsheets.
+//! - [`watch`]: file system watching (requires the `watch` feature).
+//! - [`shorthand`]: shorthand IDs for terminal use (requires the `cli` feature).
+//! - [`html`] and [`export`]: rendering cheatsheets to HTML and exporting them as a site
+//!   (requires the `html` feature).
+//! - [`serve`]: a local HTTP server with a JSON API and HTML views (requires the `serve`
+//!   feature).
+
+pub mod access;
+pub mod config;
+pub mod content;
+pub mod edit;
+pub mod editor;
+pub mod library;
+pub mod navi;
+pub mod placeholder;
+pub mod search;
+pub mod snippet;
+pub mod sources;
+
+#[cfg(feature = "cli")]
+pub mod shorthand;
+
+#[cfg(feature = "watch")]
+pub mod watch;
+
+#[cfg(feature = "html")]
+pub mod export;
+#[cfg(feature = "html")]
+pub mod html;
+
+#[cfg(feature = "serve")]
+mod http;
+#[cfg(feature = "serve")]
+pub mod serve;
+
+pub use config::{CheatsheetData, CheatsheetInfo, CognitioConfig};
+pub use library::{Directory, DirectoryFile};
+pub use snippet::{Cheatsheet, Snippet};
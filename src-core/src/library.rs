@@ -0,0 +1,340 @@
+use crate::config::{CheatsheetData, CognitioConfig};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A markdown cheatsheet file.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DirectoryFile {
+    pub name: String,
+    pub path: String,
+    /// Short ID used to refer to the file from the terminal. Empty unless assigned.
+    #[serde(default)]
+    pub shorthand_id: String,
+}
+
+/// A directory of cheatsheets. The top level directories are the configured sources.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Directory {
+    pub name: String,
+    pub path: String,
+    pub files: Vec<DirectoryFile>,
+    pub sub_directories: Vec<Directory>,
+    /// Short ID used to refer to the directory from the terminal. Empty unless assigned.
+    #[serde(default)]
+    pub shorthand_id: String,
+}
+
+/// Scans every cheatsheet source in the config, one [`Directory`] per source.
+pub fn list_cheatsheet_directories(conf: &CognitioConfig) -> Vec<Directory> {
+    conf.cheatsheets
+        .iter()
+        .map(list_cheatsheet_source)
+        .collect()
+}
+
+/// Scans a single cheatsheet source into a [`Directory`] tree, including markdown files placed
+/// directly in the source directory.
+pub fn list_cheatsheet_source(source: &CheatsheetData) -> Directory {
+    let path = match source.expanded_path() {
+        Ok(expanded) if matches!(source, CheatsheetData::Git(_)) => {
+            if !expanded.is_dir() {
+                warn!(
+                    "Git source {} has no {}, run `cognitio sources sync`",
+                    source.path(),
+                    expanded.display()
+                );
+            }
+            expanded
+        }
+        Ok(expanded) => {
+            if !expanded.is_dir() {
+                warn!(
+                    "Cheatsheet source {} ({}) is not a directory, it will be empty",
+                    source.path(),
+                    expanded.display()
+                );
+            }
+            expanded
+        }
+        Err(error) => {
+            warn!(
+                "Cheatsheet source {} can't be expanded, it will be empty: {error}",
+                source.path()
+            );
+            PathBuf::from(source.path())
+        }
+    };
+
+    let name = match source {
+        CheatsheetData::Simple(_) => path
+            .file_name()
+            .unwrap_or_default()
+            .to_str()
+            .unwrap_or_default()
+            .to_string(),
+        CheatsheetData::Git(git) => git.title.clone(),
+        CheatsheetData::Info(info) => info.title.clone(),
+    };
+
+    let mut ancestors = HashSet::new();
+    if let Ok(canonical) = fs::canonicalize(&path) {
+        ancestors.insert(canonical);
+    }
+
+    let path_string = path.to_string_lossy().to_string();
+    Directory {
+        name,
+        files: list_files_in_directory(&path_string),
+        sub_directories: scan_subdirectories(&path, source.max_depth(), &mut ancestors),
+        path: path_string,
+        shorthand_id: String::new(),
+    }
+}
+
+/// Lists the non-hidden directories below `root_dir` recursively, with their markdown files.
+///
+/// At most `max_depth` levels are scanned. A directory that is its own ancestor, for example through
+/// a symlink pointing back up the tree, is skipped.
+pub fn list_subdirectories(root_dir: &str, max_depth: usize) -> Vec<Directory> {
+    let mut ancestors = HashSet::new();
+    if let Ok(canonical) = fs::canonicalize(root_dir) {
+        ancestors.insert(canonical);
+    }
+    scan_subdirectories(Path::new(root_dir), max_depth, &mut ancestors)
+}
+
+fn scan_subdirectories(
+    root_dir: &Path,
+    depth_left: usize,
+    ancestors: &mut HashSet<PathBuf>,
+) -> Vec<Directory> {
+    let mut directories = Vec::new();
+
+    if depth_left == 0 {
+        return directories;
+    }
+
+    if let Ok(entries) = fs::read_dir(root_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+
+            let Some(name) = entry_name(&path) else {
+                continue;
+            };
+            if name.starts_with('.') {
+                continue;
+            }
+
+            let canonical = match fs::canonicalize(&path) {
+                Ok(canonical) => canonical,
+                Err(error) => {
+                    warn!("Skipping {}: {error:?}", path.display());
+                    continue;
+                }
+            };
+            if !ancestors.insert(canonical.clone()) {
+                warn!("Skipping {}: symlink loop", path.display());
+                continue;
+            }
+
+            let path_str = path.to_string_lossy().to_string();
+            directories.push(Directory {
+                name,
+                files: list_files_in_directory(&path_str),
+                sub_directories: scan_subdirectories(&path, depth_left - 1, ancestors),
+                path: path_str,
+                shorthand_id: String::new(),
+            });
+
+            ancestors.remove(&canonical);
+        }
+    }
+
+    directories
+}
+
+/// Lists the markdown files directly inside `directory_path`.
+pub fn list_files_in_directory(directory_path: &str) -> Vec<DirectoryFile> {
+    let mut files = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(directory_path) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            let Some(name) = entry_name(&path) else {
+                continue;
+            };
+            if name.ends_with(".md") {
+                files.push(DirectoryFile {
+                    name,
+                    path: path.to_string_lossy().to_string(),
+                    shorthand_id: String::new(),
+                });
+            }
+        }
+    }
+
+    files
+}
+
+/// The name of a file or directory. Names that aren't UTF-8 can't be passed on as strings, so they
+/// are skipped with a warning.
+fn entry_name(path: &Path) -> Option<String> {
+    let name = path.file_name()?;
+    match name.to_str() {
+        Some(name) => Some(name.to_string()),
+        None => {
+            warn!("Skipping {}: the name is not UTF-8", path.display());
+            None
+        }
+    }
+}
+
+/// Returns a copy of the tree with directories and files sorted by name at every level.
+pub fn sort_directories_and_files(dirs: &[Directory]) -> Vec<Directory> {
+    let mut sorted_dirs = dirs.to_vec();
+    sorted_dirs.sort_by(|a, b| a.name.cmp(&b.name));
+    sorted_dirs.iter_mut().for_each(|dir| {
+        dir.files.sort_by(|a, b| a.name.cmp(&b.name));
+        dir.sub_directories = sort_directories_and_files(&dir.sub_directories);
+    });
+    sorted_dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::symlink;
+    use std::process;
+
+    /// A fresh `source` tree:
+    ///
+    /// ```text
+    /// source/root.md, notes.txt
+    /// source/docker/run.md, back -> docker
+    /// source/docker/compose/up.md
+    /// source/docker/compose/deep/deeper/bottom.md
+    /// source/.git/hidden.md
+    /// ```
+    fn setup(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cognitio-library-{}-{name}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let source = dir.join("source");
+        fs::create_dir_all(source.join("docker/compose/deep/deeper")).unwrap();
+        fs::create_dir_all(source.join(".git")).unwrap();
+        fs::write(source.join("root.md"), "### Root\n").unwrap();
+        fs::write(source.join("notes.txt"), "notes").unwrap();
+        fs::write(source.join("docker/run.md"), "### Run\n").unwrap();
+        fs::write(source.join("docker/compose/up.md"), "### Up\n").unwrap();
+        fs::write(
+            source.join("docker/compose/deep/deeper/bottom.md"),
+            "### Bottom\n",
+        )
+        .unwrap();
+        fs::write(source.join(".git/hidden.md"), "### Hidden\n").unwrap();
+        symlink(source.join("docker"), source.join("docker/back")).unwrap();
+        source
+    }
+
+    fn names(directories: &[Directory]) -> Vec<String> {
+        sort_directories_and_files(directories)
+            .iter()
+            .map(|directory| directory.name.clone())
+            .collect()
+    }
+
+    fn file_names(files: &[DirectoryFile]) -> Vec<String> {
+        let mut names: Vec<String> = files.iter().map(|file| file.name.clone()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn scans_nested_directories() {
+        let source = setup("nested");
+        let tree = list_cheatsheet_source(&CheatsheetData::Simple(
+            source.to_string_lossy().to_string(),
+        ));
+        assert_eq!(tree.name, "source");
+        // Markdown files at the root of the source are listed, other files aren't.
+        assert_eq!(file_names(&tree.files), ["root.md"]);
+        // Hidden directories are skipped.
+        assert_eq!(names(&tree.sub_directories), ["docker"]);
+
+        let docker = &tree.sub_directories[0];
+        assert_eq!(file_names(&docker.files), ["run.md"]);
+        assert_eq!(docker.path, source.join("docker").to_string_lossy());
+        // The link back to `docker` is its own ancestor, so only `compose` is below it.
+        assert_eq!(names(&docker.sub_directories), ["compose"]);
+        let compose = &docker.sub_directories[0];
+        assert_eq!(file_names(&compose.files), ["up.md"]);
+        let deeper = &compose.sub_directories[0].sub_directories[0];
+        assert_eq!(file_names(&deeper.files), ["bottom.md"]);
+        assert_eq!(
+            deeper.files[0].path,
+            source
+                .join("docker/compose/deep/deeper/bottom.md")
+                .to_string_lossy()
+        );
+    }
+
+    #[test]
+    fn stops_at_the_depth_limit() {
+        let source = setup("depth");
+        let source = source.to_string_lossy();
+        assert!(list_subdirectories(&source, 0).is_empty());
+
+        let one_level = list_subdirectories(&source, 1);
+        assert_eq!(names(&one_level), ["docker"]);
+        assert!(one_level[0].sub_directories.is_empty());
+
+        let two_levels = list_subdirectories(&source, 2);
+        let docker = &two_levels[0];
+        assert_eq!(names(&docker.sub_directories), ["compose"]);
+        assert!(docker.sub_directories[0].sub_directories.is_empty());
+    }
+
+    #[test]
+    fn follows_links_that_are_not_loops() {
+        let source = setup("links");
+        let other = source.parent().unwrap().join("other");
+        fs::create_dir_all(&other).unwrap();
+        fs::write(other.join("linked.md"), "### Linked\n").unwrap();
+        symlink(&other, source.join("other")).unwrap();
+        // A link to the source itself is a loop, even from a sibling.
+        symlink(&source, source.join("docker/compose/top")).unwrap();
+
+        let directories = list_subdirectories(&source.to_string_lossy(), 10);
+        assert_eq!(names(&directories), ["docker", "other"]);
+        let other = directories.iter().find(|d| d.name == "other").unwrap();
+        assert_eq!(file_names(&other.files), ["linked.md"]);
+        let docker = directories.iter().find(|d| d.name == "docker").unwrap();
+        assert_eq!(names(&docker.sub_directories[0].sub_directories), ["deep"]);
+    }
+
+    #[test]
+    fn skips_names_that_are_not_utf8() {
+        let source = setup("utf8");
+        let name = std::ffi::OsStr::from_bytes(b"bad-\xff");
+        let file = source.join(name).with_extension("md");
+        if fs::write(&file, "### Bad\n").is_err() {
+            // Some file systems only allow UTF-8 names.
+            return;
+        }
+        fs::create_dir_all(source.join(name)).unwrap();
+
+        let files = list_files_in_directory(&source.to_string_lossy());
+        assert_eq!(file_names(&files), ["root.md"]);
+        let directories = list_subdirectories(&source.to_string_lossy(), 1);
+        assert_eq!(names(&directories), ["docker"]);
+    }
+}
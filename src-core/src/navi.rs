@@ -0,0 +1,364 @@
+//! Importing [navi](https://github.com/denisidoro/navi) `.cheat` files as cheatsheets.
+//!
+//! A `.cheat` file becomes a cheatsheet like this:
+//!
+//! - `% tags` starts an h2 group titled with the tags.
+//! - `@ tags`, which makes navi reuse the variables of other cheats, is kept as a line of prose
+//!   below the group heading.
+//! - Each command becomes an h3 section titled with its `# description`, or with the command as
+//!   inline code when it has none, and the command goes in a `bash` code block. Earlier
+//!   description lines are kept as prose.
+//! - `<name>` variables become `{{name}}` placeholders. A `$ name: command` generator that only
+//!   prints fixed lines, like `echo -e "dev\nprod"`, becomes choices, `{{name:dev|prod}}`. Other
+//!   generators are listed below the code block, since Cognitio doesn't run them.
+//! - `; comments` are dropped, like navi ignores them.
+
+use crate::snippet::parse_cheatsheet;
+use log::{info, warn};
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// What [`import_navi`] wrote.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ImportSummary {
+    pub cheatsheets: usize,
+    pub sections: usize,
+    /// `.cheat` files that weren't imported, with the reason.
+    pub skipped: Vec<String>,
+}
+
+/// Commands under the same `%` line.
+#[derive(Default)]
+struct Group {
+    tags: Option<String>,
+    extends: Vec<String>,
+    variables: Vec<Variable>,
+    commands: Vec<Command>,
+}
+
+#[derive(Default)]
+struct Command {
+    descriptions: Vec<String>,
+    lines: Vec<String>,
+}
+
+struct Variable {
+    name: String,
+    generator: String,
+}
+
+/// Imports a `.cheat` file, or every `.cheat` file below a directory, into the cheatsheet source
+/// `into`.
+///
+/// Cheatsheets are written to directories, which is where the app lists them: `<name>.cheat`
+/// directly in `from`, or `from` itself, becomes `<into>/<name>/<name>.md`, and files in
+/// subdirectories keep their directory, `<dir>/<name>.cheat` becomes `<into>/<dir>/<name>.md`.
+/// Existing cheatsheets are never overwritten.
+pub fn import_navi(from: &Path, into: &Path) -> io::Result<ImportSummary> {
+    if !into.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} is not a directory", into.display()),
+        ));
+    }
+    let files = if from.is_dir() {
+        let mut files = Vec::new();
+        find_cheat_files(from, &mut files)?;
+        files.sort();
+        files
+    } else if from.is_file() {
+        vec![from.to_path_buf()]
+    } else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} doesn't exist", from.display()),
+        ));
+    };
+
+    let mut summary = ImportSummary::default();
+    for file in files {
+        let target = target_path(from, &file, into);
+        match import_file(&file, &target) {
+            Ok(sections) => {
+                info!("Imported {} to {}", file.display(), target.display());
+                summary.cheatsheets += 1;
+                summary.sections += sections;
+            }
+            Err(error) => {
+                info!("Not importing {}: {error}", file.display());
+                summary.skipped.push(format!("{}: {error}", file.display()));
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// Converts a `.cheat` file and writes it to `target`, returning the number of sections.
+fn import_file(file: &Path, target: &Path) -> io::Result<usize> {
+    let markdown = convert_cheat(&fs::read_to_string(file)?);
+    let sections = parse_cheatsheet(&target.to_string_lossy(), &markdown)
+        .sections()
+        .count();
+    if sections == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "it has no commands",
+        ));
+    }
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut out = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(target)
+        .map_err(|error| match error.kind() {
+            io::ErrorKind::AlreadyExists => io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", target.display()),
+            ),
+            _ => error,
+        })?;
+    out.write_all(markdown.as_bytes())?;
+    Ok(sections)
+}
+
+/// Collects the `.cheat` files below `dir`, leaving out hidden files and directories like `.git`.
+fn find_cheat_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let hidden = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            find_cheat_files(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "cheat")
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Where the cheatsheet for `file`, found in `from`, goes in `into`.
+fn target_path(from: &Path, file: &Path, into: &Path) -> PathBuf {
+    let name = file
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let dir = file
+        .strip_prefix(from)
+        .ok()
+        .and_then(Path::parent)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map_or_else(|| PathBuf::from(&name), Path::to_path_buf);
+    into.join(dir).join(format!("{name}.md"))
+}
+
+/// Converts the text of a `.cheat` file into the markdown of a cheatsheet.
+pub fn convert_cheat(text: &str) -> String {
+    let mut markdown = String::new();
+    for group in parse_cheat(text) {
+        if group.commands.is_empty() {
+            continue;
+        }
+        if let Some(tags) = &group.tags {
+            markdown.push_str(&format!("## {tags}\n\n"));
+        }
+        for tags in &group.extends {
+            markdown.push_str(&format!("Uses the variables of {}.\n\n", code_span(tags)));
+        }
+        for command in &group.commands {
+            markdown.push_str(&convert_command(command, &group.variables));
+        }
+    }
+    markdown
+}
+
+fn parse_cheat(text: &str) -> Vec<Group> {
+    let mut groups = vec![Group::default()];
+    let mut command = Command::default();
+
+    for line in text.lines() {
+        let line = line.trim_end();
+        let group = groups.last_mut().unwrap();
+        if let Some(tags) = line.strip_prefix('%') {
+            finish_command(group, &mut command);
+            let tags = tags.trim();
+            groups.push(Group {
+                tags: (!tags.is_empty()).then(|| tags.to_string()),
+                ..Group::default()
+            });
+        } else if let Some(tags) = line.strip_prefix('@') {
+            finish_command(group, &mut command);
+            group.extends.push(tags.trim().to_string());
+        } else if let Some(definition) = line.strip_prefix('$') {
+            finish_command(group, &mut command);
+            match definition.split_once(':') {
+                Some((name, generator)) => group.variables.push(Variable {
+                    name: name.trim().to_string(),
+                    generator: generator.trim().to_string(),
+                }),
+                None => warn!("Ignoring a navi variable without a command: {line}"),
+            }
+        } else if let Some(description) = line.strip_prefix('#') {
+            finish_command(group, &mut command);
+            command.descriptions.push(description.trim().to_string());
+        } else if line.trim().is_empty() {
+            finish_command(group, &mut command);
+        } else if !line.starts_with(';') {
+            command.lines.push(line.to_string());
+        }
+    }
+    finish_command(groups.last_mut().unwrap(), &mut command);
+    groups
+}
+
+/// Adds the command that was being read to `group`, if it has any lines. Descriptions without
+/// a command are kept for the next one.
+fn finish_command(group: &mut Group, command: &mut Command) {
+    if !command.lines.is_empty() {
+        group.commands.push(std::mem::take(command));
+    }
+}
+
+fn convert_command(command: &Command, variables: &[Variable]) -> String {
+    let code = command.lines.join("\n");
+    let (descriptions, title) = match command.descriptions.split_last() {
+        Some((title, descriptions)) if !title.is_empty() => (descriptions, title.clone()),
+        // As code, so that `<variables>` in the command aren't taken for HTML.
+        _ => (
+            command.descriptions.as_slice(),
+            code_span(command.lines[0].trim()),
+        ),
+    };
+
+    let mut markdown = format!("### {title}\n\n");
+    for description in descriptions.iter().filter(|line| !line.is_empty()) {
+        markdown.push_str(&format!("{description}\n\n"));
+    }
+
+    let mut generated = Vec::new();
+    let mut code = replace_variables(&code, |name, first| {
+        let variable = variables
+            .iter()
+            .rev()
+            .find(|variable| variable.name == name);
+        match variable.map(|variable| (variable, literal_choices(&variable.generator))) {
+            Some((_, Some(choices))) if first => format!("{{{{{name}:{}}}}}", choices.join("|")),
+            Some((variable, None)) => {
+                if first {
+                    generated.push(variable);
+                }
+                format!("{{{{{name}}}}}")
+            }
+            _ => format!("{{{{{name}}}}}"),
+        }
+    });
+    code.push('\n');
+    let fence = "`".repeat(longest_run(&code, '`').max(2) + 1);
+    markdown.push_str(&format!("{fence}bash\n{code}{fence}\n\n"));
+
+    if !generated.is_empty() {
+        markdown.push_str("Values in navi came from:\n\n");
+        for variable in generated {
+            markdown.push_str(&format!(
+                "- {}: {}\n",
+                code_span(&variable.name),
+                code_span(&variable.generator)
+            ));
+        }
+        markdown.push('\n');
+    }
+    markdown
+}
+
+/// Replaces `<name>` variables with what `replacement` returns for the name and whether it is the
+/// first time the name appears. Anything else in angle brackets, like `<<EOF` or `< file.txt`, is
+/// left alone.
+fn replace_variables(code: &str, mut replacement: impl FnMut(&str, bool) -> String) -> String {
+    let mut replaced = String::with_capacity(code.len());
+    let mut seen: Vec<&str> = Vec::new();
+    let mut rest = code;
+    while let Some(start) = rest.find('<') {
+        replaced.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('>').map(|end| &after[..end]) {
+            Some(name) if is_variable_name(name) => {
+                let first = !seen.contains(&name);
+                seen.push(name);
+                replaced.push_str(&replacement(name, first));
+                rest = &after[name.len() + 1..];
+            }
+            _ => {
+                replaced.push('<');
+                rest = after;
+            }
+        }
+    }
+    replaced.push_str(rest);
+    replaced
+}
+
+/// Whether `name` can be a placeholder name, see [`crate::placeholder::Placeholder`].
+fn is_variable_name(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// The lines a generator prints when it only prints fixed text, like `echo -e "a\nb"` or
+/// `printf 'a\nb'`. Needs at least two lines, since one line would make a default rather than
+/// choices.
+fn literal_choices(generator: &str) -> Option<Vec<String>> {
+    let quoted = generator
+        .strip_prefix("echo -e ")
+        .or_else(|| generator.strip_prefix("printf "))?
+        .trim();
+    let quote = quoted.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let text = quoted.strip_prefix(quote)?.strip_suffix(quote)?;
+    if text.contains(quote) {
+        return None;
+    }
+
+    let choices: Vec<String> = text
+        .split("\\n")
+        .map(|choice| choice.trim().to_string())
+        .filter(|choice| !choice.is_empty())
+        .collect();
+    let plain = choices
+        .iter()
+        .all(|choice| !choice.contains(['\\', '$', '`', '|', '{', '}', '%']));
+    (plain && choices.len() > 1).then_some(choices)
+}
+
+/// Inline code for `text`, with enough backticks around it for the backticks inside.
+fn code_span(text: &str) -> String {
+    let ticks = "`".repeat(longest_run(text, '`') + 1);
+    let pad = if text.starts_with('`') || text.ends_with('`') {
+        " "
+    } else {
+        ""
+    };
+    format!("{ticks}{pad}{text}{pad}{ticks}")
+}
+
+fn longest_run(text: &str, c: char) -> usize {
+    text.split(|other| other != c)
+        .map(str::len)
+        .max()
+        .unwrap_or(0)
+}
@@ -0,0 +1,395 @@
+use crate::config::cognitio_home_dir;
+use crate::edit::write_atomically;
+use crate::snippet::load_cheatsheet_file;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// How many recently used values are remembered per placeholder.
+const MAX_RECENT_VALUES: usize = 10;
+
+/// A value to fill in in a snippet, written as `{{name}}`.
+///
+/// A default can be given after a colon, `{{namespace:default}}`, and a list of choices separated
+/// by `|`, `{{env:dev|staging|prod}}`, where the first choice is the default. Names come right
+/// after the braces and may contain letters, digits, `_` and `-`, so template syntax like
+/// `{{ .Values.image }}` or `{{ end }}` is left alone.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Placeholder {
+    pub name: String,
+    pub default: Option<String>,
+    pub choices: Vec<String>,
+}
+
+/// A section's code with placeholders filled in.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RenderedSnippet {
+    pub title: String,
+    /// Every code block of the section, filled in.
+    pub code_blocks: Vec<String>,
+    /// The code blocks joined by a blank line.
+    pub text: String,
+    /// Every placeholder found in the section.
+    pub placeholders: Vec<Placeholder>,
+    /// Placeholders that had neither a value nor a default and were left as they were.
+    pub unfilled: Vec<String>,
+}
+
+/// Recently used placeholder values, saved in `placeholder_history.yaml` in [`cognitio_home_dir`].
+#[derive(Debug, Default)]
+pub struct PlaceholderHistory {
+    /// Placeholder name to values, most recent first.
+    values: BTreeMap<String, Vec<String>>,
+}
+
+/// A placeholder occurrence in a text.
+struct Occurrence {
+    start: usize,
+    end: usize,
+    placeholder: Placeholder,
+}
+
+/// Lists the placeholders in a text in order of first appearance, once per name.
+pub fn find_placeholders(text: &str) -> Vec<Placeholder> {
+    let mut placeholders: Vec<Placeholder> = Vec::new();
+    for occurrence in occurrences(text) {
+        match placeholders
+            .iter_mut()
+            .find(|p| p.name == occurrence.placeholder.name)
+        {
+            // A later occurrence may be the one that has a default or choices.
+            Some(existing) => {
+                if existing.default.is_none() {
+                    existing.default = occurrence.placeholder.default;
+                }
+                if existing.choices.is_empty() {
+                    existing.choices = occurrence.placeholder.choices;
+                }
+            }
+            None => placeholders.push(occurrence.placeholder),
+        }
+    }
+    placeholders
+}
+
+/// Replaces placeholders with their value from `vars`, or their default. Placeholders without
+/// either are left as they are.
+pub fn fill_placeholders(text: &str, vars: &HashMap<String, String>) -> String {
+    let placeholders = find_placeholders(text);
+    let mut filled = String::with_capacity(text.len());
+    let mut position = 0;
+
+    for occurrence in occurrences(text) {
+        let name = &occurrence.placeholder.name;
+        let default = placeholders
+            .iter()
+            .find(|p| &p.name == name)
+            .and_then(|p| p.default.as_ref());
+
+        if let Some(value) = vars.get(name).or(default) {
+            filled.push_str(&text[position..occurrence.start]);
+            filled.push_str(value);
+            position = occurrence.end;
+        }
+    }
+
+    filled.push_str(&text[position..]);
+    filled
+}
+
+/// Fills in the placeholders of a section's code blocks.
+pub fn render_snippet(
+    path: &str,
+    section: &str,
+    vars: &HashMap<String, String>,
+) -> io::Result<RenderedSnippet> {
+    let cheatsheet = load_cheatsheet_file(path)?;
+    let snippet = cheatsheet.find_section(section).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No section '{section}' in {path}"),
+        )
+    })?;
+
+    let code: Vec<&str> = snippet
+        .code_blocks()
+        .map(|block| block.code.as_str())
+        .collect();
+    let placeholders = find_placeholders(&code.join("\n"));
+    let unfilled = placeholders
+        .iter()
+        .filter(|p| p.default.is_none() && !vars.contains_key(&p.name))
+        .map(|p| p.name.clone())
+        .collect();
+    let code_blocks: Vec<String> = code
+        .iter()
+        .map(|code| fill_placeholders(code.trim_end_matches('\n'), vars))
+        .collect();
+
+    Ok(RenderedSnippet {
+        title: snippet.title.clone(),
+        text: code_blocks.join("\n\n"),
+        code_blocks,
+        placeholders,
+        unfilled,
+    })
+}
+
+impl Placeholder {
+    fn parse(inner: &str) -> Option<Placeholder> {
+        let (name, default) = match inner.split_once(':') {
+            Some((name, default)) => (name, Some(default.trim())),
+            None => (inner, None),
+        };
+
+        let valid_name = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !valid_name {
+            return None;
+        }
+
+        let choices: Vec<String> = match default {
+            Some(default) if default.contains('|') => default
+                .split('|')
+                .map(|choice| choice.trim().to_string())
+                .filter(|choice| !choice.is_empty())
+                .collect(),
+            _ => Vec::new(),
+        };
+        let default = match choices.first() {
+            Some(first) => Some(first.clone()),
+            None => default.map(|default| default.to_string()),
+        };
+
+        Some(Placeholder {
+            name: name.to_string(),
+            default,
+            choices,
+        })
+    }
+}
+
+fn occurrences(text: &str) -> Vec<Occurrence> {
+    let mut found = Vec::new();
+    let mut search_from = 0;
+
+    while let Some(offset) = text[search_from..].find("{{") {
+        let start = search_from + offset;
+        let Some(length) = text[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + length + 2;
+        let inner = &text[start + 2..end - 2];
+
+        match (inner.contains('\n'), Placeholder::parse(inner)) {
+            (false, Some(placeholder)) => {
+                found.push(Occurrence {
+                    start,
+                    end,
+                    placeholder,
+                });
+                search_from = end;
+            }
+            _ => search_from = start + 2,
+        }
+    }
+
+    found
+}
+
+/// Where recently used placeholder values are saved.
+pub fn placeholder_history_path() -> PathBuf {
+    PathBuf::from(cognitio_home_dir()).join("placeholder_history.yaml")
+}
+
+impl PlaceholderHistory {
+    /// Reads saved values. A missing file gives an empty history.
+    pub fn load(path: &Path) -> io::Result<PlaceholderHistory> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(PlaceholderHistory::default())
+            }
+            Err(error) => return Err(error),
+        };
+
+        if contents.trim().is_empty() {
+            return Ok(PlaceholderHistory::default());
+        }
+        let values = serde_yaml::from_str(&contents)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        Ok(PlaceholderHistory { values })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let contents = serde_yaml::to_string(&self.values)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        write_atomically(path, &contents)
+    }
+
+    /// Recently used values for a placeholder, most recent first.
+    pub fn recent(&self, name: &str) -> &[String] {
+        self.values.get(name).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    /// Moves a value to the front of a placeholder's recent values.
+    pub fn remember(&mut self, name: &str, value: &str) {
+        let values = self.values.entry(name.to_string()).or_default();
+        values.retain(|existing| existing != value);
+        values.insert(0, value.to_string());
+        values.truncate(MAX_RECENT_VALUES);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process;
+
+    #[test]
+    fn remembers_the_most_recent_value_first() {
+        let mut history = PlaceholderHistory::default();
+        assert!(history.recent("env").is_empty());
+        history.remember("env", "dev");
+        history.remember("env", "prod");
+        history.remember("namespace", "default");
+        assert_eq!(history.recent("env"), ["prod", "dev"]);
+
+        // Using a value again moves it to the front instead of adding it twice.
+        history.remember("env", "dev");
+        assert_eq!(history.recent("env"), ["dev", "prod"]);
+        assert_eq!(history.recent("namespace"), ["default"]);
+    }
+
+    #[test]
+    fn keeps_the_last_values() {
+        let mut history = PlaceholderHistory::default();
+        for number in 0..=MAX_RECENT_VALUES {
+            history.remember("n", &number.to_string());
+        }
+        let recent = history.recent("n");
+        assert_eq!(recent.len(), MAX_RECENT_VALUES);
+        assert_eq!(recent[0], MAX_RECENT_VALUES.to_string());
+        assert!(!recent.contains(&"0".to_string()));
+    }
+
+    #[test]
+    fn saves_and_loads_the_history() {
+        let dir = std::env::temp_dir().join(format!("cognitio-history-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("placeholder_history.yaml");
+        assert!(PlaceholderHistory::load(&path).unwrap().values.is_empty());
+
+        let mut history = PlaceholderHistory::default();
+        history.remember("env", "dev");
+        history.remember("env", "prod");
+        history.save(&path).unwrap();
+        let loaded = PlaceholderHistory::load(&path).unwrap();
+        assert_eq!(loaded.recent("env"), ["prod", "dev"]);
+
+        fs::write(&path, "env: [unclosed").unwrap();
+        let error = PlaceholderHistory::load(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    fn placeholder(name: &str, default: Option<&str>, choices: &[&str]) -> Placeholder {
+        Placeholder {
+            name: name.to_string(),
+            default: default.map(|default| default.to_string()),
+            choices: choices.iter().map(|choice| choice.to_string()).collect(),
+        }
+    }
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn finds_each_placeholder_once() {
+        let text = "kubectl -n {{namespace}} logs {{pod}} -c {{pod}}\n\
+                    kubectl -n {{namespace:default}} get {{kind:pods|deployments}}";
+        assert_eq!(
+            find_placeholders(text),
+            [
+                // The default comes from the later occurrence.
+                placeholder("namespace", Some("default"), &[]),
+                placeholder("pod", None, &[]),
+                placeholder("kind", Some("pods"), &["pods", "deployments"]),
+            ]
+        );
+        assert_eq!(
+            find_placeholders("{{env: dev | staging || prod }}"),
+            [placeholder("env", Some("dev"), &["dev", "staging", "prod"])]
+        );
+    }
+
+    #[test]
+    fn leaves_template_syntax_alone() {
+        let text = "{{ .Values.image }} {{ end }} {{else}} {{ include \"x\" . }} {{-name}}\n\
+                    {{ name }} {{name }} {{1st}} {{multi\nline}} {{unclosed";
+        let names: Vec<String> = find_placeholders(text)
+            .into_iter()
+            .map(|placeholder| placeholder.name)
+            .collect();
+        assert_eq!(names, ["else"]);
+        assert_eq!(fill_placeholders(text, &vars(&[("end", "x")])), text);
+    }
+
+    #[test]
+    fn fills_in_values_and_defaults() {
+        let text = "helm upgrade {{release}} -n {{namespace:default}} --set env={{env:dev|prod}} \
+                    {{namespace}} {{tag}}";
+        assert_eq!(
+            fill_placeholders(text, &vars(&[("release", "web"), ("env", "prod")])),
+            "helm upgrade web -n default --set env=prod default {{tag}}"
+        );
+        assert_eq!(
+            fill_placeholders(text, &vars(&[("namespace", "kube-system"), ("tag", "1.2")])),
+            "helm upgrade {{release}} -n kube-system --set env=dev kube-system 1.2"
+        );
+    }
+
+    #[test]
+    fn lists_unfilled_placeholders_of_a_section() {
+        let dir = std::env::temp_dir().join(format!("cognitio-render-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("k8s.md");
+        fs::write(
+            &path,
+            "### Logs\n\n```bash\nkubectl -n {{namespace:default}} logs {{pod}}\n```\n\n\
+             Then:\n\n```bash\nkubectl describe pod {{pod}} {{ end }} {{flags}}\n```\n",
+        )
+        .unwrap();
+        let path = path.to_string_lossy();
+
+        let rendered = render_snippet(&path, "Logs", &vars(&[("flags", "-o wide")])).unwrap();
+        assert_eq!(rendered.title, "Logs");
+        assert_eq!(rendered.unfilled, ["pod"]);
+        assert_eq!(
+            rendered.code_blocks,
+            [
+                "kubectl -n default logs {{pod}}",
+                "kubectl describe pod {{pod}} {{ end }} -o wide"
+            ]
+        );
+        assert_eq!(rendered.text, rendered.code_blocks.join("\n\n"));
+        assert_eq!(rendered.placeholders.len(), 3);
+
+        let rendered = render_snippet(&path, "Logs", &vars(&[("pod", "web-1")])).unwrap();
+        assert_eq!(rendered.unfilled, ["flags"]);
+        let error = render_snippet(&path, "Missing", &HashMap::new()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+}
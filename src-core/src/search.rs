@@ -0,0 +1,785 @@
+use crate::config::CognitioConfig;
+use crate::library::{
+    list_cheatsheet_directories, list_files_in_directory, list_subdirectories, Directory,
+    DirectoryFile,
+};
+use crate::snippet::{load_cheatsheet_file, Block, Cheatsheet};
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+/// An in-memory full-text index over the snippets of every configured cheatsheet source.
+///
+/// The index is built once with [`SearchIndex::build`] and kept up to date by passing changed
+/// paths to [`SearchIndex::refresh_path`], typically from the file watcher.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    files: BTreeMap<String, Vec<IndexedSnippet>>,
+    /// The cheatsheet sources with their max depth, so that [`SearchIndex::refresh_path`] only
+    /// indexes what a full scan would.
+    sources: Vec<(PathBuf, usize)>,
+}
+
+/// A snippet matching a search query.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SearchHit {
+    /// Path of the cheatsheet file.
+    pub path: String,
+    /// Cheatsheet file name without the `.md` extension.
+    pub name: String,
+    /// The h2 heading the snippet is grouped under.
+    pub group: Option<String>,
+    /// The h3 heading of the snippet.
+    pub section: String,
+    /// Line of the h3 heading.
+    pub line: usize,
+    pub score: f32,
+    pub matches: Vec<SearchMatch>,
+}
+
+/// Where in a snippet a query term matched.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SearchMatch {
+    pub field: MatchField,
+    /// The line of text that matched.
+    pub text: String,
+    /// Line in the cheatsheet file, or `None` for matches in the file name.
+    pub line: Option<usize>,
+    /// Byte offsets of the match within `text`.
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchField {
+    Name,
+    Heading,
+    Prose,
+    Code,
+}
+
+/// A parsed search query.
+///
+/// Supported syntax:
+///
+/// - `word`: matches words in headings, prose and code, allowing small typos.
+/// - `"exact phrase"`: matches the phrase as written, ignoring case.
+/// - `lang:bash`: only snippets with a code block in the given language.
+/// - `path:docker`: only snippets in files whose path contains the given text.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub words: Vec<String>,
+    pub phrases: Vec<String>,
+    pub languages: Vec<String>,
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug)]
+struct IndexedSnippet {
+    path: String,
+    name: String,
+    group: Option<String>,
+    section: String,
+    line: usize,
+    languages: Vec<String>,
+    lines: Vec<IndexedLine>,
+    tokens: Vec<Token>,
+}
+
+#[derive(Debug)]
+struct IndexedLine {
+    field: MatchField,
+    line: Option<usize>,
+    text: String,
+    folded: String,
+}
+
+#[derive(Debug)]
+struct Token {
+    text: String,
+    line_index: usize,
+    start: usize,
+    end: usize,
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str) -> SearchQuery {
+        let mut parsed = SearchQuery::default();
+        let mut chars = query.chars().peekable();
+
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+                continue;
+            }
+
+            if c == '"' {
+                chars.next();
+                let phrase: String = chars.by_ref().take_while(|&c| c != '"').collect();
+                let phrase = fold_case(phrase.trim());
+                if !phrase.is_empty() {
+                    parsed.phrases.push(phrase);
+                }
+                continue;
+            }
+
+            let mut term = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                term.push(c);
+                chars.next();
+            }
+
+            let term = fold_case(&term);
+            if let Some(language) = term.strip_prefix("lang:") {
+                if !language.is_empty() {
+                    parsed.languages.push(language.to_string());
+                }
+            } else if let Some(path) = term.strip_prefix("path:") {
+                if !path.is_empty() {
+                    parsed.paths.push(path.to_string());
+                }
+            } else {
+                parsed
+                    .words
+                    .extend(tokenize(&term).into_iter().map(|(word, _, _)| word));
+            }
+        }
+
+        parsed
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+            && self.phrases.is_empty()
+            && self.languages.is_empty()
+            && self.paths.is_empty()
+    }
+}
+
+impl SearchIndex {
+    pub fn new() -> SearchIndex {
+        SearchIndex::default()
+    }
+
+    /// Indexes every cheatsheet file in the configured sources.
+    pub fn build(conf: &CognitioConfig) -> SearchIndex {
+        let mut index = SearchIndex::new();
+        index.sources = conf
+            .cheatsheets
+            .iter()
+            .filter_map(|source| Some((source.expanded_path().ok()?, source.max_depth())))
+            .collect();
+        for dir in list_cheatsheet_directories(conf) {
+            index.add_directory(&dir);
+        }
+        index
+    }
+
+    /// Indexes every cheatsheet file in an already scanned directory tree.
+    pub fn add_directory(&mut self, dir: &Directory) {
+        for file in &dir.files {
+            self.update_file(&file.path);
+        }
+        for sub_dir in &dir.sub_directories {
+            self.add_directory(sub_dir);
+        }
+    }
+
+    /// Re-indexes a single cheatsheet file.
+    pub fn update_file(&mut self, path: &str) {
+        match load_cheatsheet_file(path) {
+            Ok(cheatsheet) => self.add_cheatsheet(&cheatsheet),
+            Err(error) => {
+                error!("Failed to index {}: {:?}", path, error);
+                self.files.remove(path);
+            }
+        }
+    }
+
+    /// Adds or replaces the snippets of a parsed cheatsheet.
+    pub fn add_cheatsheet(&mut self, cheatsheet: &Cheatsheet) {
+        self.files
+            .insert(cheatsheet.path.clone(), index_cheatsheet(cheatsheet));
+    }
+
+    /// Removes a file, or every file below a directory, from the index.
+    pub fn remove_path(&mut self, path: &str) {
+        let prefix = format!("{}/", path.trim_end_matches('/'));
+        self.files
+            .retain(|file_path, _| file_path != path && !file_path.starts_with(&prefix));
+    }
+
+    /// Brings the index up to date after something changed at `path`.
+    ///
+    /// Changed markdown files are re-indexed and removed files and directories are dropped. For a
+    /// directory, files that no longer exist are dropped and files that aren't indexed yet are
+    /// added, which covers directories that were moved or copied into a source.
+    ///
+    /// Like the scan of the sources in [`SearchIndex::build`], hidden directories and directories
+    /// below a source's max depth are left out, and so is anything outside the sources.
+    pub fn refresh_path(&mut self, path: &str) {
+        let fs_path = Path::new(path);
+        let parent_depth_left = || fs_path.parent().and_then(|dir| self.depth_left(dir));
+        if fs_path.is_dir() {
+            let Some(depth_left) = self.depth_left(fs_path) else {
+                self.remove_path(path);
+                return;
+            };
+            let prefix = format!("{}/", path.trim_end_matches('/'));
+            self.files.retain(|file_path, _| {
+                !file_path.starts_with(&prefix) || Path::new(file_path).is_file()
+            });
+            self.add_new_files(&list_files_in_directory(path));
+            for sub_dir in list_subdirectories(path, depth_left) {
+                self.add_new_files_in_directory(&sub_dir);
+            }
+        } else if fs_path.is_file() && path.ends_with(".md") && parent_depth_left().is_some() {
+            self.update_file(path);
+        } else {
+            self.remove_path(path);
+        }
+    }
+
+    /// How many levels of subdirectories below `dir` a scan of the sources goes into, or `None`
+    /// if the scan doesn't list `dir` at all.
+    fn depth_left(&self, dir: &Path) -> Option<usize> {
+        self.sources
+            .iter()
+            .filter_map(|(source, max_depth)| {
+                let components: Vec<Component> =
+                    dir.strip_prefix(source).ok()?.components().collect();
+                let hidden = components.iter().any(|component| {
+                    component
+                        .as_os_str()
+                        .to_str()
+                        .is_some_and(|name| name.starts_with('.'))
+                });
+                if hidden {
+                    return None;
+                }
+                max_depth.checked_sub(components.len())
+            })
+            .max()
+    }
+
+    fn add_new_files_in_directory(&mut self, dir: &Directory) {
+        self.add_new_files(&dir.files);
+        for sub_dir in &dir.sub_directories {
+            self.add_new_files_in_directory(sub_dir);
+        }
+    }
+
+    fn add_new_files(&mut self, files: &[DirectoryFile]) {
+        for file in files {
+            if !self.files.contains_key(&file.path) {
+                self.update_file(&file.path);
+            }
+        }
+    }
+
+    /// Number of indexed cheatsheet files.
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Runs a query in the [`SearchQuery`] syntax and returns at most `limit` hits, best first.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        self.search_parsed(&SearchQuery::parse(query), limit)
+    }
+
+    pub fn search_parsed(&self, query: &SearchQuery, limit: usize) -> Vec<SearchHit> {
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut hits: Vec<SearchHit> = self
+            .files
+            .values()
+            .flatten()
+            .filter_map(|snippet| match_snippet(snippet, query))
+            .collect();
+
+        hits.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.path.cmp(&b.path))
+                .then_with(|| a.line.cmp(&b.line))
+        });
+        hits.truncate(limit);
+        hits
+    }
+}
+
+fn index_cheatsheet(cheatsheet: &Cheatsheet) -> Vec<IndexedSnippet> {
+    let mut snippets = Vec::new();
+
+    for group in &cheatsheet.groups {
+        for section in &group.sections {
+            let mut lines = vec![
+                IndexedLine::new(MatchField::Name, None, &cheatsheet.name),
+                IndexedLine::new(
+                    MatchField::Heading,
+                    Some(section.start_line),
+                    &section.title,
+                ),
+            ];
+            if let (Some(title), Some(line)) = (&group.title, group.line) {
+                lines.push(IndexedLine::new(MatchField::Heading, Some(line), title));
+            }
+
+            let mut languages = Vec::new();
+            for block in &section.blocks {
+                match block {
+                    Block::Prose(prose) => {
+                        for (offset, text) in prose.markdown.lines().enumerate() {
+                            let line = prose.start_line + offset;
+                            lines.push(IndexedLine::new(MatchField::Prose, Some(line), text));
+                        }
+                    }
+                    Block::Code(code) => {
+                        if let Some(language) = &code.language {
+                            languages.push(fold_case(language));
+                        }
+                        // Fenced code starts on the line after the opening fence.
+                        let line_count = code.code.lines().count();
+                        let first_line = if code.end_line - code.start_line + 1 > line_count {
+                            code.start_line + 1
+                        } else {
+                            code.start_line
+                        };
+                        for (offset, text) in code.code.lines().enumerate() {
+                            let line = first_line + offset;
+                            lines.push(IndexedLine::new(MatchField::Code, Some(line), text));
+                        }
+                    }
+                }
+            }
+
+            let tokens = lines
+                .iter()
+                .enumerate()
+                .flat_map(|(line_index, line)| {
+                    tokenize(&line.folded)
+                        .into_iter()
+                        .map(move |(text, start, end)| Token {
+                            text,
+                            line_index,
+                            start,
+                            end,
+                        })
+                })
+                .collect();
+
+            snippets.push(IndexedSnippet {
+                path: cheatsheet.path.clone(),
+                name: cheatsheet.name.clone(),
+                group: group.title.clone(),
+                section: section.title.clone(),
+                line: section.start_line,
+                languages,
+                lines,
+                tokens,
+            });
+        }
+    }
+
+    snippets
+}
+
+impl IndexedLine {
+    fn new(field: MatchField, line: Option<usize>, text: &str) -> IndexedLine {
+        IndexedLine {
+            field,
+            line,
+            text: text.to_string(),
+            folded: fold_case(text),
+        }
+    }
+
+    fn to_match(&self, start: usize, end: usize) -> SearchMatch {
+        SearchMatch {
+            field: self.field,
+            text: self.text.clone(),
+            line: self.line,
+            start,
+            end,
+        }
+    }
+}
+
+fn match_snippet(snippet: &IndexedSnippet, query: &SearchQuery) -> Option<SearchHit> {
+    let language_matches = query
+        .languages
+        .iter()
+        .all(|language| snippet.languages.contains(language));
+    let path_matches = query
+        .paths
+        .iter()
+        .all(|path| fold_case(&snippet.path).contains(path.as_str()));
+    if !language_matches || !path_matches {
+        return None;
+    }
+
+    let mut score = 0.0;
+    let mut matches = Vec::new();
+
+    for phrase in &query.phrases {
+        let mut found = false;
+        for line in &snippet.lines {
+            for (start, _) in line.folded.match_indices(phrase.as_str()) {
+                found = true;
+                score += 1.5 * field_weight(line.field);
+                matches.push(line.to_match(start, start + phrase.len()));
+            }
+        }
+        if !found {
+            return None;
+        }
+    }
+
+    for word in &query.words {
+        let mut best = 0.0;
+        for token in &snippet.tokens {
+            let quality = match_quality(word, &token.text);
+            if quality > 0.0 {
+                let line = &snippet.lines[token.line_index];
+                let token_score = quality * field_weight(line.field);
+                if token_score > best {
+                    best = token_score;
+                }
+                matches.push(line.to_match(token.start, token.end));
+            }
+        }
+        if best == 0.0 {
+            return None;
+        }
+        score += best;
+    }
+
+    matches.sort_by(|a, b| a.line.cmp(&b.line).then_with(|| a.start.cmp(&b.start)));
+    matches.dedup_by(|a, b| a.line == b.line && a.start == b.start && a.field == b.field);
+
+    Some(SearchHit {
+        path: snippet.path.clone(),
+        name: snippet.name.clone(),
+        group: snippet.group.clone(),
+        section: snippet.section.clone(),
+        line: snippet.line,
+        score,
+        matches,
+    })
+}
+
+fn field_weight(field: MatchField) -> f32 {
+    match field {
+        MatchField::Heading => 3.0,
+        MatchField::Name => 2.0,
+        MatchField::Code => 1.5,
+        MatchField::Prose => 1.0,
+    }
+}
+
+/// How well a query word matches an indexed token: 1 for an exact match, less for prefix and
+/// typo-tolerant matches, 0 for no match.
+fn match_quality(word: &str, token: &str) -> f32 {
+    if word == token {
+        return 1.0;
+    }
+    if word.len() >= 2 && token.starts_with(word) {
+        return 0.8;
+    }
+
+    let max_typos = match word.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    };
+    if max_typos > 0 && edit_distance_within(word, token, max_typos) {
+        return 0.5;
+    }
+
+    0.0
+}
+
+/// Whether the Levenshtein distance between `a` and `b` is at most `max`.
+fn edit_distance_within(a: &str, b: &str, max: usize) -> bool {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > max {
+        return false;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        if current.iter().all(|&distance| distance > max) {
+            return false;
+        }
+        previous = current;
+    }
+
+    previous[b.len()] <= max
+}
+
+/// Lowercases text without changing byte offsets, so offsets found in the folded text can be
+/// used on the original.
+fn fold_case(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            let mut lower = c.to_lowercase();
+            match (lower.next(), lower.next()) {
+                (Some(l), None) if l.len_utf8() == c.len_utf8() => l,
+                _ => c,
+            }
+        })
+        .collect()
+}
+
+/// Splits text into words of letters, digits and underscores, with their byte offsets.
+fn tokenize(text: &str) -> Vec<(String, usize, usize)> {
+    let mut tokens = Vec::new();
+    let mut start: Option<usize> = None;
+
+    for (index, c) in text.char_indices() {
+        let is_word = c.is_alphanumeric() || c == '_';
+        match (is_word, start) {
+            (true, None) => start = Some(index),
+            (false, Some(s)) => {
+                tokens.push((text[s..index].to_string(), s, index));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        tokens.push((text[s..].to_string(), s, text.len()));
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_config;
+    use crate::snippet::parse_cheatsheet;
+    use std::fs;
+    use std::process;
+
+    fn index() -> SearchIndex {
+        let mut index = SearchIndex::new();
+        index.add_cheatsheet(&parse_cheatsheet(
+            "/lib/docker/containers.md",
+            "## Containers\n\n### List running containers\n\n```bash\ndocker ps\n```\n\n\
+             ### Remove a container\n\nForce removal of a running container.\n\n\
+             ```bash\ndocker rm -f {{id}}\n```\n",
+        ));
+        index.add_cheatsheet(&parse_cheatsheet(
+            "/lib/python/venv.md",
+            "### Create a virtual environment\n\n```python\nimport venv\n```\n\n\
+             ```bash\npython -m venv .venv\n```\n",
+        ));
+        index
+    }
+
+    fn sections(hits: &[SearchHit]) -> Vec<&str> {
+        hits.iter().map(|hit| hit.section.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_queries() {
+        assert_eq!(
+            SearchQuery::parse(r#"  Docker "Running Container" lang:Bash path:docker/ rm-f "#),
+            SearchQuery {
+                words: vec!["docker".into(), "rm".into(), "f".into()],
+                phrases: vec!["running container".into()],
+                languages: vec!["bash".into()],
+                paths: vec!["docker/".into()],
+            }
+        );
+        // Empty filters and phrases, and an unclosed quote.
+        assert!(SearchQuery::parse(r#"lang: path: "" "  "#).is_empty());
+        assert_eq!(
+            SearchQuery::parse(r#"ps "docker p"#).phrases,
+            ["docker p".to_string()]
+        );
+    }
+
+    #[test]
+    fn ranks_whole_words_before_prefixes() {
+        let hits = index().search("container", 10);
+        assert_eq!(
+            sections(&hits),
+            ["Remove a container", "List running containers"]
+        );
+        assert!(hits[0].score > hits[1].score);
+        let hit = &hits[0];
+        assert_eq!(hit.group.as_deref(), Some("Containers"));
+        assert_eq!(hit.name, "containers");
+        assert_eq!(hit.line, 9);
+        let heading = hit
+            .matches
+            .iter()
+            .find(|found| found.line == Some(9))
+            .unwrap();
+        assert_eq!(
+            (heading.field, heading.line),
+            (MatchField::Heading, Some(9))
+        );
+        assert_eq!(&heading.text[heading.start..heading.end], "container");
+
+        // Every word has to match.
+        assert_eq!(
+            sections(&index().search("container venv", 10)),
+            [] as [&str; 0]
+        );
+        assert!(index().search("", 10).is_empty());
+        assert_eq!(index().search("container", 1).len(), 1);
+    }
+
+    #[test]
+    fn filters_by_language_and_path() {
+        let index = index();
+        assert_eq!(
+            sections(&index.search("lang:python", 10)),
+            ["Create a virtual environment"]
+        );
+        assert_eq!(sections(&index.search("lang:PYTHON venv", 10)).len(), 1);
+        assert_eq!(sections(&index.search("lang:bash", 10)).len(), 3);
+        assert_eq!(
+            sections(&index.search("lang:bash path:python", 10)).len(),
+            1
+        );
+        assert!(index.search("lang:rust", 10).is_empty());
+        assert!(index.search("path:rust docker", 10).is_empty());
+    }
+
+    #[test]
+    fn matches_phrases_as_written() {
+        let index = index();
+        let hits = index.search(r#""removal of a running""#, 10);
+        assert_eq!(sections(&hits), ["Remove a container"]);
+        let prose = &hits[0].matches[0];
+        assert_eq!((prose.field, prose.line), (MatchField::Prose, Some(11)));
+        assert!(index.search(r#""running removal""#, 10).is_empty());
+        assert_eq!(
+            sections(&index.search(r#""rm -f""#, 10)),
+            ["Remove a container"]
+        );
+    }
+
+    #[test]
+    fn tolerates_typos() {
+        let index = index();
+        // Swapped letters are two edits, as many as a nine letter word may have.
+        assert_eq!(
+            sections(&index.search("contianer", 10)),
+            ["Remove a container"]
+        );
+        assert_eq!(
+            sections(&index.search("virtal enviroment", 10)),
+            ["Create a virtual environment"]
+        );
+        assert_eq!(sections(&index.search("dcker", 10)).len(), 2);
+        // Words of up to three letters have to be exact or a prefix.
+        assert_eq!(sections(&index.search("pyt", 10)).len(), 1);
+        assert!(index.search("pyh", 10).is_empty());
+    }
+
+    #[test]
+    fn refreshes_only_what_a_scan_would_list() {
+        let dir = std::env::temp_dir().join(format!("cognitio-search-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for sub_dir in ["source/a/b", "source/.git", "outside"] {
+            fs::create_dir_all(dir.join(sub_dir)).unwrap();
+        }
+        let write = |name: &str| {
+            let path = dir.join(name);
+            fs::write(&path, "### Docker\n").unwrap();
+            path.to_string_lossy().to_string()
+        };
+        let conf = parse_config(
+            &dir.join("cognitio.yaml"),
+            &format!(
+                "cheatsheets:\n  - title: Source\n    path: {}\n    max_depth: 1\n",
+                dir.join("source").display()
+            ),
+        )
+        .unwrap();
+        let mut index = SearchIndex::build(&conf);
+        assert_eq!(index.file_count(), 0);
+
+        let top = write("source/top.md");
+        let shallow = write("source/a/shallow.md");
+        let deep = write("source/a/b/deep.md");
+        let hidden = write("source/.git/notes.md");
+        let outside = write("outside/notes.md");
+        for path in [&top, &shallow, &deep, &hidden, &outside] {
+            index.refresh_path(path);
+        }
+        let indexed = |index: &SearchIndex| -> Vec<String> {
+            let mut paths: Vec<_> = index
+                .search("docker", 10)
+                .into_iter()
+                .map(|hit| hit.path)
+                .collect();
+            paths.sort();
+            paths
+        };
+        assert_eq!(indexed(&index), [shallow.clone(), top.clone()]);
+
+        // Refreshing a directory follows the same rules.
+        let mut index = SearchIndex::build(&conf);
+        fs::remove_file(&top).unwrap();
+        index.refresh_path(&dir.join("source").to_string_lossy());
+        assert_eq!(indexed(&index), [shallow]);
+        index.refresh_path(&dir.join("source/.git").to_string_lossy());
+        index.refresh_path(&dir.join("source/a/b").to_string_lossy());
+        assert_eq!(index.file_count(), 1);
+
+        // A full scan agrees.
+        assert_eq!(indexed(&SearchIndex::build(&conf)), indexed(&index));
+    }
+
+    #[test]
+    fn measures_edit_distance() {
+        assert!(edit_distance_within("kitten", "sitting", 3));
+        assert!(!edit_distance_within("kitten", "sitting", 2));
+        assert!(edit_distance_within("docker", "docker", 0));
+        assert!(edit_distance_within("dokcer", "docker", 2));
+        assert!(!edit_distance_within("ab", "abcde", 2));
+        assert!(edit_distance_within("", "ab", 2));
+        assert_eq!(match_quality("docker", "docker"), 1.0);
+        assert_eq!(match_quality("dock", "docker"), 0.8);
+        assert_eq!(match_quality("dockre", "docker"), 0.0);
+        assert_eq!(match_quality("dokcer", "docker"), 0.0);
+        assert_eq!(match_quality("dockr", "docker"), 0.5);
+    }
+
+    #[test]
+    fn keeps_byte_offsets_when_folding_case() {
+        let text = "İstanbul Straße ÄRGER";
+        let folded = fold_case(text);
+        assert_eq!(folded.len(), text.len());
+        assert!(folded.ends_with("straße ärger"));
+        assert_eq!(
+            tokenize("git rm -f my_file"),
+            [
+                ("git".to_string(), 0, 3),
+                ("rm".to_string(), 4, 6),
+                ("f".to_string(), 8, 9),
+                ("my_file".to_string(), 10, 17),
+            ]
+        );
+    }
+}
@@ -0,0 +1,690 @@
+//! A local HTTP server with a JSON API and HTML views of the cheatsheet library, for browsing it
+//! without the desktop app.
+//!
+//! The JSON API is under `/api`:
+//!
+//! - `GET /api/tree`: the sources as a [`Directory`] tree, like the app's menu.
+//! - `GET /api/cheatsheet?path=<file>`: the markdown of a cheatsheet.
+//! - `GET /api/sections?path=<file>`: a cheatsheet parsed into groups and sections.
+//! - `GET /api/search?q=<query>&limit=<n>`: search hits, like `cognitio search --json`.
+//!
+//! With [`ServeOptions::writable`], sections can be changed like in the app:
+//! `POST /api/cheatsheets` creates a cheatsheet from `{directory, name}`, and `POST`, `PUT`,
+//! `PATCH` and `DELETE` on `/api/sections` append, replace, rename and delete a section, from
+//! `{path, heading, body, new_heading}`.
+//!
+//! The HTML views are `/`, and `/view?path=<file>` for a cheatsheet, laid out like an exported
+//! site. Paths are absolute file paths, and only the cheatsheets and directories the library
+//! lists are served, never other files in the sources or in `COGNITIO_HOME`.
+//!
+//! `GET /api/events` is a stream of [Server-Sent Events] for the changes the server's file
+//! watcher sees, named and shaped like the events of the desktop app: `file_changed` with
+//! `{events}`, `cognitio_config_changed` with `{path, config}` and `cognitio_config_invalid` with
+//! `{path, errors}`. The HTML views use it to reload themselves.
+//!
+//! With a token, every request needs `Authorization: Bearer <token>`. Browsers can open any page
+//! with `?token=<token>` once, which sets a cookie for the rest of the pages.
+//!
+//! [Server-Sent Events]: https://html.spec.whatwg.org/multipage/server-sent-events.html
+
+use crate::access::AllowedRoots;
+use crate::config::CognitioConfig;
+use crate::edit;
+use crate::export::{
+    cheatsheet_content, nav_html, plan_site, search_entries, site_css, site_page, NavSection, Page,
+    SearchEntry, SITE_JS,
+};
+use crate::html::HtmlRenderer;
+use crate::http::{percent_encode, write_event_stream_head, Request, Response, MAX_HEAD_BYTES};
+use crate::library::{list_cheatsheet_directories, sort_directories_and_files};
+use crate::search::SearchIndex;
+use crate::snippet::{load_cheatsheet_file, parse_cheatsheet, Cheatsheet};
+use crate::watch::{
+    config_debounce, config_watch_roots, library_events, FileChange, LibraryEvent, WatchService,
+};
+use crate::Directory;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::Duration;
+
+const TOKEN_COOKIE: &str = "cognitio_token";
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+/// Paths that answer 405 rather than 404 to other methods.
+const ROUTES: [&str; 11] = [
+    "/api/tree",
+    "/api/cheatsheet",
+    "/api/sections",
+    "/api/search",
+    "/api/cheatsheets",
+    EVENTS_PATH,
+    "/",
+    "/index.html",
+    "/view",
+    "/search-index.json",
+    "/assets/cognitio.css",
+];
+const EVENTS_PATH: &str = "/api/events";
+/// A client that stops sending in the middle of a request is dropped after this long.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often a comment is sent to event streams when nothing changed, so that proxies keep them
+/// open and clients that went away are noticed.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+/// A client turned away because the server is busy gets this long to send its request, and to
+/// take the answer.
+const BUSY_READ_TIMEOUT: Duration = Duration::from_millis(200);
+const BUSY_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+/// An event stream whose client stops reading is dropped after this long.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How many connections [`ServeOptions::default`] handles at once.
+pub const DEFAULT_MAX_CONNECTIONS: usize = 64;
+
+#[derive(Clone, Debug)]
+pub struct ServeOptions {
+    /// Required from every client, as a bearer token or cookie, when set.
+    pub token: Option<String>,
+    /// Allows the API requests that create cheatsheets and change sections.
+    pub writable: bool,
+    /// How many connections are handled at once, each on a thread of its own. More connections
+    /// are answered with 503. Event streams, which stay open, may take half of them, so that
+    /// other requests still get through.
+    pub max_connections: usize,
+}
+
+impl Default for ServeOptions {
+    fn default() -> ServeOptions {
+        ServeOptions {
+            token: None,
+            writable: false,
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+        }
+    }
+}
+
+/// The markdown of a cheatsheet, from `GET /api/cheatsheet`.
+#[derive(Clone, Debug, Serialize)]
+pub struct CheatsheetContent {
+    pub path: String,
+    pub markdown: String,
+}
+
+/// The body of the requests that change sections. Which fields are needed depends on the request.
+#[derive(Debug, Deserialize)]
+struct SectionChange {
+    path: String,
+    heading: String,
+    #[serde(default)]
+    body: String,
+    #[serde(default)]
+    new_heading: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct NewCheatsheet {
+    directory: String,
+    name: String,
+}
+
+/// A bound server. Requests are handled when [`Server::run`] is called.
+pub struct Server {
+    listener: TcpListener,
+    state: Arc<State>,
+}
+
+struct State {
+    /// The config and the roots it allows, replaced when the config file changes.
+    conf: RwLock<CognitioConfig>,
+    roots: RwLock<AllowedRoots>,
+    renderer: HtmlRenderer,
+    search: Mutex<SearchIndex>,
+    /// The open event streams, as the senders of their messages.
+    subscribers: Mutex<Vec<Sender<String>>>,
+    /// Connections being handled, and how many of them are event streams.
+    connections: AtomicUsize,
+    event_streams: AtomicUsize,
+    options: ServeOptions,
+    /// Whether the server only listens on a loopback address, where the `Host` header is checked
+    /// so that web pages can't reach the server through a domain resolving to 127.0.0.1.
+    loopback: bool,
+}
+
+impl Server {
+    /// Binds to `addr`, builds the search index of the library in `conf` and starts watching it.
+    ///
+    /// Like the desktop app, the server then follows changes to the Cognitio config file, and
+    /// switches to its sources when it has no errors.
+    pub fn bind(
+        conf: CognitioConfig,
+        addr: impl ToSocketAddrs,
+        options: ServeOptions,
+    ) -> io::Result<Server> {
+        let listener = TcpListener::bind(addr)?;
+        let loopback = listener.local_addr()?.ip().is_loopback();
+        if !loopback && options.token.is_none() {
+            warn!("Serving without a token on an address others can reach");
+        }
+
+        let (sender, batches) = channel();
+        let watcher = WatchService::start(sender, config_debounce(&conf));
+        let state = Arc::new(State {
+            roots: RwLock::new(AllowedRoots::sources(&conf)),
+            renderer: HtmlRenderer::default(),
+            search: Mutex::new(SearchIndex::build(&conf)),
+            subscribers: Mutex::new(Vec::new()),
+            connections: AtomicUsize::new(0),
+            event_streams: AtomicUsize::new(0),
+            conf: RwLock::new(conf),
+            options,
+            loopback,
+        });
+
+        match watcher {
+            Ok(watcher) => {
+                watcher.set_roots(config_watch_roots(&state.conf.read().unwrap()));
+                let state = Arc::clone(&state);
+                thread::Builder::new()
+                    .name("cognitio-serve-events".to_string())
+                    .spawn(move || follow_changes(&state, &watcher, batches))?;
+            }
+            Err(error) => warn!("Not watching the library, pages won't reload: {error}"),
+        }
+        Ok(Server { listener, state })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Handles connections until the listener fails, each on its own thread.
+    pub fn run(self) -> io::Result<()> {
+        info!("Serving on http://{}", self.local_addr()?);
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(error) => {
+                    warn!("Failed to accept a connection: {error}");
+                    continue;
+                }
+            };
+            if !acquire(&self.state.connections, self.state.options.max_connections) {
+                debug!(
+                    "Turning a connection away, {} are open",
+                    self.state.options.max_connections
+                );
+                turn_away(&stream);
+                continue;
+            }
+            let state = Arc::clone(&self.state);
+            let spawned = thread::Builder::new().spawn(move || {
+                let _release = Release(&state.connections);
+                handle_connection(&state, &stream);
+            });
+            if let Err(error) = spawned {
+                warn!("Failed to start a thread for a connection: {error}");
+                self.state.connections.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Counts one more user of something there may be at most `max` of, like open connections.
+/// Returns false, counting nothing, when there are `max` already.
+fn acquire(count: &AtomicUsize, max: usize) -> bool {
+    count
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+            (count < max).then_some(count + 1)
+        })
+        .is_ok()
+}
+
+/// Counts one user less when dropped, see [`acquire`].
+struct Release<'a>(&'a AtomicUsize);
+
+impl Drop for Release<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Answers a connection there is no room for with a 503. The request head is read first, since
+/// closing a connection with unread data resets it, and the client might not see the answer.
+fn turn_away(mut stream: &TcpStream) {
+    let _ = stream.set_read_timeout(Some(BUSY_READ_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(BUSY_WRITE_TIMEOUT));
+    let mut head = Vec::new();
+    let mut buffer = [0; 1024];
+    while head.len() < MAX_HEAD_BYTES && !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        match stream.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(read) => head.extend_from_slice(&buffer[..read]),
+        }
+    }
+    let _ = busy_response().write_to(stream);
+}
+
+fn busy_response() -> Response {
+    Response::error(503, "The server is busy, try again later")
+        .with_header("Retry-After", "5".to_string())
+}
+
+/// Updates the state with the batches of changes from the watcher, and sends them to the event
+/// streams.
+fn follow_changes(state: &State, watcher: &WatchService, batches: Receiver<Vec<FileChange>>) {
+    for batch in batches {
+        for event in library_events(&batch, watcher) {
+            state.apply(&event);
+            state.publish(&event);
+        }
+    }
+}
+
+fn handle_connection(state: &State, stream: &TcpStream) {
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let response = match Request::read(stream) {
+        Ok(request) if request.method == "GET" && request.path == EVENTS_PATH => {
+            match state.admit(&request) {
+                Ok(_) => return state.stream_events(stream),
+                Err(response) => response,
+            }
+        }
+        Ok(request) => {
+            let response = state.handle(&request);
+            debug!("{} {} {}", request.method, request.path, response.status);
+            response
+        }
+        Err(response) => response,
+    };
+    if let Err(error) = response.write_to(stream) {
+        debug!("Failed to send a response: {error}");
+    }
+}
+
+impl State {
+    fn handle(&self, request: &Request) -> Response {
+        let from_query = match self.admit(request) {
+            Ok(from_query) => from_query,
+            Err(response) => return response,
+        };
+
+        let response = match self.route(request) {
+            Ok(response) => response,
+            Err(error) => error_response(&error),
+        };
+        // Browsers keep the token from the link they were given, so other pages work too.
+        match (&self.options.token, from_query) {
+            (Some(token), true) => response.with_header(
+                "Set-Cookie",
+                format!("{TOKEN_COOKIE}={token}; Path=/; HttpOnly; SameSite=Strict"),
+            ),
+            _ => response,
+        }
+    }
+
+    /// Checks the `Host` header and the token, and returns whether the token was given in the
+    /// query string.
+    fn admit(&self, request: &Request) -> Result<bool, Response> {
+        if self.loopback && !is_local_host(request.header("host")) {
+            return Err(Response::error(421, "The server only answers to localhost"));
+        }
+        self.authorize(request)
+    }
+
+    /// Checks the token, and returns whether it was given in the query string.
+    fn authorize(&self, request: &Request) -> Result<bool, Response> {
+        let Some(token) = &self.options.token else {
+            return Ok(false);
+        };
+        let bearer = request
+            .header("authorization")
+            .and_then(|value| value.strip_prefix("Bearer "));
+        if [bearer, request.cookie(TOKEN_COOKIE)]
+            .into_iter()
+            .flatten()
+            .any(|given| same_token(given, token))
+        {
+            return Ok(false);
+        }
+        if request
+            .query("token")
+            .is_some_and(|given| same_token(given, token))
+        {
+            return Ok(true);
+        }
+        Err(Response::error(401, "A valid token is required")
+            .with_header("WWW-Authenticate", "Bearer".to_string()))
+    }
+
+    fn route(&self, request: &Request) -> io::Result<Response> {
+        let method = request.method.as_str();
+        match (method, request.path.as_str()) {
+            ("GET", "/api/tree") => Ok(Response::json(200, &self.directories())),
+            ("GET", "/api/cheatsheet") => {
+                let path = self.cheatsheet_param(request, "path")?;
+                let markdown = fs::read_to_string(&path)?;
+                Ok(Response::json(200, &CheatsheetContent { path, markdown }))
+            }
+            ("GET", "/api/sections") => {
+                let path = self.cheatsheet_param(request, "path")?;
+                Ok(Response::json(200, &load_cheatsheet_file(&path)?))
+            }
+            ("GET", "/api/search") => {
+                let query = request.query("q").unwrap_or_default();
+                let limit = match request.query("limit") {
+                    Some(limit) => limit.parse().map_err(|_| {
+                        invalid_input(format!("limit must be a number, not '{limit}'"))
+                    })?,
+                    None => DEFAULT_SEARCH_LIMIT,
+                };
+                let hits = self.search.lock().unwrap().search(query, limit);
+                Ok(Response::json(200, &hits))
+            }
+            ("POST", "/api/cheatsheets") => {
+                self.check_writable()?;
+                let new: NewCheatsheet = json_body(request)?;
+                let directory = self.check_directory(&new.directory)?;
+                let cheatsheet = edit::create_cheatsheet(&directory, &new.name)?;
+                self.refresh_search(&cheatsheet);
+                Ok(Response::json(201, &cheatsheet))
+            }
+            ("POST" | "PUT" | "PATCH" | "DELETE", "/api/sections") => {
+                self.check_writable()?;
+                let change: SectionChange = json_body(request)?;
+                let path = self.check_cheatsheet(&change.path)?;
+                let heading = &change.heading;
+                let cheatsheet = match method {
+                    "POST" => edit::append_section(&path, heading, &change.body)?,
+                    "PUT" => edit::replace_section_body(&path, heading, &change.body)?,
+                    "PATCH" => edit::rename_section(&path, heading, &change.new_heading)?,
+                    _ => edit::delete_section(&path, heading)?,
+                };
+                self.refresh_search(&cheatsheet);
+                Ok(Response::json(200, &cheatsheet))
+            }
+            ("GET", "/" | "/index.html") => Ok(Response::html(self.index_page())),
+            ("GET", "/view") => {
+                let path = self.cheatsheet_param(request, "path")?;
+                let requested = request.query("path").unwrap_or_default();
+                Ok(Response::html(self.cheatsheet_page(&path, requested)?))
+            }
+            ("GET", "/search-index.json") => Ok(Response::json(200, &self.search_index())),
+            ("GET", "/assets/cognitio.css") => Ok(Response::new(
+                200,
+                "text/css; charset=utf-8",
+                site_css(&self.renderer),
+            )),
+            ("GET", "/assets/cognitio.js") => Ok(Response::new(
+                200,
+                "text/javascript; charset=utf-8",
+                SITE_JS,
+            )),
+            (_, path) if ROUTES.contains(&path) => Ok(Response::error(
+                405,
+                &format!("{method} is not allowed here"),
+            )),
+            _ => Ok(Response::error(404, "Not found")),
+        }
+    }
+
+    /// Sends the events of [`State::publish`] to `stream` until the client goes away.
+    fn stream_events(&self, mut stream: &TcpStream) {
+        let max_streams = (self.options.max_connections / 2).max(1);
+        if !acquire(&self.event_streams, max_streams) {
+            let _ = busy_response().write_to(stream);
+            return;
+        }
+        let _release = Release(&self.event_streams);
+
+        let (sender, messages) = channel();
+        self.subscribers.lock().unwrap().push(sender);
+        let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+        if write_event_stream_head(stream).is_err() {
+            return;
+        }
+        debug!("GET {EVENTS_PATH} 200");
+
+        loop {
+            let sent = match messages.recv_timeout(KEEPALIVE_INTERVAL) {
+                Ok(message) => stream.write_all(message.as_bytes()),
+                Err(RecvTimeoutError::Timeout) => stream.write_all(b": keepalive\n\n"),
+                Err(RecvTimeoutError::Disconnected) => return,
+            };
+            if sent.and_then(|_| stream.flush()).is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Brings the config, allowed paths and search index up to date after a change.
+    fn apply(&self, event: &LibraryEvent) {
+        match event {
+            LibraryEvent::ConfigChanged { config, .. } => {
+                info!("The config changed, serving its sources");
+                let search = SearchIndex::build(config);
+                *self.roots.write().unwrap() = AllowedRoots::sources(config);
+                *self.conf.write().unwrap() = config.clone();
+                *self.search.lock().unwrap() = search;
+            }
+            LibraryEvent::ConfigInvalid { .. } => {}
+            LibraryEvent::FilesChanged { events } => {
+                let mut search = self.search.lock().unwrap();
+                for path in events.iter().flat_map(|event| event.paths()) {
+                    search.refresh_path(path);
+                }
+            }
+        }
+    }
+
+    /// Sends an event to every open event stream, and forgets the streams that were closed.
+    fn publish(&self, event: &LibraryEvent) {
+        let data = match serde_json::to_string(event) {
+            Ok(data) => data,
+            Err(error) => {
+                warn!("Failed to send a {} event: {error}", event.name());
+                return;
+            }
+        };
+        let message = format!("event: {}\ndata: {data}\n\n", event.name());
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|subscriber| subscriber.send(message.clone()).is_ok());
+    }
+
+    fn directories(&self) -> Vec<Directory> {
+        let conf = self.conf.read().unwrap();
+        sort_directories_and_files(&list_cheatsheet_directories(&conf))
+    }
+
+    fn nav(&self) -> Vec<NavSection> {
+        plan_site(&self.directories())
+    }
+
+    /// A query parameter with the path of a cheatsheet, see [`State::check_cheatsheet`].
+    fn cheatsheet_param(&self, request: &Request, name: &str) -> io::Result<String> {
+        let path = request
+            .query(name)
+            .ok_or_else(|| invalid_input(format!("The {name} parameter is missing")))?;
+        self.check_cheatsheet(path)
+    }
+
+    /// Checks that `path` is a cheatsheet the library lists, and returns it canonicalized. Other
+    /// files in the sources, like `.git/config`, are never served.
+    fn check_cheatsheet(&self, path: &str) -> io::Result<String> {
+        self.check_listed(path, true)
+    }
+
+    /// Checks that `path` is a source or a directory the library lists, and returns it
+    /// canonicalized.
+    fn check_directory(&self, path: &str) -> io::Result<String> {
+        self.check_listed(path, false)
+    }
+
+    fn check_listed(&self, path: &str, file: bool) -> io::Result<String> {
+        let canonical = self.roots.read().unwrap().check_str(path)?;
+        let directories = self.directories();
+        let mut listed = Vec::new();
+        collect_paths(&directories, &mut listed, file);
+        if listed.iter().any(|listed| same_file(listed, &canonical)) {
+            Ok(canonical)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{path} is not in the cheatsheet library"),
+            ))
+        }
+    }
+
+    fn check_writable(&self) -> io::Result<()> {
+        if self.options.writable {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "The server is read-only, start it with --writable to change cheatsheets",
+            ))
+        }
+    }
+
+    fn refresh_search(&self, cheatsheet: &Cheatsheet) {
+        self.search.lock().unwrap().refresh_path(&cheatsheet.path);
+    }
+
+    fn index_page(&self) -> String {
+        let nav = self.nav();
+        let cheatsheets: usize = nav
+            .iter()
+            .flat_map(|section| &section.items)
+            .map(|item| item.pages.len())
+            .sum();
+        let content = format!(
+            "<h1 class=\"page-title\">Cheatsheets</h1>\n\
+             <p class=\"page-path\">{cheatsheets} cheatsheets. Pick one in the menu or search for \
+             a section.</p>\n"
+        );
+        site_page(
+            "Cheatsheets",
+            &content,
+            &nav_html(&nav, None, view_url),
+            "",
+            true,
+        )
+    }
+
+    /// The page of the cheatsheet at `path`. `requested` is the path as it was asked for, which
+    /// is how the menu knows it, rather than with symlinks resolved.
+    fn cheatsheet_page(&self, path: &str, requested: &str) -> io::Result<String> {
+        let markdown = fs::read_to_string(path)?;
+        let cheatsheet = parse_cheatsheet(path, &markdown);
+        let nav = self.nav();
+        let location = nav
+            .iter()
+            .flat_map(|section| &section.items)
+            .find(|item| item.pages.iter().any(|page| page.source == requested))
+            .map_or(requested, |item| item.title.as_str());
+
+        let content = cheatsheet_content(&self.renderer, &cheatsheet, location);
+        let menu = nav_html(&nav, Some(requested), view_url);
+        Ok(site_page(&cheatsheet.name, &content, &menu, "", true))
+    }
+
+    /// The index the search box in the pages loads, like the one of an exported site.
+    fn search_index(&self) -> Vec<SearchEntry> {
+        let mut entries = Vec::new();
+        for item in self.nav().iter().flat_map(|section| &section.items) {
+            for page in &item.pages {
+                match load_cheatsheet_file(&page.source) {
+                    Ok(cheatsheet) => {
+                        entries.extend(search_entries(&cheatsheet, &view_url(page), &item.title))
+                    }
+                    Err(error) => warn!("Leaving {} out of the search index: {error}", page.source),
+                }
+            }
+        }
+        entries
+    }
+}
+
+/// Collects the paths of the files, or of the directories, in a tree.
+fn collect_paths<'a>(directories: &'a [Directory], paths: &mut Vec<&'a str>, files: bool) {
+    for directory in directories {
+        if files {
+            paths.extend(directory.files.iter().map(|file| file.path.as_str()));
+        } else {
+            paths.push(&directory.path);
+        }
+        collect_paths(&directory.sub_directories, paths, files);
+    }
+}
+
+fn same_file(listed: &str, canonical: &str) -> bool {
+    fs::canonicalize(listed).is_ok_and(|listed| listed == Path::new(canonical))
+}
+
+fn view_url(page: &Page) -> String {
+    format!("view?path={}", percent_encode(&page.source))
+}
+
+/// Reads a JSON body. Other web sites can make browsers send forms to the server, but not with
+/// this content type, so this also keeps them from changing cheatsheets.
+fn json_body<T: serde::de::DeserializeOwned>(request: &Request) -> io::Result<T> {
+    let content_type = request.header("content-type").unwrap_or_default();
+    if !content_type.starts_with("application/json") {
+        return Err(invalid_input(
+            "The request body must be sent as application/json".to_string(),
+        ));
+    }
+    serde_json::from_slice(&request.body)
+        .map_err(|error| invalid_input(format!("Invalid request body: {error}")))
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn error_response(error: &io::Error) -> Response {
+    let status = match error.kind() {
+        io::ErrorKind::NotFound => 404,
+        io::ErrorKind::PermissionDenied => 403,
+        io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData => 400,
+        io::ErrorKind::AlreadyExists => 409,
+        _ => 500,
+    };
+    if status == 500 {
+        warn!("Failed to handle a request: {error}");
+    }
+    Response::error(status, &error.to_string())
+}
+
+/// Whether the `Host` header names the local machine.
+fn is_local_host(host: Option<&str>) -> bool {
+    let Some(host) = host else {
+        return false;
+    };
+    let name = match host.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    matches!(
+        name.to_ascii_lowercase().as_str(),
+        "localhost" | "127.0.0.1" | "::1"
+    )
+}
+
+/// Compares tokens in time that doesn't depend on where they differ.
+fn same_token(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
@@ -0,0 +1,344 @@
+use crate::config::cognitio_home_dir;
+use crate::edit::write_atomically;
+use crate::library::Directory;
+use log::warn;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Shorthand IDs for cheatsheet files and directories, like `doco` for `docker/compose.md`.
+///
+/// IDs are unique across all sources. Once assigned, an ID is saved in `shorthand_ids.yaml` in
+/// [`cognitio_home_dir`] and kept for as long as its path exists, so adding files never changes
+/// the IDs of existing ones. IDs of paths in sources that couldn't be scanned, like a git source
+/// that isn't cloned or a source on a disk that isn't mounted, are kept too.
+#[derive(Debug, Default)]
+pub struct ShorthandIds {
+    /// Path to ID.
+    ids: BTreeMap<String, String>,
+    changed: bool,
+}
+
+/// Where assigned shorthand IDs are saved.
+pub fn shorthand_ids_path() -> PathBuf {
+    PathBuf::from(cognitio_home_dir()).join("shorthand_ids.yaml")
+}
+
+/// Loads the saved IDs, assigns IDs to every directory and file in the tree, writes them to the
+/// tree's `shorthand_id` fields and saves the IDs if anything changed.
+pub fn load_and_assign_shorthand_ids(dirs: &mut [Directory]) -> io::Result<ShorthandIds> {
+    let path = shorthand_ids_path();
+    let mut ids = ShorthandIds::load(&path)?;
+    ids.assign(dirs);
+    enrich_directories_with_shorthand_ids(dirs, &ids);
+
+    if ids.changed {
+        if let Err(error) = ids.save(&path) {
+            // IDs still work for this run, they just might not be stable.
+            warn!(
+                "Failed to save shorthand IDs to {}: {error:?}",
+                path.display()
+            );
+        }
+    }
+
+    Ok(ids)
+}
+
+/// Writes assigned IDs to the `shorthand_id` fields of every directory and file in the tree.
+pub fn enrich_directories_with_shorthand_ids(dirs: &mut [Directory], ids: &ShorthandIds) {
+    dirs.iter_mut().for_each(|dir| {
+        dir.shorthand_id = ids.id_for(&dir.path).unwrap_or_default().to_string();
+        dir.files.iter_mut().for_each(|file| {
+            file.shorthand_id = ids.id_for(&file.path).unwrap_or_default().to_string();
+        });
+        enrich_directories_with_shorthand_ids(&mut dir.sub_directories, ids);
+    });
+}
+
+impl ShorthandIds {
+    /// Reads saved IDs. A missing file gives no IDs.
+    pub fn load(path: &Path) -> io::Result<ShorthandIds> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(ShorthandIds::default())
+            }
+            Err(error) => return Err(error),
+        };
+
+        let ids: BTreeMap<String, String> = if contents.trim().is_empty() {
+            BTreeMap::new()
+        } else {
+            serde_yaml::from_str(&contents)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?
+        };
+
+        Ok(ShorthandIds {
+            ids,
+            changed: false,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let contents = serde_yaml::to_string(&self.ids)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        write_atomically(path, &contents)
+    }
+
+    /// Makes sure every directory and file in the tree has an ID, and forgets the IDs of paths
+    /// that are no longer in the tree. Only paths in sources that were scanned, whose directory
+    /// exists, are forgotten, the others keep their IDs for when the source is back.
+    pub fn assign(&mut self, dirs: &[Directory]) {
+        let mut paths = BTreeMap::new();
+        collect_paths(dirs, &mut paths);
+        let scanned: Vec<&Path> = dirs
+            .iter()
+            .map(|dir| Path::new(&dir.path))
+            .filter(|root| root.is_dir())
+            .collect();
+
+        let before = self.ids.len();
+        self.ids.retain(|path, _| {
+            paths.contains_key(path)
+                || !scanned.iter().any(|root| Path::new(path).starts_with(root))
+        });
+        if self.ids.len() != before {
+            self.changed = true;
+        }
+
+        // Saved IDs win. Should the file have been edited by hand to contain duplicates, the
+        // first path keeps the ID and the others get new ones below.
+        let mut taken = HashSet::new();
+        let mut duplicates = Vec::new();
+        for (path, id) in &self.ids {
+            if !taken.insert(id.clone()) {
+                duplicates.push(path.clone());
+            }
+        }
+        for path in duplicates {
+            self.ids.remove(&path);
+        }
+
+        for (path, is_file) in paths {
+            if self.ids.contains_key(&path) {
+                continue;
+            }
+            let id = unique_id(&path, is_file, &taken);
+            taken.insert(id.clone());
+            self.ids.insert(path, id);
+            self.changed = true;
+        }
+    }
+
+    pub fn id_for(&self, path: &str) -> Option<&str> {
+        self.ids.get(path).map(|id| id.as_str())
+    }
+
+    /// Finds the path with the given ID, ignoring case.
+    pub fn resolve(&self, id: &str) -> Option<&str> {
+        self.ids
+            .iter()
+            .find(|(_, candidate)| candidate.eq_ignore_ascii_case(id.trim()))
+            .map(|(path, _)| path.as_str())
+    }
+}
+
+/// Collects every directory and file path in the tree, mapped to whether it is a file.
+fn collect_paths(dirs: &[Directory], paths: &mut BTreeMap<String, bool>) {
+    for dir in dirs {
+        paths.insert(dir.path.clone(), false);
+        for file in &dir.files {
+            paths.insert(file.path.clone(), true);
+        }
+        collect_paths(&dir.sub_directories, paths);
+    }
+}
+
+/// Picks an ID for a path that doesn't collide with any taken ID.
+///
+/// The ID is made from the first letters of the last path components: three for files (parent
+/// directories and the file name) and two for directories. On a collision more letters of each
+/// component are used, and if the names are identical all the way a number is added.
+fn unique_id(path: &str, is_file: bool, taken: &HashSet<String>) -> String {
+    let parts = id_parts(path, if is_file { 3 } else { 2 });
+    let longest = parts.iter().map(|part| part.len()).max().unwrap_or(0);
+
+    for letters in 2..=longest.max(2) {
+        let candidate = candidate_id(&parts, letters);
+        if !taken.contains(&candidate) {
+            return candidate;
+        }
+    }
+
+    let base = candidate_id(&parts, 2);
+    (2..)
+        .map(|number| format!("{base}{number}"))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("there is always a free number")
+}
+
+fn candidate_id(parts: &[String], letters: usize) -> String {
+    parts
+        .iter()
+        .map(|part| part.chars().take(letters).collect::<String>())
+        .collect()
+}
+
+/// The last `count` path components, reduced to lowercase ASCII letters and digits. The `.md`
+/// extension is dropped from file names.
+fn id_parts(path: &str, count: usize) -> Vec<String> {
+    let path = Path::new(path);
+    let mut parts: Vec<String> = path
+        .components()
+        .rev()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .enumerate()
+        .map(|(index, name)| {
+            let name = if index == 0 {
+                name.strip_suffix(".md").unwrap_or(&name).to_string()
+            } else {
+                name
+            };
+            fold_to_ascii(&name)
+        })
+        .filter(|part| !part.is_empty())
+        .take(count)
+        .collect();
+    parts.reverse();
+
+    if parts.is_empty() {
+        parts.push("x".to_string());
+    }
+    parts
+}
+
+/// Lowercases a name and keeps only ASCII letters and digits, spelling out common accented
+/// letters, so `Ærøskøbing` becomes `aeroskobing`.
+fn fold_to_ascii(name: &str) -> String {
+    let mut folded = String::new();
+    for c in name.chars().flat_map(|c| c.to_lowercase()) {
+        match c {
+            'a'..='z' | '0'..='9' => folded.push(c),
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => folded.push('a'),
+            'æ' => folded.push_str("ae"),
+            'ç' => folded.push('c'),
+            'è' | 'é' | 'ê' | 'ë' => folded.push('e'),
+            'ì' | 'í' | 'î' | 'ï' => folded.push('i'),
+            'ñ' => folded.push('n'),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => folded.push('o'),
+            'ù' | 'ú' | 'û' | 'ü' => folded.push('u'),
+            'ý' | 'ÿ' => folded.push('y'),
+            'ß' => folded.push_str("ss"),
+            _ => {}
+        }
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::library::DirectoryFile;
+    use std::process;
+
+    fn directory(path: &str, files: &[&str], sub_directories: Vec<Directory>) -> Directory {
+        Directory {
+            name: path.rsplit('/').next().unwrap().to_string(),
+            path: path.to_string(),
+            files: files
+                .iter()
+                .map(|name| DirectoryFile {
+                    name: name.to_string(),
+                    path: format!("{path}/{name}"),
+                    shorthand_id: String::new(),
+                })
+                .collect(),
+            sub_directories,
+            shorthand_id: String::new(),
+        }
+    }
+
+    #[test]
+    fn keeps_ids_when_files_are_added() {
+        let mut ids = ShorthandIds::default();
+        let docker = directory("/lib/docker", &["compose.md"], vec![]);
+        ids.assign(&[directory("/lib", &[], vec![docker])]);
+        assert_eq!(ids.id_for("/lib/docker/compose.md"), Some("lidoco"));
+        assert_eq!(ids.id_for("/lib/docker"), Some("lido"));
+        assert!(ids.changed);
+
+        let docker = directory("/lib/docker", &["config.md", "compose.md"], vec![]);
+        ids.assign(&[directory("/lib", &[], vec![docker])]);
+        assert_eq!(ids.id_for("/lib/docker/compose.md"), Some("lidoco"));
+        assert_eq!(ids.id_for("/lib/docker/config.md"), Some("libdoccon"));
+        assert_eq!(ids.resolve(" LIBDOCCON"), Some("/lib/docker/config.md"));
+    }
+
+    #[test]
+    fn uses_more_letters_then_a_number() {
+        let taken: HashSet<String> = ["dico".to_string()].into();
+        assert_eq!(unique_id("/a/dir/compose.md", true, &taken), "adico");
+        assert_eq!(unique_id("/dir/compose.md", true, &taken), "dircom");
+
+        let taken: HashSet<String> = ["dico", "dircom", "dircomp", "dircompo", "dircompos"]
+            .map(String::from)
+            .into();
+        assert_eq!(unique_id("/dir/compose.md", true, &taken), "dircompose");
+        let taken: HashSet<_> = taken.into_iter().chain(["dircompose".into()]).collect();
+        assert_eq!(unique_id("/dir/compose.md", true, &taken), "dico2");
+        assert_eq!(unique_id("/Ærø/", false, &HashSet::new()), "ae");
+        assert_eq!(unique_id("/", false, &HashSet::new()), "x");
+    }
+
+    #[test]
+    fn forgets_ids_only_in_scanned_sources() {
+        let dir = std::env::temp_dir().join(format!("cognitio-shorthand-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("source")).unwrap();
+        let source = dir.join("source").to_string_lossy().to_string();
+        let unmounted = dir.join("unmounted").to_string_lossy().to_string();
+
+        let mut ids = ShorthandIds::default();
+        ids.assign(&[
+            directory(&source, &["gone.md", "kept.md"], vec![]),
+            directory(&unmounted, &["gone.md"], vec![]),
+        ]);
+        let unmounted_id = ids
+            .id_for(&format!("{unmounted}/gone.md"))
+            .unwrap()
+            .to_string();
+        let path = dir.join("shorthand_ids.yaml");
+        ids.save(&path).unwrap();
+
+        let mut ids = ShorthandIds::load(&path).unwrap();
+        assert!(!ids.changed);
+        ids.assign(&[
+            directory(&source, &["kept.md", "new.md"], vec![]),
+            directory(&unmounted, &[], vec![]),
+        ]);
+        assert_eq!(ids.id_for(&format!("{source}/gone.md")), None);
+        assert!(ids.id_for(&format!("{source}/kept.md")).is_some());
+        assert_eq!(
+            ids.id_for(&format!("{unmounted}/gone.md")),
+            Some(unmounted_id.as_str())
+        );
+        assert_ne!(
+            ids.id_for(&format!("{source}/new.md")),
+            Some(unmounted_id.as_str())
+        );
+    }
+
+    #[test]
+    fn folds_names_to_ascii() {
+        assert_eq!(fold_to_ascii("Ærøskøbing"), "aeroskobing");
+        assert_eq!(fold_to_ascii("Straße"), "strasse");
+        assert_eq!(fold_to_ascii("Café Crème"), "cafecreme");
+        assert_eq!(fold_to_ascii("C++ & Go 2!"), "cgo2");
+        assert_eq!(fold_to_ascii("日本"), "");
+    }
+}
@@ -0,0 +1,433 @@
+use crate::content::read_file_to_string;
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::path::Path;
+
+/// A cheatsheet file parsed into snippets.
+///
+/// Each h3 heading starts a [`Snippet`] (shown as a card in the app), and h2 headings group the
+/// snippets below them. An h1 heading at the top of the file is taken as the cheatsheet title.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Cheatsheet {
+    /// File name without the `.md` extension.
+    pub name: String,
+    pub path: String,
+    pub title: Option<String>,
+    /// The first group holds the snippets above the first h2 heading and has no title.
+    pub groups: Vec<SnippetGroup>,
+}
+
+/// Snippets under the same h2 heading.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SnippetGroup {
+    pub title: Option<String>,
+    /// Line of the h2 heading, if any.
+    pub line: Option<usize>,
+    /// Content between the h2 heading and the first h3 heading.
+    pub intro: Vec<Block>,
+    pub sections: Vec<Snippet>,
+}
+
+/// An h3 section of a cheatsheet.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Snippet {
+    pub title: String,
+    pub blocks: Vec<Block>,
+    /// Line of the h3 heading.
+    pub start_line: usize,
+    /// Last non-blank line before the next heading or the end of the file.
+    pub end_line: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Block {
+    Prose(ProseBlock),
+    Code(CodeBlock),
+}
+
+/// Consecutive markdown that isn't a code block, like paragraphs, lists and tables.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProseBlock {
+    pub markdown: String,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CodeBlock {
+    /// The first word of the info string, e.g. `bash` for ```` ```bash title="x" ````.
+    pub language: Option<String>,
+    /// The full info string after the opening fence. Empty for indented code blocks.
+    pub info: String,
+    pub code: String,
+    /// Line of the opening fence.
+    pub start_line: usize,
+    /// Line of the closing fence.
+    pub end_line: usize,
+}
+
+impl Cheatsheet {
+    /// All snippets in the order they appear in the file.
+    pub fn sections(&self) -> impl Iterator<Item = &Snippet> {
+        self.groups.iter().flat_map(|group| group.sections.iter())
+    }
+
+    /// Finds a snippet by its title, ignoring case and surrounding whitespace.
+    pub fn find_section(&self, title: &str) -> Option<&Snippet> {
+        let title = title.trim().to_lowercase();
+        self.sections()
+            .find(|section| section.title.trim().to_lowercase() == title)
+    }
+}
+
+impl Snippet {
+    pub fn code_blocks(&self) -> impl Iterator<Item = &CodeBlock> {
+        self.blocks.iter().filter_map(|block| match block {
+            Block::Code(code) => Some(code),
+            Block::Prose(_) => None,
+        })
+    }
+}
+
+/// Reads and parses the cheatsheet file at `path`.
+pub fn load_cheatsheet_file(path: &str) -> std::io::Result<Cheatsheet> {
+    let markdown = read_file_to_string(path)?;
+    Ok(parse_cheatsheet(path, &markdown))
+}
+
+/// Parses the markdown of a cheatsheet file. Line numbers in the result are 1-based.
+pub fn parse_cheatsheet(path: &str, markdown: &str) -> Cheatsheet {
+    let mut parser = CheatsheetParser {
+        markdown,
+        line_starts: line_starts(markdown),
+        cheatsheet: Cheatsheet {
+            name: cheatsheet_name(path),
+            path: path.to_string(),
+            title: None,
+            groups: vec![SnippetGroup::default()],
+        },
+        in_section: false,
+        prose: None,
+    };
+    parser.parse();
+    parser.cheatsheet
+}
+
+fn cheatsheet_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .unwrap_or_default()
+        .to_str()
+        .unwrap_or_default()
+        .replace(".md", "")
+}
+
+struct CheatsheetParser<'a> {
+    markdown: &'a str,
+    line_starts: Vec<usize>,
+    cheatsheet: Cheatsheet,
+    /// True after an h3 heading, until the next h1-h3 heading.
+    in_section: bool,
+    /// Byte range of prose that hasn't been turned into a [`ProseBlock`] yet.
+    prose: Option<Range<usize>>,
+}
+
+enum Element {
+    Heading(HeadingLevel, String),
+    Code(String, String),
+}
+
+impl<'a> CheatsheetParser<'a> {
+    fn parse(&mut self) {
+        let markdown = self.markdown;
+        let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+        let mut depth = 0;
+        let mut element: Option<Element> = None;
+
+        for (event, range) in Parser::new_ext(markdown, options).into_offset_iter() {
+            match event {
+                Event::Start(tag) => {
+                    if depth == 0 {
+                        match tag {
+                            Tag::Heading { level, .. } if level <= HeadingLevel::H3 => {
+                                element = Some(Element::Heading(level, String::new()));
+                            }
+                            Tag::CodeBlock(kind) => {
+                                let info = match kind {
+                                    CodeBlockKind::Fenced(info) => info.trim().to_string(),
+                                    CodeBlockKind::Indented => String::new(),
+                                };
+                                element = Some(Element::Code(info, String::new()));
+                            }
+                            _ => self.add_prose(range.clone()),
+                        }
+                    }
+                    depth += 1;
+                }
+                Event::End(tag_end) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        match (tag_end, element.take()) {
+                            (TagEnd::Heading(_), Some(Element::Heading(level, title))) => {
+                                self.add_heading(level, title.trim().to_string(), range);
+                            }
+                            (TagEnd::CodeBlock, Some(Element::Code(info, code))) => {
+                                self.add_code(info, code, range);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Event::Text(text) | Event::Code(text) => match element.as_mut() {
+                    Some(Element::Heading(_, title)) => title.push_str(&text),
+                    Some(Element::Code(_, code)) => code.push_str(&text),
+                    None => {
+                        if depth == 0 {
+                            self.add_prose(range);
+                        }
+                    }
+                },
+                _ => {
+                    if depth == 0 {
+                        self.add_prose(range);
+                    }
+                }
+            }
+        }
+
+        self.flush_prose();
+    }
+
+    fn add_heading(&mut self, level: HeadingLevel, title: String, range: Range<usize>) {
+        self.flush_prose();
+        let line = self.line_of(range.start);
+
+        match level {
+            HeadingLevel::H1 if self.cheatsheet.title.is_none() => {
+                self.in_section = false;
+                self.cheatsheet.title = Some(title);
+            }
+            HeadingLevel::H1 => self.add_prose(range),
+            HeadingLevel::H2 => {
+                self.in_section = false;
+                self.cheatsheet.groups.push(SnippetGroup {
+                    title: Some(title),
+                    line: Some(line),
+                    ..SnippetGroup::default()
+                });
+            }
+            _ => {
+                self.in_section = true;
+                self.current_group().sections.push(Snippet {
+                    title,
+                    blocks: Vec::new(),
+                    start_line: line,
+                    end_line: line,
+                });
+            }
+        }
+    }
+
+    fn add_code(&mut self, info: String, code: String, range: Range<usize>) {
+        self.flush_prose();
+        let language = info.split_whitespace().next().map(|lang| lang.to_string());
+        let block = Block::Code(CodeBlock {
+            language,
+            info,
+            code,
+            start_line: self.line_of(range.start),
+            end_line: self.line_of(range.end.saturating_sub(1)),
+        });
+        self.push_block(block);
+    }
+
+    fn add_prose(&mut self, range: Range<usize>) {
+        self.prose = match self.prose.take() {
+            Some(prose) => Some(prose.start..range.end.max(prose.end)),
+            None => Some(range),
+        };
+    }
+
+    fn flush_prose(&mut self) {
+        if let Some(range) = self.prose.take() {
+            // Lists take in the blank lines after them, which don't count.
+            let markdown = self.markdown[range.clone()].trim_end().to_string();
+            let end = range.start + markdown.len();
+            let block = Block::Prose(ProseBlock {
+                start_line: self.line_of(range.start),
+                end_line: self.line_of(end.saturating_sub(1)),
+                markdown,
+            });
+            self.push_block(block);
+        }
+    }
+
+    fn push_block(&mut self, block: Block) {
+        let end_line = match &block {
+            Block::Prose(prose) => prose.end_line,
+            Block::Code(code) => code.end_line,
+        };
+
+        if self.in_section {
+            if let Some(section) = self.current_group().sections.last_mut() {
+                section.end_line = end_line;
+                section.blocks.push(block);
+                return;
+            }
+        }
+
+        self.current_group().intro.push(block);
+    }
+
+    fn current_group(&mut self) -> &mut SnippetGroup {
+        self.cheatsheet
+            .groups
+            .last_mut()
+            .expect("a cheatsheet always has at least one group")
+    }
+
+    fn line_of(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(index) => index + 1,
+            Err(index) => index,
+        }
+    }
+}
+
+fn line_starts(markdown: &str) -> Vec<usize> {
+    let mut starts = vec![0];
+    starts.extend(markdown.match_indices('\n').map(|(index, _)| index + 1));
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MARKDOWN: &str = "\
+# Docker
+
+Commands I keep looking up.
+
+### Version
+
+```bash
+docker version
+```
+
+## Containers
+
+Everything about containers.
+
+### Run a container
+
+Starts it in the background.
+
+```bash title=\"run\"
+docker run -d {{image}}
+# Not a heading
+```
+
+- `-d`: detached
+
+### List containers
+
+    docker ps
+
+# Not the title
+
+## Images
+";
+
+    fn code(snippet: &Snippet) -> Vec<&str> {
+        snippet
+            .code_blocks()
+            .map(|block| block.code.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn parses_groups_and_sections() {
+        let cheatsheet = parse_cheatsheet("/lib/docker.md", MARKDOWN);
+        assert_eq!(cheatsheet.name, "docker");
+        assert_eq!(cheatsheet.title.as_deref(), Some("Docker"));
+
+        let groups: Vec<_> = cheatsheet
+            .groups
+            .iter()
+            .map(|group| (group.title.as_deref(), group.line, group.sections.len()))
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                (None, None, 1),
+                (Some("Containers"), Some(11), 2),
+                (Some("Images"), Some(32), 0),
+            ]
+        );
+        let Block::Prose(intro) = &cheatsheet.groups[1].intro[0] else {
+            panic!("no group intro");
+        };
+        assert_eq!(intro.markdown, "Everything about containers.");
+        assert_eq!((intro.start_line, intro.end_line), (13, 13));
+
+        // The text before the first h3 heading belongs to no section.
+        assert_eq!(cheatsheet.groups[0].intro.len(), 1);
+        let titles: Vec<_> = cheatsheet
+            .sections()
+            .map(|section| (section.title.as_str(), section.start_line, section.end_line))
+            .collect();
+        assert_eq!(
+            titles,
+            [
+                ("Version", 5, 9),
+                ("Run a container", 15, 24),
+                ("List containers", 26, 30),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_code_blocks() {
+        let cheatsheet = parse_cheatsheet("/lib/docker.md", MARKDOWN);
+        let run = cheatsheet.find_section("  run A CONTAINER ").unwrap();
+        assert_eq!(run.blocks.len(), 3);
+        let block = run.code_blocks().next().unwrap();
+        assert_eq!(block.language.as_deref(), Some("bash"));
+        assert_eq!(block.info, "bash title=\"run\"");
+        assert_eq!(block.code, "docker run -d {{image}}\n# Not a heading\n");
+        assert_eq!((block.start_line, block.end_line), (19, 22));
+
+        let list = cheatsheet.find_section("List containers").unwrap();
+        assert_eq!(code(list), ["docker ps\n"]);
+        assert_eq!(list.code_blocks().next().unwrap().language, None);
+
+        // A second h1 heading is prose of the section it is in.
+        let Block::Prose(prose) = list.blocks.last().unwrap() else {
+            panic!("the second h1 heading is missing");
+        };
+        assert_eq!(prose.markdown, "# Not the title");
+    }
+
+    #[test]
+    fn parses_files_without_headings() {
+        let cheatsheet = parse_cheatsheet("/lib/notes.md", "Just notes.\n");
+        assert_eq!(cheatsheet.title, None);
+        assert_eq!(cheatsheet.sections().count(), 0);
+        assert_eq!(cheatsheet.groups[0].intro.len(), 1);
+
+        let cheatsheet = parse_cheatsheet("/lib/empty.md", "");
+        assert_eq!(cheatsheet.groups.len(), 1);
+        assert!(cheatsheet.groups[0].intro.is_empty());
+    }
+
+    #[test]
+    fn parses_a_section_at_the_end_without_a_newline() {
+        let cheatsheet = parse_cheatsheet("/lib/git.md", "### Status\n\n```sh\ngit status\n```");
+        let status = cheatsheet.find_section("Status").unwrap();
+        assert_eq!((status.start_line, status.end_line), (1, 5));
+        assert_eq!(code(status), ["git status\n"]);
+    }
+}
@@ -0,0 +1,205 @@
+use crate::config::{
+    cognitio_home_dir, config_dir, expand_path, CheatsheetData, CognitioConfig, GitSource,
+};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The result of syncing one git source.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SyncResult {
+    pub title: String,
+    pub git: String,
+    /// Where the repository is checked out.
+    pub path: String,
+    #[serde(flatten)]
+    pub outcome: SyncOutcome,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SyncOutcome {
+    /// Cloned for the first time.
+    Cloned {
+        commit: String,
+    },
+    /// Fast-forwarded to a newer commit.
+    Updated {
+        from: String,
+        to: String,
+    },
+    UpToDate {
+        commit: String,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+/// The directory git sources are cloned into: `sources` in [`cognitio_home_dir`].
+pub fn sources_cache_dir() -> PathBuf {
+    PathBuf::from(cognitio_home_dir()).join("sources")
+}
+
+impl GitSource {
+    /// Where this source is checked out. The directory name is made from the repository name and
+    /// a hash of the URL and branch, so two sources never share a checkout.
+    pub fn checkout_dir(&self) -> PathBuf {
+        let name: String = self
+            .git
+            .trim_end_matches('/')
+            .rsplit(['/', '\\', ':'])
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches(".git")
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        let key = format!(
+            "{}#{}",
+            self.git,
+            self.branch.as_deref().unwrap_or_default()
+        );
+        sources_cache_dir().join(format!("{name}-{:016x}", fnv1a(key.as_bytes())))
+    }
+}
+
+/// Clones or fast-forwards every git source in the config.
+pub fn sync_sources(conf: &CognitioConfig) -> Vec<SyncResult> {
+    conf.cheatsheets
+        .iter()
+        .filter_map(|source| match source {
+            CheatsheetData::Git(git) => Some(sync_source(git)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Clones a git source if it isn't cloned yet, otherwise fetches and fast-forwards it. Local
+/// changes in the checkout are never overwritten; a checkout that can't be fast-forwarded fails.
+pub fn sync_source(source: &GitSource) -> SyncResult {
+    let dir = source.checkout_dir();
+    let outcome = if dir.join(".git").exists() {
+        update(source, &dir)
+    } else {
+        clone(source, &dir)
+    }
+    .unwrap_or_else(|message| SyncOutcome::Failed { message });
+
+    info!("Synced {} ({}): {outcome:?}", source.title, source.git);
+    SyncResult {
+        title: source.title.clone(),
+        git: source.git.clone(),
+        path: dir.to_string_lossy().to_string(),
+        outcome,
+    }
+}
+
+fn clone(source: &GitSource, dir: &Path) -> Result<SyncOutcome, String> {
+    if let Some(parent) = dir.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("Failed to create {}: {error}", parent.display()))?;
+    }
+    // A directory left behind by an earlier clone that failed half way would make git refuse.
+    if dir.exists() {
+        fs::remove_dir_all(dir)
+            .map_err(|error| format!("Failed to remove {}: {error}", dir.display()))?;
+    }
+
+    let dir_arg = dir.to_string_lossy().to_string();
+    let mut args = vec!["clone", "--quiet"];
+    if let Some(branch) = &source.branch {
+        args.extend(["--branch", branch]);
+    }
+    let url = clone_url(&source.git)?;
+    args.extend(["--", &url, &dir_arg]);
+    git(None, &args)?;
+
+    Ok(SyncOutcome::Cloned { commit: head(dir)? })
+}
+
+/// Local repository paths are expanded like other source paths, so `~/repos/cheats.git` and
+/// paths relative to the config file work. URLs are used as they are.
+fn clone_url(git: &str) -> Result<String, String> {
+    if git.starts_with(['~', '.', '/', '$']) {
+        let path = expand_path(git, &config_dir())?;
+        Ok(path.to_string_lossy().to_string())
+    } else {
+        Ok(git.to_string())
+    }
+}
+
+fn update(source: &GitSource, dir: &Path) -> Result<SyncOutcome, String> {
+    let before = head(dir)?;
+    let remote_ref = source.branch.as_deref().unwrap_or("HEAD");
+    git(Some(dir), &["fetch", "--quiet", "origin", remote_ref])?;
+
+    if let Some(branch) = &source.branch {
+        let current = git(Some(dir), &["rev-parse", "--abbrev-ref", "HEAD"])?;
+        if &current != branch {
+            // The branch was changed in the config. Switching fails rather than losing local
+            // changes.
+            git(
+                Some(dir),
+                &["checkout", "--quiet", "-B", branch, "FETCH_HEAD"],
+            )?;
+        }
+    }
+    git(Some(dir), &["merge", "--ff-only", "--quiet", "FETCH_HEAD"])?;
+
+    let after = head(dir)?;
+    if before == after {
+        Ok(SyncOutcome::UpToDate { commit: after })
+    } else {
+        Ok(SyncOutcome::Updated {
+            from: before,
+            to: after,
+        })
+    }
+}
+
+fn head(dir: &Path) -> Result<String, String> {
+    git(Some(dir), &["rev-parse", "--short", "HEAD"])
+}
+
+/// Runs git and returns its trimmed stdout, or its stderr as the error.
+fn git(dir: Option<&Path>, args: &[&str]) -> Result<String, String> {
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+    // Never wait for a password prompt nobody will see.
+    command.args(args).env("GIT_TERMINAL_PROMPT", "0");
+    debug!("Running git {}", args.join(" "));
+
+    let output = command
+        .output()
+        .map_err(|error| format!("Failed to run git: {error}"))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        // The first line holds the reason, the rest is advice about access rights and the like.
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            stderr.lines().next().unwrap_or_default().trim()
+        ))
+    }
+}
+
+/// 64-bit FNV-1a, used for checkout directory names since it is stable across Rust versions,
+/// unlike the standard library's hasher.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
@@ -0,0 +1,1062 @@
+use crate::config::{
+    check_cognitio_yaml, cognitio_home_dir, cognitio_yaml_path, config_dir, CognitioConfig,
+    ConfigError,
+};
+use log::{debug, error, info, warn};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How long the watcher waits for more changes before reporting a batch, when the config doesn't
+/// say.
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
+/// How long the watcher waits at most before reporting a batch while changes keep coming in, as
+/// during a `git pull` into a big source. A longer debounce window makes it wait longer.
+const MAX_BATCH_DELAY: Duration = Duration::from_secs(2);
+
+/// A change to a file or directory below a watched directory.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum FileChange {
+    Create { path: String },
+    Modify { path: String },
+    Remove { path: String },
+    Rename { from: String, to: String },
+}
+
+impl FileChange {
+    /// Every path the change touches: both paths for a rename.
+    pub fn paths(&self) -> Vec<&str> {
+        match self {
+            FileChange::Create { path }
+            | FileChange::Modify { path }
+            | FileChange::Remove { path } => vec![path],
+            FileChange::Rename { from, to } => vec![from, to],
+        }
+    }
+}
+
+/// What a batch of [`FileChange`]s means for Cognitio, see [`classify_changes`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChangeEvent {
+    /// The config file was changed, created or removed.
+    ConfigChanged {
+        path: String,
+    },
+    CheatsheetCreated {
+        path: String,
+    },
+    CheatsheetModified {
+        path: String,
+    },
+    CheatsheetRemoved {
+        path: String,
+    },
+    CheatsheetRenamed {
+        from: String,
+        to: String,
+    },
+    /// A directory was created, removed or renamed, so the tree of cheatsheets has changed.
+    DirectoryChanged {
+        path: String,
+    },
+}
+
+impl ChangeEvent {
+    /// Every path the event is about: both paths for a rename.
+    pub fn paths(&self) -> Vec<&str> {
+        match self {
+            ChangeEvent::ConfigChanged { path }
+            | ChangeEvent::CheatsheetCreated { path }
+            | ChangeEvent::CheatsheetModified { path }
+            | ChangeEvent::CheatsheetRemoved { path }
+            | ChangeEvent::DirectoryChanged { path } => vec![path],
+            ChangeEvent::CheatsheetRenamed { from, to } => vec![from, to],
+        }
+    }
+}
+
+/// Files Cognitio writes itself in [`cognitio_home_dir`]. Changes to them are ignored, or
+/// writing to the log file about a change would be a change of its own.
+pub const OWN_FILES: &[&str] = &[
+    "cognitio.log",
+    "shorthand_ids.yaml",
+    "placeholder_history.yaml",
+];
+
+/// Whether a path is one of Cognitio's [`OWN_FILES`].
+pub fn is_own_file(path: &Path) -> bool {
+    is_own_file_in(path, &canonical_home())
+}
+
+/// Like [`is_own_file`], with the home directory canonicalized. The watcher reports canonical
+/// paths, so the path's directory is compared canonicalized too, in case the home is a symlink or
+/// a relative path.
+fn is_own_file_in(path: &Path, home: &Path) -> bool {
+    let own_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| OWN_FILES.contains(&name));
+    own_name
+        && path
+            .parent()
+            .is_some_and(|parent| canonical(parent) == home)
+}
+
+/// [`cognitio_home_dir`] canonicalized. It is kept from the first time the directory exists.
+fn canonical_home() -> PathBuf {
+    static HOME: OnceLock<PathBuf> = OnceLock::new();
+    if let Some(home) = HOME.get() {
+        return home.clone();
+    }
+    let home = PathBuf::from(cognitio_home_dir());
+    match std::fs::canonicalize(&home) {
+        Ok(canonical) => HOME.get_or_init(|| canonical).clone(),
+        Err(_) => home,
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Turns file changes into [`ChangeEvent`]s.
+///
+/// Changes to Cognitio's own files and to hidden files and directories, like `.git` or editor
+/// swap files, are dropped, and so are changes to files that aren't markdown. Paths that were
+/// removed can't be told apart, so a removed path that isn't markdown is taken to be a directory.
+/// The config file and each directory are reported at most once, and a file that is gone by the end
+/// of the batch isn't reported as modified.
+pub fn classify_changes(changes: &[FileChange], config_path: &Path) -> Vec<ChangeEvent> {
+    let canonical_config = std::fs::canonicalize(config_path).ok();
+    let is_config = |path: &str| {
+        Path::new(path) == config_path || canonical_config.as_deref() == Some(Path::new(path))
+    };
+    let mut events: Vec<ChangeEvent> = Vec::new();
+    let mut push = |event: ChangeEvent| {
+        if !events.contains(&event) {
+            events.push(event);
+        }
+    };
+
+    for change in changes {
+        if let Some(path) = change.paths().into_iter().find(|path| is_config(path)) {
+            push(ChangeEvent::ConfigChanged {
+                path: path.to_string(),
+            });
+            continue;
+        }
+
+        match change {
+            FileChange::Rename { from, to } => {
+                let (from_kind, to_kind) = (kind_of(from), kind_of(to));
+                match (from_kind, to_kind) {
+                    (PathKind::Cheatsheet, PathKind::Cheatsheet) => {
+                        push(ChangeEvent::CheatsheetRenamed {
+                            from: from.clone(),
+                            to: to.clone(),
+                        })
+                    }
+                    _ => {
+                        if let Some(event) = removed(from, from_kind) {
+                            push(event);
+                        }
+                        if let Some(event) = created(to, to_kind) {
+                            push(event);
+                        }
+                    }
+                }
+            }
+            FileChange::Create { path } => {
+                if let Some(event) = created(path, kind_of(path)) {
+                    push(event);
+                }
+            }
+            FileChange::Remove { path } => {
+                if let Some(event) = removed(path, kind_of(path)) {
+                    push(event);
+                }
+            }
+            FileChange::Modify { path } => {
+                if kind_of(path) == PathKind::Cheatsheet {
+                    push(ChangeEvent::CheatsheetModified { path: path.clone() });
+                }
+            }
+        }
+    }
+
+    // A file that was changed and then renamed or removed in the same batch is gone.
+    let gone: Vec<String> = events
+        .iter()
+        .filter_map(|event| match event {
+            ChangeEvent::CheatsheetRemoved { path } => Some(path.clone()),
+            ChangeEvent::CheatsheetRenamed { from, .. } => Some(from.clone()),
+            _ => None,
+        })
+        .collect();
+    events.retain(
+        |event| !matches!(event, ChangeEvent::CheatsheetModified { path } if gone.contains(path)),
+    );
+    events
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PathKind {
+    Cheatsheet,
+    /// An existing directory, or a path that no longer exists and isn't markdown.
+    Directory,
+    Ignored,
+}
+
+fn kind_of(path: &str) -> PathKind {
+    let path = Path::new(path);
+    let hidden = path.components().any(|component| {
+        component
+            .as_os_str()
+            .to_str()
+            .is_some_and(|name| name.starts_with('.') && name != "." && name != "..")
+    });
+    if hidden || is_own_file(path) {
+        return PathKind::Ignored;
+    }
+
+    if path.extension().is_some_and(|extension| extension == "md") {
+        PathKind::Cheatsheet
+    } else if path.is_dir() || !path.exists() {
+        PathKind::Directory
+    } else {
+        PathKind::Ignored
+    }
+}
+
+fn created(path: &str, kind: PathKind) -> Option<ChangeEvent> {
+    let path = path.to_string();
+    match kind {
+        PathKind::Cheatsheet => Some(ChangeEvent::CheatsheetCreated { path }),
+        PathKind::Directory => Some(ChangeEvent::DirectoryChanged { path }),
+        PathKind::Ignored => None,
+    }
+}
+
+fn removed(path: &str, kind: PathKind) -> Option<ChangeEvent> {
+    let path = path.to_string();
+    match kind {
+        PathKind::Cheatsheet => Some(ChangeEvent::CheatsheetRemoved { path }),
+        PathKind::Directory => Some(ChangeEvent::DirectoryChanged { path }),
+        PathKind::Ignored => None,
+    }
+}
+
+/// A directory to watch.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct WatchRoot {
+    pub path: PathBuf,
+    /// Whether to watch everything below the directory, or only the files directly in it.
+    pub recursive: bool,
+}
+
+impl WatchRoot {
+    pub fn recursive(path: impl Into<PathBuf>) -> WatchRoot {
+        WatchRoot {
+            path: path.into(),
+            recursive: true,
+        }
+    }
+
+    pub fn non_recursive(path: impl Into<PathBuf>) -> WatchRoot {
+        WatchRoot {
+            path: path.into(),
+            recursive: false,
+        }
+    }
+}
+
+/// The directories to watch for a config: every cheatsheet source, and the directory of the config
+/// file itself without its subdirectories.
+pub fn config_watch_roots(conf: &CognitioConfig) -> BTreeSet<WatchRoot> {
+    let mut roots = BTreeSet::from([WatchRoot::non_recursive(config_dir())]);
+    for source in &conf.cheatsheets {
+        match source.expanded_path() {
+            Ok(path) => {
+                roots.insert(WatchRoot::recursive(path));
+            }
+            Err(error) => warn!("Not watching {}: {error}", source.path()),
+        }
+    }
+    roots
+}
+
+/// The debounce window set in the config, or [`DEFAULT_DEBOUNCE`].
+pub fn config_debounce(conf: &CognitioConfig) -> Duration {
+    conf.watch
+        .as_ref()
+        .and_then(|watch| watch.debounce_ms)
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_DEBOUNCE)
+}
+
+/// What a batch of file changes means for everything showing the library, see
+/// [`library_events`].
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LibraryEvent {
+    /// The config file changed and has no errors. Its sources are being watched already.
+    ConfigChanged {
+        path: String,
+        config: CognitioConfig,
+    },
+    /// The config file changed and has errors, so the last config without errors stays in use.
+    ConfigInvalid {
+        path: String,
+        errors: Vec<ConfigError>,
+    },
+    /// Cheatsheets or directories in the sources changed.
+    FilesChanged { events: Vec<ChangeEvent> },
+}
+
+impl LibraryEvent {
+    /// The name of the event for the desktop app's frontend and in `cognitio serve`'s event
+    /// stream.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LibraryEvent::ConfigChanged { .. } => "cognitio_config_changed",
+            LibraryEvent::ConfigInvalid { .. } => "cognitio_config_invalid",
+            LibraryEvent::FilesChanged { .. } => "file_changed",
+        }
+    }
+}
+
+/// Classifies a batch from a [`WatchService`] watching [`config_watch_roots`]. When the config
+/// file changed, it is read again and, if it has no errors, `watcher` is set to watch its
+/// sources with its debounce window. Config events come first, so consumers can reload the
+/// config before anything else.
+pub fn library_events(batch: &[FileChange], watcher: &WatchService) -> Vec<LibraryEvent> {
+    let (config_events, events): (Vec<ChangeEvent>, Vec<ChangeEvent>) =
+        classify_changes(batch, &cognitio_yaml_path())
+            .into_iter()
+            .partition(|event| matches!(event, ChangeEvent::ConfigChanged { .. }));
+
+    let mut library_events = Vec::new();
+    if let Some(ChangeEvent::ConfigChanged { path }) = config_events.into_iter().next() {
+        let check = check_cognitio_yaml();
+        match check.config {
+            Some(config) if check.errors.is_empty() => {
+                for warning in &check.warnings {
+                    warn!("{warning}");
+                }
+                watcher.set_roots(config_watch_roots(&config));
+                watcher.set_debounce(config_debounce(&config));
+                library_events.push(LibraryEvent::ConfigChanged { path, config });
+            }
+            _ => {
+                for error in &check.errors {
+                    error!("Invalid Cognitio config, keeping the last good one: {error}");
+                }
+                library_events.push(LibraryEvent::ConfigInvalid {
+                    path,
+                    errors: check.errors,
+                });
+            }
+        }
+    }
+    if !events.is_empty() {
+        library_events.push(LibraryEvent::FilesChanged { events });
+    }
+    library_events
+}
+
+/// Owns the file system watcher on a thread of its own and forwards file changes.
+///
+/// Changes are collected until none have come in for the debounce window, and then sent as one
+/// batch, see [`ChangeBatch`]. While changes keep coming in, a batch is sent at least every two
+/// seconds, or every debounce window if that is longer.
+///
+/// The set of watched directories can be changed at any time with [`WatchService::set_roots`],
+/// for example after the config was reloaded; only the difference to the current set is watched
+/// or unwatched. The thread stops, and every directory is unwatched, on
+/// [`WatchService::shutdown`] or when the service is dropped.
+pub struct WatchService {
+    messages: Sender<Message>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+enum Message {
+    Notify(notify::Result<Event>),
+    SetRoots(BTreeSet<WatchRoot>),
+    SetDebounce(Duration),
+    Shutdown,
+}
+
+impl WatchService {
+    /// Starts the watcher thread, watching nothing until roots are set. Batches of changes are
+    /// sent to `on_changes` once nothing has changed for `debounce`.
+    pub fn start(
+        on_changes: Sender<Vec<FileChange>>,
+        debounce: Duration,
+    ) -> notify::Result<WatchService> {
+        let (messages, receiver) = channel();
+
+        let notify_messages = messages.clone();
+        let watcher = RecommendedWatcher::new(
+            move |event| {
+                // Fails only once the thread has stopped, when nobody is interested anymore.
+                let _ = notify_messages.send(Message::Notify(event));
+            },
+            Config::default(),
+        )?;
+
+        let thread = thread::Builder::new()
+            .name("cognitio-watcher".to_string())
+            .spawn(move || run_watcher(watcher, receiver, on_changes, debounce))?;
+
+        Ok(WatchService {
+            messages,
+            thread: Mutex::new(Some(thread)),
+        })
+    }
+
+    /// Makes the watched directories exactly `roots`.
+    pub fn set_roots(&self, roots: impl IntoIterator<Item = WatchRoot>) {
+        let roots = roots.into_iter().collect();
+        if self.messages.send(Message::SetRoots(roots)).is_err() {
+            warn!("The file watcher has stopped, not changing what is watched");
+        }
+    }
+
+    /// Changes the debounce window, for example after the config was reloaded.
+    pub fn set_debounce(&self, debounce: Duration) {
+        let _ = self.messages.send(Message::SetDebounce(debounce));
+    }
+
+    /// Stops the watcher thread and waits for it to finish. Does nothing if it already stopped.
+    pub fn shutdown(&self) {
+        let Some(thread) = self.thread.lock().unwrap().take() else {
+            return;
+        };
+        let _ = self.messages.send(Message::Shutdown);
+        if thread.join().is_err() {
+            error!("The file watcher thread panicked");
+        }
+    }
+}
+
+impl Drop for WatchService {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn run_watcher(
+    mut watcher: RecommendedWatcher,
+    messages: Receiver<Message>,
+    on_changes: Sender<Vec<FileChange>>,
+    mut debounce: Duration,
+) {
+    let mut watched: BTreeSet<WatchRoot> = BTreeSet::new();
+    let mut batch = ChangeBatch::default();
+    // When the first and the last change of the batch came in.
+    let mut pending: Option<(Instant, Instant)> = None;
+
+    loop {
+        let message = match pending {
+            Some((first, last)) => {
+                let flush_at = flush_time(first, last, debounce);
+                let now = Instant::now();
+                if now >= flush_at {
+                    pending = None;
+                    let changes = batch.take();
+                    if !changes.is_empty() && on_changes.send(changes).is_err() {
+                        debug!(
+                            "Nobody is listening for file changes anymore, stopping the watcher"
+                        );
+                        break;
+                    }
+                    continue;
+                }
+                match messages.recv_timeout(flush_at - now) {
+                    Ok(message) => message,
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            None => match messages.recv() {
+                Ok(message) => message,
+                Err(_) => break,
+            },
+        };
+
+        match message {
+            // Cognitio's own files are dropped before anything is logged about them, since
+            // logging writes to one of them.
+            Message::Notify(Ok(event)) if event.paths.iter().all(|path| is_own_file(path)) => {}
+            Message::Notify(Ok(event)) => {
+                debug!("File event: {:?}", event);
+                batch.add(&event);
+                let now = Instant::now();
+                pending = Some((pending.map_or(now, |(first, _)| first), now));
+            }
+            Message::Notify(Err(error)) => error!("File watcher error: {error:?}"),
+            Message::SetRoots(roots) => update_roots(&mut watcher, &mut watched, roots),
+            Message::SetDebounce(window) => debounce = window,
+            Message::Shutdown => break,
+        }
+    }
+
+    for root in &watched {
+        let _ = watcher.unwatch(&root.path);
+    }
+    info!("Stopped watching {} directories", watched.len());
+}
+
+/// When to send a batch: once nothing has changed for `debounce`, but no later than
+/// [`MAX_BATCH_DELAY`] after the first change, or `debounce` if that is longer.
+fn flush_time(first: Instant, last: Instant, debounce: Duration) -> Instant {
+    (last + debounce).min(first + debounce.max(MAX_BATCH_DELAY))
+}
+
+/// Unwatches the roots that are no longer wanted and watches the new ones.
+fn update_roots(
+    watcher: &mut RecommendedWatcher,
+    watched: &mut BTreeSet<WatchRoot>,
+    roots: BTreeSet<WatchRoot>,
+) {
+    for root in watched.difference(&roots) {
+        info!("Unwatching {}", root.path.display());
+        if let Err(error) = watcher.unwatch(&root.path) {
+            warn!("Failed to unwatch {}: {error:?}", root.path.display());
+        }
+    }
+    watched.retain(|root| roots.contains(root));
+
+    for root in roots {
+        if watched.contains(&root) {
+            continue;
+        }
+        let mode = if root.recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        // A root that can't be watched, for example because it doesn't exist yet, is tried again
+        // the next time the roots are set.
+        match watcher.watch(&root.path, mode) {
+            Ok(()) => {
+                info!("Watching {}", root.path.display());
+                watched.insert(root);
+            }
+            Err(error) => warn!("Failed to watch {}: {error:?}", root.path.display()),
+        }
+    }
+}
+
+/// Changes collected since the last flush, coalesced per path.
+///
+/// A file that is created and then modified is reported as created, one that is created and
+/// removed again isn't reported at all, and one that is removed and created again, like editors
+/// do when saving, is reported as modified. Renames are reported as [`FileChange::Rename`] when
+/// the platform tells which paths belong together, except for a temporary file, hidden or not
+/// markdown, renamed over a cheatsheet. That is how editors save atomically, so it is reported as
+/// a change to the cheatsheet.
+#[derive(Debug, Default)]
+pub struct ChangeBatch {
+    changes: Vec<FileChange>,
+    /// Paths renamed away from, waiting for the path they were renamed to.
+    rename_from: Vec<PathBuf>,
+    /// Paths renamed to that no path renamed away from has claimed.
+    rename_to: Vec<PathBuf>,
+}
+
+impl ChangeBatch {
+    pub fn add(&mut self, event: &Event) {
+        match event.kind {
+            EventKind::Create(_) => self.add_paths(event, |path| FileChange::Create { path }),
+            EventKind::Remove(_) => self.add_paths(event, |path| FileChange::Remove { path }),
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+                let (from, to) = (&event.paths[0], &event.paths[1]);
+                // Some platforms also report the two halves of the rename on their own.
+                self.rename_from.retain(|path| path != from);
+                self.rename_to.retain(|path| path != to);
+                self.add_rename(path_string(from), path_string(to));
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                self.rename_from.extend(event.paths.iter().cloned());
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                self.rename_to.extend(event.paths.iter().cloned());
+            }
+            EventKind::Modify(ModifyKind::Name(_)) | EventKind::Any | EventKind::Other => {
+                // Without knowing which side of a rename a path is on, whether it still exists
+                // is all there is to go on.
+                for path in &event.paths {
+                    let path_str = path_string(path);
+                    if path.exists() {
+                        self.add_change(FileChange::Create { path: path_str });
+                    } else {
+                        self.add_change(FileChange::Remove { path: path_str });
+                    }
+                }
+            }
+            EventKind::Modify(_) => self.add_paths(event, |path| FileChange::Modify { path }),
+            EventKind::Access(_) => {}
+        }
+    }
+
+    /// Returns the collected changes and starts over. Halves of renames that never got their
+    /// other half are reported as removed or created.
+    pub fn take(&mut self) -> Vec<FileChange> {
+        for from in std::mem::take(&mut self.rename_from) {
+            self.add_change(FileChange::Remove {
+                path: path_string(&from),
+            });
+        }
+        for to in std::mem::take(&mut self.rename_to) {
+            self.add_change(FileChange::Create {
+                path: path_string(&to),
+            });
+        }
+        std::mem::take(&mut self.changes)
+    }
+
+    fn add_paths(&mut self, event: &Event, change: impl Fn(String) -> FileChange) {
+        for path in &event.paths {
+            self.add_change(change(path_string(path)));
+        }
+    }
+
+    fn add_rename(&mut self, from: String, to: String) {
+        if !is_cheatsheet_name(&from) && is_cheatsheet_name(&to) {
+            self.changes
+                .retain(|change| change.paths() != [from.as_str()]);
+            self.add_change(FileChange::Modify { path: to });
+            return;
+        }
+
+        // A file created and then renamed in the same batch was simply created.
+        let created = self
+            .changes
+            .iter()
+            .position(|change| matches!(change, FileChange::Create { path } if *path == from));
+        match created {
+            Some(index) => {
+                self.changes.remove(index);
+                self.add_change(FileChange::Create { path: to });
+            }
+            None => self.changes.push(FileChange::Rename { from, to }),
+        }
+    }
+
+    fn add_change(&mut self, change: FileChange) {
+        let path = match &change {
+            FileChange::Create { path }
+            | FileChange::Modify { path }
+            | FileChange::Remove { path } => path.clone(),
+            FileChange::Rename { .. } => {
+                self.changes.push(change);
+                return;
+            }
+        };
+
+        let previous = self.changes.iter().position(|existing| match existing {
+            FileChange::Create { path: p }
+            | FileChange::Modify { path: p }
+            | FileChange::Remove { path: p } => *p == path,
+            FileChange::Rename { .. } => false,
+        });
+        let Some(index) = previous else {
+            self.changes.push(change);
+            return;
+        };
+
+        let merged = match (&self.changes[index], change) {
+            (FileChange::Create { .. }, FileChange::Remove { .. }) => None,
+            (FileChange::Create { .. }, _) => Some(FileChange::Create { path }),
+            (_, FileChange::Remove { .. }) => Some(FileChange::Remove { path }),
+            (_, _) => Some(FileChange::Modify { path }),
+        };
+        match merged {
+            Some(merged) => self.changes[index] = merged,
+            None => {
+                self.changes.remove(index);
+            }
+        }
+    }
+}
+
+/// Whether the file name of `path` is one a cheatsheet can have: markdown and not hidden.
+fn is_cheatsheet_name(path: &str) -> bool {
+    Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| !name.starts_with('.') && name.ends_with(".md"))
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, DataChange, RemoveKind};
+    use std::fs;
+    use std::process;
+
+    fn event(kind: EventKind, paths: &[&str]) -> Event {
+        paths
+            .iter()
+            .fold(Event::new(kind), |event, path| event.add_path(path.into()))
+    }
+
+    fn create(path: &str) -> Event {
+        event(EventKind::Create(CreateKind::File), &[path])
+    }
+
+    fn modify(path: &str) -> Event {
+        event(
+            EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+            &[path],
+        )
+    }
+
+    fn remove(path: &str) -> Event {
+        event(EventKind::Remove(RemoveKind::File), &[path])
+    }
+
+    fn rename(mode: RenameMode, paths: &[&str]) -> Event {
+        event(EventKind::Modify(ModifyKind::Name(mode)), paths)
+    }
+
+    fn batch(events: &[Event]) -> Vec<FileChange> {
+        let mut batch = ChangeBatch::default();
+        for event in events {
+            batch.add(event);
+        }
+        batch.take()
+    }
+
+    /// A fresh directory with a `docker` subdirectory.
+    fn setup(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cognitio-watch-{}-{name}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("docker")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn pairs_the_halves_of_renames() {
+        let both = || rename(RenameMode::Both, &["/lib/a.md", "/lib/b.md"]);
+        let renamed = FileChange::Rename {
+            from: "/lib/a.md".to_string(),
+            to: "/lib/b.md".to_string(),
+        };
+        assert_eq!(batch(&[both()]), vec![renamed.clone()]);
+
+        // inotify reports each half on its own before the pair.
+        let from = rename(RenameMode::From, &["/lib/a.md"]);
+        let to = rename(RenameMode::To, &["/lib/b.md"]);
+        assert_eq!(batch(&[from.clone(), to.clone(), both()]), [renamed]);
+
+        // Halves that were never paired, like a file moved in from outside a watched directory.
+        assert_eq!(
+            batch(&[from, to]),
+            [
+                FileChange::Remove {
+                    path: "/lib/a.md".to_string()
+                },
+                FileChange::Create {
+                    path: "/lib/b.md".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn coalesces_changes_to_the_same_path() {
+        let path = "/lib/a.md";
+        assert!(batch(&[create(path), modify(path), remove(path)]).is_empty());
+        assert_eq!(
+            batch(&[create(path), modify(path)]),
+            [FileChange::Create {
+                path: path.to_string()
+            }]
+        );
+        assert_eq!(
+            batch(&[remove(path), create(path)]),
+            [FileChange::Modify {
+                path: path.to_string()
+            }]
+        );
+        assert_eq!(
+            batch(&[modify(path), modify(path), remove(path)]),
+            [FileChange::Remove {
+                path: path.to_string()
+            }]
+        );
+        assert_eq!(
+            batch(&[create(path), rename(RenameMode::Both, &[path, "/lib/b.md"])]),
+            [FileChange::Create {
+                path: "/lib/b.md".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_atomic_saves_as_modified() {
+        let path = "/lib/docker/run.md";
+        let modified = vec![FileChange::Modify {
+            path: path.to_string(),
+        }];
+        for temp in ["/lib/docker/.run.md.swp", "/lib/docker/run.md.tmp"] {
+            let saved = rename(RenameMode::Both, &[temp, path]);
+            assert_eq!(batch(std::slice::from_ref(&saved)), modified);
+            assert_eq!(
+                batch(&[create(temp), modify(temp), saved.clone()]),
+                modified
+            );
+            // A cheatsheet that was new in the batch stays new.
+            assert_eq!(
+                batch(&[create(path), create(temp), saved]),
+                [FileChange::Create {
+                    path: path.to_string()
+                }]
+            );
+        }
+
+        let dir = setup("atomic");
+        fs::write(
+            dir.join("docker/run.md"),
+            "### Run
+",
+        )
+        .unwrap();
+        let path = dir.join("docker/run.md").to_string_lossy().to_string();
+        let temp = dir.join("docker/.run.md.swp").to_string_lossy().to_string();
+        let changes = batch(&[create(&temp), rename(RenameMode::Both, &[&temp, &path])]);
+        assert_eq!(
+            classify_changes(&changes, &dir.join("cognitio.yaml")),
+            [ChangeEvent::CheatsheetModified { path }]
+        );
+    }
+
+    #[test]
+    fn classifies_changes() {
+        let dir = setup("classify");
+        let path = |name: &str| dir.join(name).to_string_lossy().to_string();
+        fs::write(dir.join("docker/run.md"), "### Run\n").unwrap();
+        fs::write(dir.join("docker/notes.txt"), "").unwrap();
+        let changes = [
+            FileChange::Modify {
+                path: path("docker/run.md"),
+            },
+            FileChange::Modify {
+                path: path("docker/run.md"),
+            },
+            FileChange::Create {
+                path: path("docker/notes.txt"),
+            },
+            FileChange::Create {
+                path: path(".git/notes.md"),
+            },
+            FileChange::Create {
+                path: path("docker/.run.md.swp"),
+            },
+            FileChange::Create {
+                path: path("docker"),
+            },
+            FileChange::Modify {
+                path: path("gone.md"),
+            },
+            FileChange::Rename {
+                from: path("gone.md"),
+                to: path("docker/gone.md"),
+            },
+            FileChange::Remove { path: path("old") },
+        ];
+        assert_eq!(
+            classify_changes(&changes, &dir.join("cognitio.yaml")),
+            [
+                ChangeEvent::CheatsheetModified {
+                    path: path("docker/run.md")
+                },
+                ChangeEvent::DirectoryChanged {
+                    path: path("docker")
+                },
+                ChangeEvent::CheatsheetRenamed {
+                    from: path("gone.md"),
+                    to: path("docker/gone.md")
+                },
+                ChangeEvent::DirectoryChanged { path: path("old") },
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_cognitios_own_files() {
+        let home = PathBuf::from(cognitio_home_dir());
+        assert!(is_own_file(&home.join("cognitio.log")));
+        assert!(is_own_file(&home.join("shorthand_ids.yaml")));
+        assert!(!is_own_file(&home.join("cognitio.yaml")));
+        assert!(!is_own_file(&home.join("logs/cognitio.log")));
+
+        let changes = [FileChange::Modify {
+            path: home.join("cognitio.log").to_string_lossy().to_string(),
+        }];
+        assert!(classify_changes(&changes, &home.join("cognitio.yaml")).is_empty());
+    }
+
+    #[test]
+    fn ignores_own_files_in_a_linked_home() {
+        let dir = setup("linked-home");
+        fs::create_dir_all(dir.join("real-home")).unwrap();
+        std::os::unix::fs::symlink(dir.join("real-home"), dir.join("home")).unwrap();
+        let home = fs::canonicalize(dir.join("home")).unwrap();
+
+        // Reported by the watcher with the link resolved, or with the link as configured.
+        assert!(is_own_file_in(&home.join("cognitio.log"), &home));
+        assert!(is_own_file_in(&dir.join("home/cognitio.log"), &home));
+        assert!(is_own_file_in(
+            &dir.join("home/../real-home/placeholder_history.yaml"),
+            &home
+        ));
+        assert!(!is_own_file_in(&dir.join("cognitio.log"), &home));
+        assert!(!is_own_file_in(&dir.join("home/notes.md"), &home));
+    }
+
+    #[test]
+    fn detects_config_changes() {
+        let dir = setup("config");
+        fs::write(dir.join("real.yaml"), "cheatsheets: []\n").unwrap();
+        let config = dir.join("cognitio.yaml");
+        std::os::unix::fs::symlink(dir.join("real.yaml"), &config).unwrap();
+        let config_path = config.to_string_lossy().to_string();
+        let real_path = fs::canonicalize(dir.join("real.yaml"))
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+
+        let changes = [
+            FileChange::Modify {
+                path: config_path.clone(),
+            },
+            // Saved atomically.
+            FileChange::Rename {
+                from: dir.join(".cognitio.yaml.tmp").to_string_lossy().to_string(),
+                to: config_path.clone(),
+            },
+        ];
+        assert_eq!(
+            classify_changes(&changes, &config),
+            [ChangeEvent::ConfigChanged { path: config_path }]
+        );
+
+        // Changes to the file the link points to count too.
+        let changes = [FileChange::Modify {
+            path: real_path.clone(),
+        }];
+        assert_eq!(
+            classify_changes(&changes, &config),
+            [ChangeEvent::ConfigChanged { path: real_path }]
+        );
+    }
+
+    #[test]
+    fn flushes_while_changes_keep_coming() {
+        let start = Instant::now();
+        let debounce = Duration::from_millis(200);
+        let quiet = start + Duration::from_millis(500);
+        assert_eq!(flush_time(start, quiet, debounce), quiet + debounce);
+        let busy = start + Duration::from_secs(10);
+        assert_eq!(flush_time(start, busy, debounce), start + MAX_BATCH_DELAY);
+
+        let long = Duration::from_secs(5);
+        assert_eq!(flush_time(start, busy, long), start + long);
+    }
+
+    /// Writes `file` until a batch with a change to it comes in, since roots are set on the
+    /// watcher thread a little later. Returns the changes received up to that batch.
+    fn write_until_seen(changes: &Receiver<Vec<FileChange>>, file: &Path) -> Vec<FileChange> {
+        let mut received = Vec::new();
+        for attempt in 0..50 {
+            fs::write(file, format!("### Attempt {attempt}\n")).unwrap();
+            while let Ok(batch) = changes.recv_timeout(Duration::from_millis(100)) {
+                let seen = batch
+                    .iter()
+                    .any(|change| change.paths().contains(&file.to_str().unwrap()));
+                received.extend(batch);
+                if seen {
+                    return received;
+                }
+            }
+        }
+        panic!("no change to {} came in", file.display());
+    }
+
+    #[test]
+    fn watches_the_roots_it_is_given() {
+        let dir = fs::canonicalize(setup("service")).unwrap();
+        let (old, new) = (dir.join("old"), dir.join("new"));
+        fs::create_dir_all(&old).unwrap();
+        fs::create_dir_all(new.join("sub")).unwrap();
+        let (sender, changes) = channel();
+        let service = WatchService::start(sender, Duration::from_millis(50)).unwrap();
+
+        service.set_roots([WatchRoot::recursive(&old)]);
+        write_until_seen(&changes, &old.join("first.md"));
+
+        service.set_roots([WatchRoot::recursive(&new)]);
+        write_until_seen(&changes, &new.join("ready.md"));
+        fs::write(old.join("ignored.md"), "### Ignored\n").unwrap();
+        let received = write_until_seen(&changes, &new.join("sub/seen.md"));
+        assert!(received
+            .iter()
+            .flat_map(|change| change.paths())
+            .all(|path| Path::new(path).starts_with(&new)));
+
+        // Changes close together come in one batch.
+        while changes.recv_timeout(Duration::from_millis(200)).is_ok() {}
+        for name in ["a.md", "b.md", "c.md"] {
+            fs::write(new.join(name), "### Batched\n").unwrap();
+        }
+        let batch = changes.recv_timeout(Duration::from_secs(5)).unwrap();
+        for name in ["a.md", "b.md", "c.md"] {
+            let path = new.join(name).to_string_lossy().to_string();
+            assert!(batch
+                .iter()
+                .any(|change| change.paths().contains(&path.as_str())));
+        }
+
+        // Shutting down stops the thread, which drops the sender, and doing it again is harmless.
+        service.shutdown();
+        service.shutdown();
+        fs::write(new.join("after.md"), "### After\n").unwrap();
+        loop {
+            match changes.recv_timeout(Duration::from_secs(5)) {
+                Ok(_) => continue,
+                Err(error) => {
+                    assert_eq!(error, RecvTimeoutError::Disconnected);
+                    break;
+                }
+            }
+        }
+        drop(service);
+    }
+
+    #[test]
+    fn stops_when_dropped() {
+        let (sender, changes) = channel();
+        let service = WatchService::start(sender, Duration::from_millis(50)).unwrap();
+        service.set_roots([WatchRoot::recursive(setup("drop"))]);
+        drop(service);
+        assert_eq!(
+            changes.recv_timeout(Duration::from_secs(5)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    }
+}
@@ -0,0 +1,76 @@
+//! Tests of finding the config file.
+
+use cognitio_core::config::{
+    cognitio_home_dir, config_dir, locate_config, set_config_flag, ConfigSource,
+};
+use std::env;
+use std::path::PathBuf;
+
+fn located() -> (PathBuf, ConfigSource) {
+    let location = locate_config();
+    (location.path, location.source)
+}
+
+// One test, since the chain depends on environment variables, which are set for the whole
+// process, and the `--config` flag can only be set once.
+#[test]
+fn locates_the_config_file() {
+    for name in ["COGNITIO_CONFIG", "COGNITIO_HOME", "XDG_CONFIG_HOME"] {
+        env::remove_var(name);
+    }
+    env::set_var("HOME", "/home/user");
+    assert_eq!(
+        located(),
+        (
+            PathBuf::from("/home/user/.config/cognitio/cognitio.yaml"),
+            ConfigSource::Default
+        )
+    );
+    assert_eq!(cognitio_home_dir(), "/home/user/.config/cognitio");
+
+    env::set_var("XDG_CONFIG_HOME", "/xdg");
+    assert_eq!(
+        located(),
+        (
+            PathBuf::from("/xdg/cognitio/cognitio.yaml"),
+            ConfigSource::XdgConfigHome
+        )
+    );
+    assert_eq!(cognitio_home_dir(), "/xdg/cognitio");
+
+    env::set_var("COGNITIO_HOME", "/cognitio");
+    assert_eq!(
+        located(),
+        (
+            PathBuf::from("/cognitio/cognitio.yaml"),
+            ConfigSource::HomeEnv
+        )
+    );
+    assert_eq!(cognitio_home_dir(), "/cognitio");
+
+    env::set_var("COGNITIO_CONFIG", "/etc/cognitio/team.yaml");
+    assert_eq!(
+        located(),
+        (
+            PathBuf::from("/etc/cognitio/team.yaml"),
+            ConfigSource::ConfigEnv
+        )
+    );
+    // Relative sources are relative to the config file, and the other files stay in the home.
+    assert_eq!(config_dir(), PathBuf::from("/etc/cognitio"));
+    assert_eq!(cognitio_home_dir(), "/cognitio");
+
+    // Empty variables count as not set.
+    env::set_var("COGNITIO_CONFIG", "");
+    env::set_var("COGNITIO_HOME", "");
+    assert_eq!(located().1, ConfigSource::XdgConfigHome);
+
+    set_config_flag(PathBuf::from("/flag/cognitio.yaml"));
+    set_config_flag(PathBuf::from("/ignored/cognitio.yaml"));
+    env::set_var("COGNITIO_CONFIG", "/etc/cognitio/team.yaml");
+    assert_eq!(
+        located(),
+        (PathBuf::from("/flag/cognitio.yaml"), ConfigSource::Flag)
+    );
+    assert_eq!(config_dir(), PathBuf::from("/flag"));
+}
@@ -0,0 +1,133 @@
+//! Golden-file tests for the HTML renderer.
+//!
+//! Each `fixtures/html/<name>.md` is rendered and compared with `<name>.html`. The tests need the
+//! `html` feature: `cargo test --features html`. Run them with `UPDATE_GOLDEN=1` to write the
+//! expected files after a deliberate change to the output, and review the diff.
+
+#![cfg(feature = "html")]
+
+use cognitio_core::html::HtmlRenderer;
+use cognitio_core::snippet::parse_cheatsheet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/html")
+}
+
+/// Renders a fixture. The path given to the parser is the file name, since element ids are made
+/// from it and have to be the same on every machine.
+fn render(name: &str) -> String {
+    let markdown = fs::read_to_string(fixtures_dir().join(format!("{name}.md"))).unwrap();
+    let cheatsheet = parse_cheatsheet(&format!("{name}.md"), &markdown);
+    HtmlRenderer::default().render_cheatsheet(&cheatsheet)
+}
+
+fn assert_golden(name: &str) -> String {
+    let html = render(name);
+    let expected_path = fixtures_dir().join(format!("{name}.html"));
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&expected_path, &html).unwrap();
+    }
+    let expected = fs::read_to_string(&expected_path).unwrap_or_default();
+    assert!(
+        html == expected,
+        "{name}.md rendered differently than {}:\n{html}",
+        expected_path.display()
+    );
+    html
+}
+
+/// The text of the document, with tags removed and the escapes browsers undo undone.
+fn text_of(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[test]
+fn heredocs() {
+    let html = assert_golden("heredocs");
+    let text = text_of(&html);
+    assert!(text.contains(r#"IFS=';' read -a parts <<< "${userAndRole}""#));
+    assert!(text.contains("cat <<'EOF' > /tmp/out.txt"));
+    assert!(text.contains("a <<< b && c > d"));
+    assert!(!html.contains("&amp;lt;"));
+}
+
+#[test]
+fn generics() {
+    let html = assert_golden("generics");
+    let text = text_of(&html);
+    assert!(text.contains("let map: HashMap<&str, Vec<u8>> = HashMap::new();"));
+    assert!(text.contains("new Map<string, Array<Promise<void>>>();"));
+    assert!(text.contains("List<Map<String, Integer>> rows = new ArrayList<>();"));
+    assert!(text.contains("HashMap<&str, Vec<u8>>"));
+}
+
+#[test]
+fn html_in_prose() {
+    let html = assert_golden("html-in-prose");
+    assert!(!html.contains("<script"));
+    assert!(!html.contains("<b>"));
+    assert!(!html.contains("<img src=x"));
+    assert!(!html.to_lowercase().contains("script:"));
+    assert_eq!(html.matches("<a href=\"#\"").count(), 6);
+    assert!(html.contains("<a href=\"mailto:me@example.com\""));
+    assert!(html.contains("<a href=\"docker.md#run\""));
+    assert!(html.contains("<a href=\"#links\""));
+    let text = text_of(&html);
+    assert!(text.contains(r#"<div class="mk-section"><script>alert('x')</script></div>"#));
+    assert!(text.contains("Inline <b>bold</b> and <img src=x onerror=alert(1)> stay text."));
+    assert!(html.contains(r#"alt="An &lt;image&gt;""#));
+    assert!(text.contains("5 < 6 && \"quotes\" & 'apostrophes' are kept as typed."));
+    assert_eq!(html.matches("class=\"mk-section\"").count(), 3);
+}
+
+#[test]
+fn nested_fences() {
+    let html = assert_golden("nested-fences");
+    let text = text_of(&html);
+    assert!(text.contains("```bash\necho \"inner <fence>\"\n```"));
+    assert!(text.contains("print(\"```\")"));
+    assert!(text.contains("make install > /dev/null"));
+    assert!(text.contains("echo \"<quoted>\""));
+    assert_eq!(html.matches("class=\"mk-section\"").count(), 3);
+}
+
+#[test]
+fn details_and_tables() {
+    let html = assert_golden("details-and-tables");
+    assert!(html.contains("<kbd>Ctrl</kbd>+<kbd>C</kbd>, then <kbd>Ctrl</kbd>"));
+    assert!(html.contains("<br>"));
+    assert!(html.contains("H<sub>2</sub>O and x<sup>2</sup>"));
+    assert!(html.contains("<details>\n<summary>More keys</summary>"));
+    assert!(html.contains("<details open>"));
+    assert!(html.contains("<th class=\"mk-table-cell\" style=\"text-align: left;\">Key</th>"));
+    assert!(html
+        .contains("<td class=\"mk-table-cell\" style=\"text-align: left;\"><kbd>Esc</kbd></td>"));
+    assert!(!html.contains("<script"));
+    assert!(!html.contains("onclick=\""));
+    assert!(!html.contains("<kbd class"));
+    let text = text_of(&html);
+    assert!(
+        text.contains(r#"<kbd class="x">styled</kbd> and <sup onclick=alert(1)>2</sup> don't."#)
+    );
+    assert!(text.contains(r#"<details onclick="alert(1)">Not kept</details>"#));
+    // Tags left open are closed within their card.
+    assert_eq!(html.matches("<details").count(), 3);
+    assert_eq!(html.matches("</details>").count(), 3);
+}
@@ -0,0 +1,125 @@
+//! Tests of importing navi `.cheat` files.
+
+use cognitio_core::library::list_subdirectories;
+use cognitio_core::navi::{convert_cheat, import_navi};
+use cognitio_core::placeholder::find_placeholders;
+use cognitio_core::snippet::{parse_cheatsheet, Block};
+use std::fs;
+use std::process;
+
+const CHEAT: &str = r#"% docker, container
+
+# List running containers
+docker ps
+
+# Remove a container
+; careful
+docker rm -f <container_id>
+
+$ container_id: docker ps --format '{{.ID}}' --- --column 1
+
+# Open a shell
+docker exec -it <container_id> <shell>
+
+$ shell: echo -e "bash\nsh"
+
+cat <<EOF > <file>
+EOF
+
+% git
+
+@ common
+
+git checkout <branch>
+"#;
+
+#[test]
+fn converts_commands_to_sections() {
+    let markdown = convert_cheat(CHEAT);
+    let cheatsheet = parse_cheatsheet("docker.md", &markdown);
+
+    let groups: Vec<_> = cheatsheet
+        .groups
+        .iter()
+        .filter_map(|group| group.title.as_deref())
+        .collect();
+    assert_eq!(groups, ["docker, container", "git"]);
+    let titles: Vec<_> = cheatsheet
+        .sections()
+        .map(|section| section.title.as_str())
+        .collect();
+    assert_eq!(
+        titles,
+        [
+            "List running containers",
+            "Remove a container",
+            "Open a shell",
+            "cat <<EOF > <file>",
+            "git checkout <branch>",
+        ]
+    );
+
+    let shell = cheatsheet.find_section("Open a shell").unwrap();
+    let code = &shell.code_blocks().next().unwrap().code;
+    assert_eq!(code, "docker exec -it {{container_id}} {{shell:bash|sh}}\n");
+    let shell_placeholder = &find_placeholders(code)[1];
+    assert_eq!(shell_placeholder.choices, ["bash", "sh"]);
+
+    // Generators Cognitio can't run are kept next to the code, and comments are dropped.
+    let remove = cheatsheet.find_section("Remove a container").unwrap();
+    let Block::Prose(prose) = &remove.blocks[1] else {
+        panic!("no list of generators");
+    };
+    assert!(prose
+        .markdown
+        .contains("`container_id`: `docker ps --format '{{.ID}}' --- --column 1`"));
+    assert!(!markdown.contains("careful"));
+
+    let heredoc = cheatsheet.find_section("cat <<EOF > <file>").unwrap();
+    assert_eq!(
+        heredoc.code_blocks().next().unwrap().code,
+        "cat <<EOF > {{file}}\nEOF\n"
+    );
+    assert!(markdown.contains("## git\n\nUses the variables of `common`."));
+}
+
+#[test]
+fn imports_into_directories() {
+    let dir = std::env::temp_dir().join(format!("cognitio-navi-{}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("cheats/tools")).unwrap();
+    fs::create_dir_all(dir.join("source")).unwrap();
+    fs::write(dir.join("cheats/docker.cheat"), CHEAT).unwrap();
+    fs::write(dir.join("cheats/tools/jq.cheat"), "# Pretty print\njq .\n").unwrap();
+    fs::write(dir.join("cheats/empty.cheat"), "% nothing\n").unwrap();
+
+    let source = dir.join("source");
+    let summary = import_navi(&dir.join("cheats"), &source).unwrap();
+    assert_eq!(summary.cheatsheets, 2);
+    assert_eq!(summary.sections, 6);
+    assert_eq!(summary.skipped.len(), 1);
+
+    let mut files: Vec<_> = list_subdirectories(&source.to_string_lossy(), 10)
+        .iter()
+        .flat_map(|directory| {
+            directory
+                .files
+                .iter()
+                .map(|file| format!("{}/{}", directory.name, file.name))
+        })
+        .collect();
+    files.sort();
+    assert_eq!(files, ["docker/docker.md", "tools/jq.md"]);
+
+    // Imported cheatsheets are never overwritten.
+    fs::write(source.join("tools/jq.md"), "### Mine\n").unwrap();
+    let summary = import_navi(&dir.join("cheats/tools/jq.cheat"), &source).unwrap();
+    assert_eq!(summary.cheatsheets, 1);
+    assert!(source.join("jq/jq.md").exists());
+    let summary = import_navi(&dir.join("cheats"), &source).unwrap();
+    assert_eq!(summary.cheatsheets, 0);
+    assert_eq!(
+        fs::read_to_string(source.join("tools/jq.md")).unwrap(),
+        "### Mine\n"
+    );
+}
@@ -0,0 +1,463 @@
+//! Tests of `cognitio serve` against a server on localhost. They need the `serve` feature:
+//! `cargo test --features serve`.
+
+#![cfg(feature = "serve")]
+
+use cognitio_core::serve::{ServeOptions, Server};
+use cognitio_core::{CheatsheetData, CognitioConfig};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const CHEATSHEET: &str = "### List containers
+
+```bash
+docker ps -a
+```
+
+### Follow logs
+
+Shows logs <as they come>.
+
+```bash
+docker logs -f {{container}}
+```
+";
+
+/// Starts a server for a fresh source with `docker/containers.md`, and returns the source
+/// directory and the server's address.
+fn start(name: &str, options: ServeOptions) -> (PathBuf, SocketAddr) {
+    let dir = std::env::temp_dir().join(format!("cognitio-serve-{}-{name}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("source/docker")).unwrap();
+    fs::write(dir.join("source/docker/containers.md"), CHEATSHEET).unwrap();
+    fs::write(dir.join("secret.md"), "### Secret\n").unwrap();
+    let source = fs::canonicalize(dir.join("source")).unwrap();
+
+    let conf = CognitioConfig {
+        cheatsheets: vec![CheatsheetData::Simple(source.to_string_lossy().to_string())],
+        ..CognitioConfig::default()
+    };
+    let server = Server::bind(conf, "127.0.0.1:0", options).unwrap();
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+    (source, addr)
+}
+
+struct Reply {
+    status: u16,
+    head: String,
+    body: String,
+}
+
+/// Sends a request with the given extra header lines and body, and reads the whole reply.
+fn send(addr: SocketAddr, method: &str, target: &str, headers: &[&str], body: &str) -> Reply {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let mut request = format!("{method} {target} HTTP/1.1\r\nHost: localhost\r\n");
+    for header in headers {
+        request.push_str(header);
+        request.push_str("\r\n");
+    }
+    request.push_str(&format!("Content-Length: {}\r\n\r\n{body}", body.len()));
+    stream.write_all(request.as_bytes()).unwrap();
+
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).unwrap();
+    let (head, body) = reply.split_once("\r\n\r\n").unwrap();
+    Reply {
+        status: head[9..12].parse().unwrap(),
+        head: head.to_string(),
+        body: body.to_string(),
+    }
+}
+
+fn get(addr: SocketAddr, target: &str) -> Reply {
+    send(addr, "GET", target, &[], "")
+}
+
+fn json(reply: &Reply) -> serde_json::Value {
+    serde_json::from_str(&reply.body).unwrap()
+}
+
+/// A query parameter with a path, encoded the way browsers would.
+fn path_param(path: &std::path::Path) -> String {
+    path.to_string_lossy().replace(' ', "%20")
+}
+
+#[test]
+fn lists_the_sources_as_a_tree() {
+    let (source, addr) = start("tree", ServeOptions::default());
+    let reply = get(addr, "/api/tree");
+    assert_eq!(reply.status, 200);
+    assert!(reply.head.contains("Content-Type: application/json"));
+
+    let tree = json(&reply);
+    assert_eq!(tree[0]["path"], source.to_string_lossy().as_ref());
+    assert_eq!(tree[0]["sub_directories"][0]["name"], "docker");
+    assert_eq!(
+        tree[0]["sub_directories"][0]["files"][0]["name"],
+        "containers.md"
+    );
+}
+
+#[test]
+fn serves_the_content_and_sections_of_a_cheatsheet() {
+    let (source, addr) = start("content", ServeOptions::default());
+    let file = path_param(&source.join("docker/containers.md"));
+
+    let content = json(&get(addr, &format!("/api/cheatsheet?path={file}")));
+    assert_eq!(content["markdown"], CHEATSHEET);
+
+    let cheatsheet = json(&get(addr, &format!("/api/sections?path={file}")));
+    let sections = &cheatsheet["groups"][0]["sections"];
+    assert_eq!(sections[0]["title"], "List containers");
+    assert_eq!(sections[1]["title"], "Follow logs");
+    assert_eq!(sections[1]["blocks"][1]["language"], "bash");
+}
+
+#[test]
+fn searches_sections() {
+    let (_source, addr) = start("search", ServeOptions::default());
+    let hits = json(&get(addr, "/api/search?q=follow+logs&limit=5"));
+    assert_eq!(hits[0]["section"], "Follow logs");
+
+    let reply = get(addr, "/api/search?q=logs&limit=many");
+    assert_eq!(reply.status, 400);
+}
+
+#[test]
+fn renders_pages_for_browsers() {
+    let (source, addr) = start("pages", ServeOptions::default());
+    let file = path_param(&source.join("docker/containers.md"));
+
+    let index = get(addr, "/");
+    assert_eq!(index.status, 200);
+    assert!(index.body.contains(&format!("href=\"view?path={file}\"")));
+
+    let page = get(addr, &format!("/view?path={file}"));
+    assert!(page.head.contains("Content-Type: text/html"));
+    assert_eq!(page.body.matches("class=\"mk-section\"").count(), 2);
+    assert!(page.body.contains("Shows logs &lt;as they come&gt;."));
+    assert!(page.body.contains("class=\"active\""));
+
+    let entries = json(&get(addr, "/search-index.json"));
+    assert_eq!(
+        entries[1]["url"],
+        format!("view?path={file}#follow-logs").as_str()
+    );
+    assert_eq!(get(addr, "/assets/cognitio.css").status, 200);
+    assert_eq!(get(addr, "/assets/cognitio.js").status, 200);
+}
+
+#[test]
+fn denies_paths_outside_the_sources() {
+    let (source, addr) = start("access", ServeOptions::default());
+    // Files in a source that aren't cheatsheets the library lists are kept private too.
+    fs::create_dir_all(source.join(".git")).unwrap();
+    fs::write(
+        source.join(".git/config"),
+        "url = https://Secret@example.com",
+    )
+    .unwrap();
+    fs::write(source.join(".git/notes.md"), "### Secret\n").unwrap();
+    fs::write(source.join("docker/notes.txt"), "Secret").unwrap();
+
+    for path in [
+        source.join("../secret.md"),
+        source.join(".git/config"),
+        source.join(".git/notes.md"),
+        source.join("docker/notes.txt"),
+    ] {
+        let path = path_param(&path);
+        for target in [
+            format!("/api/cheatsheet?path={path}"),
+            format!("/api/sections?path={path}"),
+            format!("/view?path={path}"),
+        ] {
+            let reply = get(addr, &target);
+            assert_eq!(reply.status, 403, "{target}");
+            assert!(!reply.body.contains("Secret"));
+        }
+    }
+    for target in [
+        "/api/cheatsheet?path=/etc/passwd",
+        "/api/cheatsheet?path=secret.md",
+    ] {
+        assert_eq!(get(addr, target).status, 403, "{target}");
+    }
+    assert_eq!(get(addr, "/api/cheatsheet").status, 400);
+}
+
+#[test]
+fn is_read_only_by_default() {
+    let (source, addr) = start("read-only", ServeOptions::default());
+    let file = source.join("docker/containers.md");
+    let body = serde_json::json!({ "path": file, "heading": "New", "body": "text" }).to_string();
+
+    let reply = send(
+        addr,
+        "POST",
+        "/api/sections",
+        &["Content-Type: application/json"],
+        &body,
+    );
+    assert_eq!(reply.status, 403);
+    assert!(json(&reply)["error"]
+        .as_str()
+        .unwrap()
+        .contains("--writable"));
+    assert_eq!(fs::read_to_string(&file).unwrap(), CHEATSHEET);
+}
+
+#[test]
+fn changes_sections_when_writable() {
+    let options = ServeOptions {
+        writable: true,
+        ..ServeOptions::default()
+    };
+    let (source, addr) = start("writable", options);
+    let file = source.join("docker/containers.md");
+    let json_header = ["Content-Type: application/json"];
+
+    let body =
+        serde_json::json!({ "path": file, "heading": "Prune", "body": "Removes everything" });
+    let reply = send(
+        addr,
+        "POST",
+        "/api/sections",
+        &json_header,
+        &body.to_string(),
+    );
+    assert_eq!(reply.status, 200);
+    assert!(fs::read_to_string(&file).unwrap().contains("### Prune"));
+    assert_eq!(
+        json(&get(addr, "/api/search?q=prune"))[0]["section"],
+        "Prune"
+    );
+
+    let body = serde_json::json!({ "path": file, "heading": "Prune", "new_heading": "Clean up" });
+    let reply = send(
+        addr,
+        "PATCH",
+        "/api/sections",
+        &json_header,
+        &body.to_string(),
+    );
+    assert_eq!(
+        json(&reply)["groups"][0]["sections"][2]["title"],
+        "Clean up"
+    );
+
+    let body = serde_json::json!({ "path": file, "heading": "Clean up" });
+    let reply = send(
+        addr,
+        "DELETE",
+        "/api/sections",
+        &json_header,
+        &body.to_string(),
+    );
+    assert_eq!(reply.status, 200);
+    assert_eq!(fs::read_to_string(&file).unwrap(), CHEATSHEET);
+
+    let body = serde_json::json!({ "directory": source.join("docker"), "name": "volumes" });
+    let reply = send(
+        addr,
+        "POST",
+        "/api/cheatsheets",
+        &json_header,
+        &body.to_string(),
+    );
+    assert_eq!(reply.status, 201);
+    assert!(source.join("docker/volumes.md").exists());
+
+    // Forms from other web sites can't send JSON.
+    let body = serde_json::json!({ "path": file, "heading": "Form", "body": "" });
+    let reply = send(
+        addr,
+        "POST",
+        "/api/sections",
+        &["Content-Type: text/plain"],
+        &body.to_string(),
+    );
+    assert_eq!(reply.status, 400);
+
+    let body = serde_json::json!({ "path": source.join("../secret.md"), "heading": "Secret" });
+    let reply = send(
+        addr,
+        "DELETE",
+        "/api/sections",
+        &json_header,
+        &body.to_string(),
+    );
+    assert_eq!(reply.status, 403);
+}
+
+#[test]
+fn streams_changes_to_cheatsheets() {
+    let (source, addr) = start("events", ServeOptions::default());
+    let stream = TcpStream::connect(addr).unwrap();
+    (&stream)
+        .write_all(b"GET /api/events HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    let mut lines = BufReader::new(&stream).lines().map(Result::unwrap);
+    assert_eq!(lines.next().unwrap(), "HTTP/1.1 200 OK");
+    assert!(lines
+        .by_ref()
+        .take_while(|line| !line.is_empty())
+        .any(|line| line == "Content-Type: text/event-stream"));
+
+    // The watcher starts watching the source on a thread of its own, so the file is written
+    // until the change comes in.
+    let file = source.join("docker/containers.md");
+    let received = Arc::new(AtomicBool::new(false));
+    let writer = {
+        let (file, received) = (file.clone(), Arc::clone(&received));
+        thread::spawn(move || {
+            while !received.load(Ordering::Relaxed) {
+                fs::write(&file, format!("{CHEATSHEET}\n### Prune\n")).unwrap();
+                thread::sleep(Duration::from_millis(250));
+            }
+        })
+    };
+    let data = lines
+        .by_ref()
+        .skip_while(|line| line != "event: file_changed")
+        .nth(1)
+        .unwrap();
+    received.store(true, Ordering::Relaxed);
+    writer.join().unwrap();
+    let event: serde_json::Value =
+        serde_json::from_str(data.strip_prefix("data: ").unwrap()).unwrap();
+    assert_eq!(event["events"][0]["type"], "cheatsheet_modified");
+    assert_eq!(event["events"][0]["path"], file.to_string_lossy().as_ref());
+
+    // The search index follows the change too.
+    assert_eq!(
+        json(&get(addr, "/api/search?q=prune"))[0]["section"],
+        "Prune"
+    );
+}
+
+/// Opens an event stream and returns it with the status of the reply.
+fn open_events(addr: SocketAddr) -> (TcpStream, u16) {
+    let stream = TcpStream::connect(addr).unwrap();
+    (&stream)
+        .write_all(b"GET /api/events HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    let mut status_line = String::new();
+    BufReader::new(&stream).read_line(&mut status_line).unwrap();
+    let status = status_line[9..12].parse().unwrap();
+    (stream, status)
+}
+
+#[test]
+fn limits_connections() {
+    let options = ServeOptions {
+        max_connections: 4,
+        ..ServeOptions::default()
+    };
+    let (_source, addr) = start("limits", options);
+
+    // Event streams may take half of the connections.
+    let (_first, status) = open_events(addr);
+    assert_eq!(status, 200);
+    let (_second, status) = open_events(addr);
+    assert_eq!(status, 200);
+    let (_third, status) = open_events(addr);
+    assert_eq!(status, 503);
+    assert_eq!(get(addr, "/api/tree").status, 200);
+
+    // Clients that don't send their request hold a connection until they time out.
+    let _idle = [
+        TcpStream::connect(addr).unwrap(),
+        TcpStream::connect(addr).unwrap(),
+    ];
+    let reply = get(addr, "/api/tree");
+    assert_eq!(reply.status, 503);
+    assert!(reply.head.contains("Retry-After: 5"));
+}
+
+#[test]
+fn requires_the_token_when_one_is_set() {
+    let options = ServeOptions {
+        token: Some("s3cret".to_string()),
+        ..ServeOptions::default()
+    };
+    let (_source, addr) = start("token", options);
+
+    let reply = get(addr, "/api/tree");
+    assert_eq!(reply.status, 401);
+    assert_eq!(get(addr, "/api/events").status, 401);
+    assert!(reply.head.contains("WWW-Authenticate: Bearer"));
+    assert_eq!(
+        send(
+            addr,
+            "GET",
+            "/api/tree",
+            &["Authorization: Bearer wrong"],
+            ""
+        )
+        .status,
+        401
+    );
+    assert_eq!(
+        send(
+            addr,
+            "GET",
+            "/api/tree",
+            &["Authorization: Bearer s3cret"],
+            ""
+        )
+        .status,
+        200
+    );
+
+    let reply = get(addr, "/?token=s3cret");
+    assert_eq!(reply.status, 200);
+    assert!(reply.head.contains("Set-Cookie: cognitio_token=s3cret;"));
+    assert_eq!(
+        send(
+            addr,
+            "GET",
+            "/api/tree",
+            &["Cookie: cognitio_token=s3cret"],
+            ""
+        )
+        .status,
+        200
+    );
+}
+
+#[test]
+fn only_answers_to_localhost() {
+    let (_source, addr) = start("host", ServeOptions::default());
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .write_all(b"GET /api/tree HTTP/1.1\r\nHost: attacker.example:7777\r\n\r\n")
+        .unwrap();
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).unwrap();
+    assert!(reply.starts_with("HTTP/1.1 421"));
+}
+
+#[test]
+fn answers_unknown_requests_with_errors() {
+    let (_source, addr) = start("errors", ServeOptions::default());
+    assert_eq!(get(addr, "/nothing").status, 404);
+    assert_eq!(send(addr, "DELETE", "/api/tree", &[], "").status, 405);
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(b"nonsense\r\n\r\n").unwrap();
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).unwrap();
+    assert!(reply.starts_with("HTTP/1.1 400"));
+}
@@ -0,0 +1,115 @@
+//! Tests of git sources against a local bare repository, cloned with a `file://` URL. They need
+//! `git` on the path.
+
+use cognitio_core::config::check_cognitio_yaml;
+use cognitio_core::sources::{sync_sources, SyncOutcome};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::{self, Command};
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args([
+            "-c",
+            "user.name=Cognitio",
+            "-c",
+            "user.email=cognitio@example.com",
+        ])
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {}", args.join(" "));
+}
+
+/// Commits a file in `work` and pushes it to the bare repository.
+fn push_file(work: &Path, name: &str, contents: &str) {
+    let path = work.join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+    git(work, &["add", "."]);
+    git(work, &["commit", "--quiet", "-m", name]);
+    git(work, &["push", "--quiet", "origin", "HEAD:main"]);
+}
+
+fn outcomes(results: &[cognitio_core::sources::SyncResult]) -> Vec<&SyncOutcome> {
+    results.iter().map(|result| &result.outcome).collect()
+}
+
+// One test, since the checkout directory depends on `COGNITIO_HOME`, which is set for the whole
+// process.
+#[test]
+fn syncs_git_sources() {
+    let dir = env::temp_dir().join(format!("cognitio-sources-{}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let (origin, work, home) = (dir.join("origin.git"), dir.join("work"), dir.join("home"));
+    fs::create_dir_all(&origin).unwrap();
+    fs::create_dir_all(&work).unwrap();
+    fs::create_dir_all(&home).unwrap();
+    git(&origin, &["init", "--quiet", "--bare"]);
+    git(&origin, &["symbolic-ref", "HEAD", "refs/heads/main"]);
+    git(&work, &["init", "--quiet"]);
+    git(
+        &work,
+        &["remote", "add", "origin", &origin.to_string_lossy()],
+    );
+    push_file(&work, "cheats/docker/containers.md", "### List\n");
+
+    env::remove_var("COGNITIO_CONFIG");
+    env::set_var("COGNITIO_HOME", &home);
+    fs::write(
+        home.join("cognitio.yaml"),
+        format!(
+            "cheatsheets:\n  - title: Team\n    git: file://{}\n    subdir: cheats\n",
+            origin.display()
+        ),
+    )
+    .unwrap();
+
+    // A source that isn't cloned yet doesn't keep the config from being used.
+    let check = check_cognitio_yaml();
+    assert!(check.is_valid(), "{:?}", check.errors);
+    assert_eq!(check.warnings.len(), 1);
+    assert!(check.warnings[0].message.contains("cognitio sources sync"));
+    assert_eq!(check.warnings[0].line, Some(3));
+
+    let conf = check.config.unwrap();
+    let results = sync_sources(&conf);
+    assert!(matches!(
+        outcomes(&results)[..],
+        [SyncOutcome::Cloned { .. }]
+    ));
+    let checkout = Path::new(&results[0].path);
+    assert!(checkout.join("cheats/docker/containers.md").is_file());
+    assert!(check_cognitio_yaml().warnings.is_empty());
+
+    let results = sync_sources(&conf);
+    assert!(matches!(
+        outcomes(&results)[..],
+        [SyncOutcome::UpToDate { .. }]
+    ));
+
+    push_file(&work, "cheats/docker/volumes.md", "### Prune\n");
+    let results = sync_sources(&conf);
+    assert!(matches!(
+        outcomes(&results)[..],
+        [SyncOutcome::Updated { .. }]
+    ));
+    assert!(checkout.join("cheats/docker/volumes.md").is_file());
+
+    // Local changes are never overwritten.
+    fs::write(checkout.join("cheats/docker/volumes.md"), "### Mine\n").unwrap();
+    git(checkout, &["commit", "--quiet", "-am", "local"]);
+    push_file(&work, "cheats/docker/volumes.md", "### Theirs\n");
+    let results = sync_sources(&conf);
+    assert!(matches!(
+        outcomes(&results)[..],
+        [SyncOutcome::Failed { .. }]
+    ));
+    assert_eq!(
+        fs::read_to_string(checkout.join("cheats/docker/volumes.md")).unwrap(),
+        "### Mine\n"
+    );
+}
@@ -1,54 +1,45 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use cognitio_core::access::AllowedRoots;
+use cognitio_core::config::{
+    check_cognitio_yaml, cognitio_home_dir, cognitio_yaml_path, ConfigError,
+};
+use cognitio_core::content;
+use cognitio_core::edit;
+use cognitio_core::editor;
+use cognitio_core::export;
+use cognitio_core::html::HtmlRenderer;
+use cognitio_core::library;
+use cognitio_core::placeholder::{self, RenderedSnippet};
+use cognitio_core::search::{SearchHit, SearchIndex};
+use cognitio_core::snippet::{self, Cheatsheet};
+use cognitio_core::sources::{self, SyncResult};
+use cognitio_core::watch::{
+    config_debounce, config_watch_roots, library_events, ChangeEvent, FileChange, LibraryEvent,
+    WatchService,
+};
+use cognitio_core::{CognitioConfig, Directory, DirectoryFile};
 use log::LevelFilter;
-use log::{error, info};
-use log4rs;
+use log::{error, warn};
 use log4rs::append::console::ConsoleAppender;
 use log4rs::append::file::FileAppender;
 use log4rs::config::{Appender, Config as Log4rsConfig, Root};
 use log4rs::encode::pattern::PatternEncoder;
-use notify::{Config, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use serde::Deserialize;
-use serde::Serialize;
-use serde_yaml;
 use std::collections::HashMap;
-use std::env;
-use std::fs;
-use std::fs::File;
-use std::io::Read;
-use std::path::Path;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::channel;
 use std::sync::mpsc::Receiver;
-use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+use tauri::api::dialog::blocking::FileDialogBuilder;
 use tauri::AppHandle;
 use tauri::Manager;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DirectoryFile {
-    pub name: String,
-    pub path: String,
-}
-
-#[derive(Debug, Serialize)]
-pub struct Directory {
-    pub name: String,
-    pub path: String,
-    pub files: Vec<DirectoryFile>,
-    pub sub_directories: Vec<Directory>,
-}
-
 #[derive(Clone, serde::Serialize)]
 pub struct FileChangedPayload {
-    pub path: String,
-    pub event: String,
-}
-
-#[derive(Clone, serde::Serialize)]
-pub struct FileEvent {
-    pub path: String,
-    pub event: String,
+    /// What changed in the cheatsheet sources since the last `file_changed` event. Config changes
+    /// are sent as `cognitio_config_changed` or `cognitio_config_invalid` instead.
+    pub events: Vec<ChangeEvent>,
 }
 
 #[derive(Clone, serde::Serialize)]
@@ -56,36 +47,63 @@ pub struct CognitioConfigChangedPayload {
     pub config: CognitioConfig,
 }
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
-pub struct CheatsheetInfo {
-    title: String,
-    path: String,
+#[derive(Clone, serde::Serialize)]
+pub struct CognitioConfigInvalidPayload {
+    pub errors: Vec<ConfigError>,
 }
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
-pub struct Styling {
-    menu: Option<Menu>,
+/// The last config without errors, used while the config file has errors, and the errors of
+/// the config file as it is now.
+pub struct ConfigState {
+    config: Mutex<CognitioConfig>,
+    errors: Mutex<Vec<ConfigError>>,
 }
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
-pub struct Menu {
-    width: Option<String>,
-}
+impl ConfigState {
+    fn config(&self) -> CognitioConfig {
+        self.config.lock().unwrap().clone()
+    }
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
-pub struct CognitioConfig {
-    editor: Option<String>,
-    cheatsheets: Vec<CheatsheetData>,
-    styling: Option<Styling>,
-}
+    /// Checks that a path from the frontend is inside a cheatsheet source or COGNITIO_HOME, and
+    /// returns it canonicalized. Commands work with the returned path, so that a symlink swapped
+    /// in after the check can't send them elsewhere.
+    fn check_path(&self, path: &str) -> Result<String, tauri::Error> {
+        AllowedRoots::from_config(&self.config())
+            .check_str(path)
+            .map_err(|error| {
+                error!("{error}");
+                tauri::Error::from(error)
+            })
+    }
 
-#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
-#[serde(untagged)]
-pub enum CheatsheetData {
-    Simple(String),
-    Info(CheatsheetInfo),
+    /// Like [`ConfigState::check_path`], for every file of a cheatsheet.
+    fn check_files(&self, files: Vec<DirectoryFile>) -> Result<Vec<DirectoryFile>, tauri::Error> {
+        let roots = AllowedRoots::from_config(&self.config());
+        files
+            .into_iter()
+            .map(|file| {
+                let path = roots.check_str(&file.path).map_err(|error| {
+                    error!("{error}");
+                    tauri::Error::from(error)
+                })?;
+                Ok(DirectoryFile { path, ..file })
+            })
+            .collect()
+    }
 }
 
+/// The file watcher, watching the config directory and every cheatsheet source.
+pub struct WatchState(WatchService);
+
+/// The search index shared between the `search_snippets` command and the file watcher.
+pub struct SearchState(Mutex<SearchIndex>);
+
+/// Loading the syntaxes and theme for highlighting is slow, so one renderer is kept.
+pub struct HtmlState(HtmlRenderer);
+
+/// Number of hits returned by `search_snippets` when the frontend doesn't ask for a limit.
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
 fn main() {
     setup_logger();
 
@@ -128,364 +146,390 @@ fn run_tauri() -> Result<(), tauri::Error> {
         error!("Failed to fix PATH environment variable: {error:?}")
     }
 
-    let (sender, receiver) = channel::<FileEvent>();
-    let cheatsheet_sender = sender.clone();
-    let cognitio_config_sender = sender.clone();
+    let (sender, receiver) = channel::<Vec<FileChange>>();
 
-    tauri::Builder::default()
+    let app = tauri::Builder::default()
         .invoke_handler(tauri::generate_handler![
             load_cheatsheet,
             load_cheatsheet_section,
+            load_parsed_cheatsheet,
+            load_parsed_cheatsheet_section,
+            render_cheatsheet_html,
+            render_snippet,
             load_cognitio_config,
             list_cheatsheet_directories,
+            validate_cognitio_config,
+            sync_sources,
+            search_snippets,
             edit_directory,
             edit_file,
+            open_snippet_in_editor,
+            create_cheatsheet,
+            append_section,
+            replace_section_body,
+            rename_section,
+            delete_section,
+            export_cheatsheet_html,
             edit_cognitio_config
         ])
         .setup(|app| {
+            // With nothing better to go on at startup, a config with missing sources is used
+            // as it is, and a config that can't be read is replaced with an empty one.
+            let check = check_cognitio_yaml();
+            for error in &check.errors {
+                error!("Invalid Cognitio config: {error}");
+            }
+            for warning in &check.warnings {
+                warn!("{warning}");
+            }
+            let conf = check.config.unwrap_or_default();
+
+            let watcher = WatchService::start(sender, config_debounce(&conf))?;
+            watcher.set_roots(config_watch_roots(&conf));
+            app.manage(WatchState(watcher));
+
+            app.manage(SearchState(Mutex::new(SearchIndex::build(&conf))));
+            app.manage(HtmlState(HtmlRenderer::default()));
+            app.manage(ConfigState {
+                config: Mutex::new(conf),
+                errors: Mutex::new(check.errors),
+            });
+
             let app_handle = app.app_handle();
-            watch_cheatsheet_directories(cheatsheet_sender);
-            watch_cognitio_config_file(cognitio_config_sender);
             emit_events_to_frontend_when_files_change(receiver, app_handle);
             Ok(())
         })
-        .run(tauri::generate_context!())
-}
-
-#[tauri::command]
-fn edit_cognitio_config() -> Result<String, tauri::Error> {
-    let home = cognitio_home_dir();
-    let yaml_path = PathBuf::from(home).join("cognitio.yaml");
-    let conf = read_cognitio_yaml().unwrap();
-    let editor = conf.editor;
-
-    if editor.is_none() {
-        return Err(tauri::Error::from(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "Editor is not configured in Cognitio config file",
-        )));
-    }
+        .build(tauri::generate_context!())?;
 
-    let cmd_output = std::process::Command::new(editor.unwrap())
-        .arg(yaml_path.clone())
-        .output();
-
-    match cmd_output {
-        Ok(output) => Ok(String::from_utf8(output.stdout).unwrap()),
-        Err(error) => {
-            error!(
-                "Failed to edit Cognitio config {}: {:?}",
-                yaml_path.clone().to_str().unwrap(),
-                error
-            );
-            Err(tauri::Error::from(error))
+    app.run(|app_handle, event| {
+        if let tauri::RunEvent::Exit = event {
+            app_handle.state::<WatchState>().0.shutdown();
         }
-    }
+    });
+    Ok(())
 }
 
 #[tauri::command]
-fn edit_directory(path: String) -> Result<String, tauri::Error> {
-    let conf = read_cognitio_yaml().unwrap();
-    let editor = conf.editor;
-
-    if editor.is_none() {
-        return Err(tauri::Error::from(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "Editor is not configured in Cognitio config file",
-        )));
-    }
+fn edit_cognitio_config(config: tauri::State<ConfigState>) -> Result<(), tauri::Error> {
+    open_in_editor(&config, &cognitio_yaml_path(), None)
+}
 
-    let cmd_output = std::process::Command::new(editor.unwrap())
-        .arg(path.clone())
-        .output();
+#[tauri::command]
+fn edit_directory(path: String, config: tauri::State<ConfigState>) -> Result<(), tauri::Error> {
+    let path = config.check_path(&path)?;
+    open_in_editor(&config, Path::new(&path), None)
+}
 
-    match cmd_output {
-        Ok(output) => Ok(String::from_utf8(output.stdout).unwrap()),
-        Err(error) => {
-            error!("Failed to edit directory {}: {:?}", path.clone(), error);
-            Err(tauri::Error::from(error))
-        }
-    }
+#[tauri::command]
+fn edit_file(path: String, config: tauri::State<ConfigState>) -> Result<(), tauri::Error> {
+    let path = config.check_path(&path)?;
+    open_in_editor(&config, Path::new(&path), None)
 }
 
+/// Opens a cheatsheet in the editor at the line of a section's heading.
 #[tauri::command]
-fn edit_file(path: String) -> Result<String, tauri::Error> {
-    let conf = read_cognitio_yaml().unwrap();
-    let editor = conf.editor;
-
-    if editor.is_none() {
-        return Err(tauri::Error::from(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "Editor is not configured in Cognitio config file",
-        )));
-    }
+fn open_snippet_in_editor(
+    path: String,
+    heading: String,
+    config: tauri::State<ConfigState>,
+) -> Result<(), tauri::Error> {
+    let path = config.check_path(&path)?;
+    let line = editor::section_line(&path, &heading).map_err(|error| {
+        error!("Failed to find section {heading} in {path}: {error:?}");
+        tauri::Error::from(error)
+    })?;
+    open_in_editor(&config, Path::new(&path), Some(line))
+}
 
-    let cmd_output = std::process::Command::new(editor.unwrap())
-        .arg(path.clone())
-        .output();
+/// Starts the configured editor without waiting for it to exit.
+fn open_in_editor(
+    config: &ConfigState,
+    path: &Path,
+    line: Option<usize>,
+) -> Result<(), tauri::Error> {
+    editor::open_in_editor(&config.config(), path, line).map_err(|error| {
+        error!("Failed to open {} in the editor: {error:?}", path.display());
+        tauri::Error::from(error)
+    })
+}
 
-    match cmd_output {
-        Ok(output) => Ok(String::from_utf8(output.stdout).unwrap()),
-        Err(error) => {
-            error!("Failed to edit file {}: {:?}", path.clone(), error);
-            Err(tauri::Error::from(error))
-        }
-    }
+#[tauri::command]
+fn load_cognitio_config(config: tauri::State<ConfigState>) -> CognitioConfig {
+    config.config()
 }
 
+/// Returns the errors of the config file. While there are any, the last config without errors
+/// is used.
 #[tauri::command]
-fn load_cognitio_config() -> CognitioConfig {
-    return read_cognitio_yaml().unwrap();
+fn validate_cognitio_config(config: tauri::State<ConfigState>) -> Vec<ConfigError> {
+    config.errors.lock().unwrap().clone()
 }
 
 #[tauri::command]
-fn load_cheatsheet(files: Vec<DirectoryFile>) -> HashMap<String, String> {
-    let mut cheatsheet: HashMap<String, String> = HashMap::new();
-    for file in files {
-        let file_content = read_file_to_string(&file.path).unwrap_or_default();
-        let clean_name = file.name.replace(".md", "");
-        cheatsheet.insert(clean_name, file_content);
-    }
-    cheatsheet
+fn load_cheatsheet(
+    files: Vec<DirectoryFile>,
+    config: tauri::State<ConfigState>,
+) -> Result<HashMap<String, String>, tauri::Error> {
+    let files = config.check_files(files)?;
+    Ok(content::load_cheatsheet(&files))
 }
 
 #[tauri::command]
-fn load_cheatsheet_section(path: String) -> HashMap<String, String> {
-    let mut cheatsheet: HashMap<String, String> = HashMap::new();
-    let file_content = read_file_to_string(&path).unwrap_or_default();
-    let file_name = Path::new(&path)
-        .file_name()
-        .unwrap_or_default()
-        .to_str()
-        .unwrap_or_default()
-        .to_string()
-        .replace(".md", "");
-    cheatsheet.insert(file_name, file_content);
-    cheatsheet
+fn load_cheatsheet_section(
+    path: String,
+    config: tauri::State<ConfigState>,
+) -> Result<HashMap<String, String>, tauri::Error> {
+    let path = config.check_path(&path)?;
+    Ok(content::load_cheatsheet_section(&path))
 }
 
 #[tauri::command]
-fn list_cheatsheet_directories() -> Vec<Directory> {
-    let conf = read_cognitio_yaml().unwrap();
-    let res: Vec<Directory> = conf
-        .cheatsheets
+fn load_parsed_cheatsheet(
+    files: Vec<DirectoryFile>,
+    config: tauri::State<ConfigState>,
+) -> Result<Vec<Cheatsheet>, tauri::Error> {
+    let files = config.check_files(files)?;
+    Ok(files
         .iter()
-        .map(|cheatsheet_path| match cheatsheet_path {
-            CheatsheetData::Simple(path) => {
-                let name = Path::new(path)
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_str()
-                    .unwrap_or_default()
-                    .to_string();
-                Directory {
-                    name,
-                    path: path.to_string(),
-                    files: Vec::new(),
-                    sub_directories: list_subdirectories(&path),
-                }
-            }
-            CheatsheetData::Info(info) => {
-                let name = info.title.clone();
-                Directory {
-                    name,
-                    path: info.path.clone(),
-                    files: Vec::new(),
-                    sub_directories: list_subdirectories(&info.path),
-                }
+        .filter_map(|file| match snippet::load_cheatsheet_file(&file.path) {
+            Ok(cheatsheet) => Some(cheatsheet),
+            Err(error) => {
+                error!("Failed to load cheatsheet {}: {:?}", file.path, error);
+                None
             }
         })
-        .collect();
-    res
-}
-
-fn emit_events_to_frontend_when_files_change(receiver: Receiver<FileEvent>, tauri_app: AppHandle) {
-    tauri::async_runtime::spawn(async move {
-        for msg in receiver.iter() {
-            if msg.path.ends_with("cognitio.yaml") {
-                tauri_app
-                    .emit_all(
-                        "cognitio_config_changed",
-                        CognitioConfigChangedPayload {
-                            config: read_cognitio_yaml().unwrap(),
-                        },
-                    )
-                    .unwrap();
-            } else {
-                tauri_app
-                    .emit_all(
-                        "file_changed",
-                        FileChangedPayload {
-                            path: msg.path,
-                            event: msg.event,
-                        },
-                    )
-                    .unwrap();
-            }
-        }
-    });
+        .collect())
 }
 
-fn watch_cheatsheet_directories(on_file_event: Sender<FileEvent>) {
-    for dir in list_cheatsheet_directories() {
-        tauri::async_runtime::spawn(watch_dir(dir.path, on_file_event.clone()));
-    }
+#[tauri::command]
+fn load_parsed_cheatsheet_section(
+    path: String,
+    config: tauri::State<ConfigState>,
+) -> Result<Cheatsheet, tauri::Error> {
+    let path = config.check_path(&path)?;
+    snippet::load_cheatsheet_file(&path).map_err(tauri::Error::from)
 }
 
-fn watch_cognitio_config_file(on_file_event: Sender<FileEvent>) {
-    let home = cognitio_home_dir();
-    tauri::async_runtime::spawn(watch_dir(home, on_file_event.clone()));
+/// Creates an empty cheatsheet file in a directory.
+#[tauri::command]
+fn create_cheatsheet(
+    directory: String,
+    name: String,
+    config: tauri::State<ConfigState>,
+) -> Result<Cheatsheet, tauri::Error> {
+    let directory = config.check_path(&directory)?;
+    edit::create_cheatsheet(&directory, &name).map_err(|error| {
+        error!("Failed to create cheatsheet {name} in {directory}: {error:?}");
+        tauri::Error::from(error)
+    })
 }
 
-async fn watch_dir(path: String, on_file_event: Sender<FileEvent>) {
-    if let Err(error) = watch(path.clone(), on_file_event) {
-        error!("Watch {} error: {error:?}", path.to_string());
-    }
+#[tauri::command]
+fn append_section(
+    path: String,
+    heading: String,
+    body: String,
+    config: tauri::State<ConfigState>,
+) -> Result<Cheatsheet, tauri::Error> {
+    let path = config.check_path(&path)?;
+    edit::append_section(&path, &heading, &body).map_err(|error| {
+        error!("Failed to add section {heading} to {path}: {error:?}");
+        tauri::Error::from(error)
+    })
 }
 
-fn watch<P: AsRef<Path>>(path: P, on_file_event: Sender<FileEvent>) -> notify::Result<()> {
-    let (tx, rx) = channel();
-
-    let mut watcher = RecommendedWatcher::new(tx, Config::default())?;
-    watcher.watch(path.as_ref(), RecursiveMode::Recursive)?;
-
-    for res in rx {
-        match res {
-            Ok(event) => {
-                info!("File event: {:?}", event);
-
-                if let Some(path) = event.paths.first() {
-                    let path_str = path.to_str().unwrap_or_default().to_string();
-
-                    match event.kind {
-                        EventKind::Create(_) => {
-                            on_file_event
-                                .send(FileEvent {
-                                    path: path_str,
-                                    event: "create".to_string(),
-                                })
-                                .unwrap();
-                        }
-                        EventKind::Remove(_) => {
-                            on_file_event
-                                .send(FileEvent {
-                                    path: path_str,
-                                    event: "remove".to_string(),
-                                })
-                                .unwrap();
-                        }
-                        EventKind::Modify(_) => {
-                            // We seem to get the Modify event when deleting files.
-                            // Check if the file exists and send the correct event type.
-                            let action = if check_if_path_exists(path_str.as_str()) {
-                                "modify".to_string()
-                            } else {
-                                "remove".to_string()
-                            };
-
-                            on_file_event
-                                .send(FileEvent {
-                                    path: path_str,
-                                    event: action,
-                                })
-                                .unwrap();
-                        }
-                        _ => {}
-                    }
-                }
-            }
-            Err(error) => error!(
-                "Failed to watch {}: {error:?}",
-                path.as_ref().to_str().unwrap_or_default()
-            ),
-        }
-    }
+#[tauri::command]
+fn replace_section_body(
+    path: String,
+    heading: String,
+    body: String,
+    config: tauri::State<ConfigState>,
+) -> Result<Cheatsheet, tauri::Error> {
+    let path = config.check_path(&path)?;
+    edit::replace_section_body(&path, &heading, &body).map_err(|error| {
+        error!("Failed to change section {heading} in {path}: {error:?}");
+        tauri::Error::from(error)
+    })
+}
 
-    Ok(())
+#[tauri::command]
+fn rename_section(
+    path: String,
+    heading: String,
+    new_heading: String,
+    config: tauri::State<ConfigState>,
+) -> Result<Cheatsheet, tauri::Error> {
+    let path = config.check_path(&path)?;
+    edit::rename_section(&path, &heading, &new_heading).map_err(|error| {
+        error!("Failed to rename section {heading} in {path}: {error:?}");
+        tauri::Error::from(error)
+    })
 }
 
-fn check_if_path_exists(path_str: &str) -> bool {
-    Path::new(path_str).exists()
+#[tauri::command]
+fn delete_section(
+    path: String,
+    heading: String,
+    config: tauri::State<ConfigState>,
+) -> Result<Cheatsheet, tauri::Error> {
+    let path = config.check_path(&path)?;
+    edit::delete_section(&path, &heading).map_err(|error| {
+        error!("Failed to delete section {heading} from {path}: {error:?}");
+        tauri::Error::from(error)
+    })
 }
 
-fn read_file_to_string(file_path: &str) -> std::io::Result<String> {
-    let mut file = File::open(file_path)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    Ok(contents)
+/// Renders a cheatsheet with its sections as cards and its code highlighted.
+#[tauri::command]
+fn render_cheatsheet_html(
+    path: String,
+    config: tauri::State<ConfigState>,
+    html: tauri::State<HtmlState>,
+) -> Result<String, tauri::Error> {
+    let path = config.check_path(&path)?;
+    let cheatsheet = snippet::load_cheatsheet_file(&path).map_err(|error| {
+        error!("Failed to load cheatsheet {path}: {error:?}");
+        tauri::Error::from(error)
+    })?;
+    Ok(html.0.render_cheatsheet(&cheatsheet))
 }
 
-fn list_subdirectories(root_dir: &str) -> Vec<Directory> {
-    let mut directories = Vec::new();
+/// Asks where to save a cheatsheet as a standalone HTML page, writes it there and returns the
+/// path written, or nothing when the dialog is cancelled. The destination comes from a native
+/// save dialog rather than from the frontend, since it may be outside the cheatsheet sources.
+/// Async, because the dialog can't block the main thread that sync commands run on.
+#[tauri::command]
+async fn export_cheatsheet_html(
+    path: String,
+    config: tauri::State<'_, ConfigState>,
+) -> Result<Option<String>, tauri::Error> {
+    let path = config.check_path(&path)?;
+    let name = Path::new(&path)
+        .file_stem()
+        .map(|stem| format!("{}.html", stem.to_string_lossy()))
+        .unwrap_or_default();
+    let dest = FileDialogBuilder::new()
+        .add_filter("HTML", &["html"])
+        .set_file_name(&name)
+        .save_file();
+    let Some(dest) = dest else {
+        return Ok(None);
+    };
+    let dest = dest.with_extension("html");
+    export::export_cheatsheet(&config.config(), &path, &dest)
+        .map(|out| Some(out.to_string_lossy().to_string()))
+        .map_err(|error| {
+            error!("Failed to export {path} to {}: {error:?}", dest.display());
+            tauri::Error::from(error)
+        })
+}
 
-    if let Ok(entries) = fs::read_dir(root_dir) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.is_dir() {
-                    let name = path.file_name().unwrap().to_string_lossy().to_string();
-                    if name.starts_with(".") {
-                        continue;
-                    }
-                    let files = list_files_in_directory(path.to_str().unwrap());
-                    directories.push(Directory {
-                        name,
-                        path: path.to_str().unwrap().to_string(),
-                        files,
-                        sub_directories: Vec::new(),
-                    });
-                }
-            }
-        }
-    }
+/// Returns the code of a section with its `{{placeholders}}` filled in from `vars`.
+#[tauri::command]
+fn render_snippet(
+    path: String,
+    section: String,
+    vars: HashMap<String, String>,
+    config: tauri::State<ConfigState>,
+) -> Result<RenderedSnippet, tauri::Error> {
+    let path = config.check_path(&path)?;
+    placeholder::render_snippet(&path, &section, &vars).map_err(tauri::Error::from)
+}
+
+#[tauri::command]
+fn list_cheatsheet_directories(config: tauri::State<ConfigState>) -> Vec<Directory> {
+    library::list_cheatsheet_directories(&config.config())
+}
+
+/// Clones or fast-forwards every git source, rebuilds the search index and starts watching
+/// sources that were cloned for the first time. Runs off the main thread since it talks to the
+/// network.
+#[tauri::command]
+async fn sync_sources(
+    config: tauri::State<'_, ConfigState>,
+    search: tauri::State<'_, SearchState>,
+    watcher: tauri::State<'_, WatchState>,
+) -> Result<Vec<SyncResult>, tauri::Error> {
+    let conf = config.config();
+    let results = sources::sync_sources(&conf);
+    *search.0.lock().unwrap() = SearchIndex::build(&conf);
+    watcher.0.set_roots(config_watch_roots(&conf));
+    Ok(results)
+}
 
-    directories
+#[tauri::command]
+fn search_snippets(
+    query: String,
+    limit: Option<usize>,
+    search: tauri::State<SearchState>,
+) -> Vec<SearchHit> {
+    let index = search.0.lock().unwrap();
+    index.search(&query, limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
 }
 
-fn list_files_in_directory(directory_path: &str) -> Vec<DirectoryFile> {
-    let mut files = Vec::new();
+/// Replaces the current config with one from a changed config file without errors, and
+/// rebuilds the search index. The watcher already watches the new sources.
+fn config_changed(tauri_app: &AppHandle, conf: CognitioConfig) {
+    let state = tauri_app.state::<ConfigState>();
+    *state.errors.lock().unwrap() = Vec::new();
+    *state.config.lock().unwrap() = conf.clone();
+    *tauri_app.state::<SearchState>().0.lock().unwrap() = SearchIndex::build(&conf);
+
+    if let Err(error) = tauri_app.emit_all(
+        "cognitio_config_changed",
+        CognitioConfigChangedPayload { config: conf },
+    ) {
+        error!("Failed to emit cognitio_config_changed: {error:?}");
+    }
+}
 
-    if let Ok(entries) = fs::read_dir(directory_path) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.is_file() && path.file_name().unwrap().to_str().unwrap().ends_with(".md") {
-                    let name = path.file_name().unwrap().to_string_lossy().to_string();
-                    files.push(DirectoryFile {
-                        name,
-                        path: path.to_str().unwrap().to_string(),
-                    });
+/// Sends the errors of a changed config file to the frontend, keeping the current config.
+fn config_invalid(tauri_app: &AppHandle, errors: Vec<ConfigError>) {
+    *tauri_app.state::<ConfigState>().errors.lock().unwrap() = errors.clone();
+    if let Err(error) = tauri_app.emit_all(
+        "cognitio_config_invalid",
+        CognitioConfigInvalidPayload { errors },
+    ) {
+        error!("Failed to emit cognitio_config_invalid: {error:?}");
+    }
+}
+
+/// Handles batches of file changes on a thread of its own, since waiting for them blocks. A
+/// change to the config file reloads the config; the other changes update the search index and
+/// are sent to the frontend as one `file_changed` event.
+fn emit_events_to_frontend_when_files_change(
+    receiver: Receiver<Vec<FileChange>>,
+    tauri_app: AppHandle,
+) {
+    std::thread::spawn(move || {
+        for batch in receiver.iter() {
+            let events = library_events(&batch, &tauri_app.state::<WatchState>().0);
+            for event in events {
+                match event {
+                    LibraryEvent::ConfigChanged { config, .. } => {
+                        config_changed(&tauri_app, config)
+                    }
+                    LibraryEvent::ConfigInvalid { errors, .. } => {
+                        config_invalid(&tauri_app, errors)
+                    }
+                    LibraryEvent::FilesChanged { events } => files_changed(&tauri_app, events),
                 }
             }
         }
-    }
-
-    files
+    });
 }
 
-fn read_cognitio_yaml() -> std::io::Result<CognitioConfig> {
-    let home = cognitio_home_dir();
-    let yaml_path = PathBuf::from(home).join("cognitio.yaml");
-    let mut file = File::open(yaml_path)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    let manifest: CognitioConfig = serde_yaml::from_str(&contents).unwrap();
-    Ok(manifest)
-}
-
-fn cognitio_home_dir() -> String {
-    let home_dir = env::var("HOME").unwrap();
-    if home_dir.is_empty() {
-        error!("Error reading environment variable HOME: Empty or not exist");
+/// Updates the search index for the changed files and sends the changes to the frontend.
+fn files_changed(tauri_app: &AppHandle, events: Vec<ChangeEvent>) {
+    {
+        let search = tauri_app.state::<SearchState>();
+        let mut index = search.0.lock().unwrap();
+        for path in events.iter().flat_map(|event| event.paths()) {
+            index.refresh_path(path);
+        }
     }
 
-    match env::var("COGNITIO_HOME") {
-        Ok(value) => value,
-        Err(_e) => PathBuf::from(home_dir)
-            .join(".config")
-            .join("cognitio")
-            .to_str()
-            .unwrap_or_default()
-            .to_string(),
+    if let Err(error) = tauri_app.emit_all("file_changed", FileChangedPayload { events }) {
+        error!("Failed to emit file_changed: {error:?}");
     }
 }
@@ -0,0 +1,25 @@
+import type { File } from '$lib/models';
+import { invokeRenderCheatsheetHtmlCommand } from './tauri';
+
+function nameWithoutExtension(fileName: string): string {
+	return fileName.replace('.md', '');
+}
+
+// Renders each file to HTML in the backend, keyed by file name without the .md extension.
+// Empty cheatsheets are left out.
+export async function renderCheatsheetFiles(files: File[]): Promise<Record<string, string>> {
+	const rendered = await Promise.all(
+		files.map(async (file) => {
+			const html = await invokeRenderCheatsheetHtmlCommand(file.path);
+			return [nameWithoutExtension(file.name), html] as const;
+		})
+	);
+	return rendered.reduce((all, [name, html]) => {
+		return html ? { ...all, [name]: html } : all;
+	}, {} as Record<string, string>);
+}
+
+export async function renderCheatsheetFile(path: string): Promise<Record<string, string>> {
+	const name = path.split(/[\\/]/).pop() ?? path;
+	return renderCheatsheetFiles([{ name, path }]);
+}
@@ -5,61 +5,40 @@ export function findDirectoryWithPath(
 	directories: Directory[],
 	path: string
 ): Directory | undefined {
-	let directory: Directory | undefined;
-
 	for (const dir of directories) {
 		if (dir.path === path) {
-			directory = dir;
-			break;
+			return dir;
 		}
 
-		if (dir.sub_directories.length > 0) {
-			for (const subDirectory of dir.sub_directories) {
-				if (subDirectory.path === path) {
-					directory = subDirectory;
-					break;
-				}
-			}
+		const subDirectory = findDirectoryWithPath(dir.sub_directories, path);
+		if (subDirectory) {
+			return subDirectory;
 		}
 	}
 
-	return directory;
+	return undefined;
 }
 
 export function findFileWithPath(directories: Directory[], path: string): File | undefined {
-	let file: File | undefined;
-
 	for (const dir of directories) {
-		if (dir.files.length > 0) {
-			for (const f of dir.files) {
-				if (f.path === path) {
-					file = f;
-					break;
-				}
-			}
+		const file = dir.files.find((f) => f.path === path);
+		if (file) {
+			return file;
 		}
 
-		if (dir.sub_directories.length > 0) {
-			for (const subDirectory of dir.sub_directories) {
-				if (subDirectory.files.length > 0) {
-					for (const f of subDirectory.files) {
-						if (f.path === path) {
-							file = f;
-							break;
-						}
-					}
-				}
-			}
+		const subDirectoryFile = findFileWithPath(dir.sub_directories, path);
+		if (subDirectoryFile) {
+			return subDirectoryFile;
 		}
 	}
 
-	return file;
+	return undefined;
 }
 
 export function orderAllDirectoriesByName(directories: Directory[]): Directory[] {
 	const orderedDirectories = orderBy(directories, ['name'], ['asc']);
 	for (const dir of orderedDirectories) {
-		dir.sub_directories = orderBy(dir.sub_directories, ['name'], ['asc']);
+		dir.sub_directories = orderAllDirectoriesByName(dir.sub_directories);
 	}
 	return orderedDirectories;
 }
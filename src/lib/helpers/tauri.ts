@@ -1,22 +1,74 @@
 import type {
 	CognitioConfig,
 	CognitioConfigChangedPayload,
+	CognitioConfigInvalidPayload,
+	ConfigError,
 	Directory,
 	File,
-	FileChangedPayload
+	FileChangedPayload,
+	ParsedCheatsheet,
+	RenderedSnippet,
+	SearchHit,
+	SyncResult
 } from '$lib/models';
 import { listen, type UnlistenFn, type Event } from '@tauri-apps/api/event';
 import { invoke } from '@tauri-apps/api/tauri';
 
-export function invokeEditDirectoryCommand(path: string): Promise<string> {
+export function invokeEditDirectoryCommand(path: string): Promise<void> {
 	return invoke('edit_directory', { path });
 }
 
-export function invokeEditFileCommand(path: string): Promise<string> {
+export function invokeEditFileCommand(path: string): Promise<void> {
 	return invoke('edit_file', { path });
 }
 
-export function invokeEditCognitioConfigCommand(): Promise<string> {
+export function invokeOpenSnippetInEditorCommand(path: string, heading: string): Promise<void> {
+	return invoke('open_snippet_in_editor', { path, heading });
+}
+
+export function invokeCreateCheatsheetCommand(
+	directory: string,
+	name: string
+): Promise<ParsedCheatsheet> {
+	return invoke('create_cheatsheet', { directory, name });
+}
+
+export function invokeAppendSectionCommand(
+	path: string,
+	heading: string,
+	body: string
+): Promise<ParsedCheatsheet> {
+	return invoke('append_section', { path, heading, body });
+}
+
+export function invokeReplaceSectionBodyCommand(
+	path: string,
+	heading: string,
+	body: string
+): Promise<ParsedCheatsheet> {
+	return invoke('replace_section_body', { path, heading, body });
+}
+
+export function invokeRenameSectionCommand(
+	path: string,
+	heading: string,
+	newHeading: string
+): Promise<ParsedCheatsheet> {
+	return invoke('rename_section', { path, heading, newHeading });
+}
+
+export function invokeDeleteSectionCommand(
+	path: string,
+	heading: string
+): Promise<ParsedCheatsheet> {
+	return invoke('delete_section', { path, heading });
+}
+
+export function invokeExportCheatsheetHtmlCommand(path: string): Promise<string | null> {
+	return invoke('export_cheatsheet_html', { path });
+}
+
+export function invokeEditCognitioConfigCommand(): Promise<void> {
 	return invoke('edit_cognitio_config');
 }
 
@@ -28,14 +80,46 @@ export function invokeLoadCheatsheetSectionCommand(path: string): Promise<Record
 	return invoke('load_cheatsheet_section', { path });
 }
 
+export function invokeLoadParsedCheatsheetCommand(files: File[]): Promise<ParsedCheatsheet[]> {
+	return invoke('load_parsed_cheatsheet', { files });
+}
+
+export function invokeLoadParsedCheatsheetSectionCommand(path: string): Promise<ParsedCheatsheet> {
+	return invoke('load_parsed_cheatsheet_section', { path });
+}
+
+export function invokeRenderCheatsheetHtmlCommand(path: string): Promise<string> {
+	return invoke('render_cheatsheet_html', { path });
+}
+
+export function invokeRenderSnippetCommand(
+	path: string,
+	section: string,
+	vars: Record<string, string>
+): Promise<RenderedSnippet> {
+	return invoke('render_snippet', { path, section, vars });
+}
+
 export function invokeLoadCheatsheetDirectoriesCommand(): Promise<Directory[]> {
 	return invoke('list_cheatsheet_directories');
 }
 
+export function invokeSyncSourcesCommand(): Promise<SyncResult[]> {
+	return invoke('sync_sources');
+}
+
+export function invokeSearchSnippetsCommand(query: string, limit?: number): Promise<SearchHit[]> {
+	return invoke('search_snippets', { query, limit });
+}
+
 export function invokeLoadCognitioConfigCommand(): Promise<CognitioConfig> {
 	return invoke('load_cognitio_config');
 }
 
+export function invokeValidateCognitioConfigCommand(): Promise<ConfigError[]> {
+	return invoke('validate_cognitio_config');
+}
+
 export type OnFileChanged<T> = (event: Event<T>) => void;
 
 export function listenForFileChangedEvents(
@@ -49,3 +133,9 @@ export function listenForCognitioConfigChangedEvents(
 ): Promise<UnlistenFn> {
 	return listen('cognitio_config_changed', onFileChanged);
 }
+
+export function listenForCognitioConfigInvalidEvents(
+	onFileChanged: OnFileChanged<CognitioConfigInvalidPayload>
+): Promise<UnlistenFn> {
+	return listen('cognitio_config_invalid', onFileChanged);
+}
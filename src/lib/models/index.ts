@@ -3,6 +3,96 @@ export interface Cheatsheet {
 	markdown: string;
 }
 
+export interface ParsedCheatsheet {
+	name: string;
+	path: string;
+	title: string | null;
+	groups: SnippetGroup[];
+}
+
+export interface SnippetGroup {
+	title: string | null;
+	line: number | null;
+	intro: SnippetBlock[];
+	sections: Snippet[];
+}
+
+export interface Snippet {
+	title: string;
+	blocks: SnippetBlock[];
+	start_line: number;
+	end_line: number;
+}
+
+export type SnippetBlock = ProseBlock | CodeBlock;
+
+export interface ProseBlock {
+	type: 'prose';
+	markdown: string;
+	start_line: number;
+	end_line: number;
+}
+
+export interface CodeBlock {
+	type: 'code';
+	language: string | null;
+	info: string;
+	code: string;
+	start_line: number;
+	end_line: number;
+}
+
+export interface Placeholder {
+	name: string;
+	default: string | null;
+	choices: string[];
+}
+
+export interface RenderedSnippet {
+	title: string;
+	code_blocks: string[];
+	text: string;
+	placeholders: Placeholder[];
+	unfilled: string[];
+}
+
+export interface GitSource {
+	title: string;
+	git: string;
+	branch?: string;
+	subdir?: string;
+	max_depth?: number;
+}
+
+export type SyncResult = {
+	title: string;
+	git: string;
+	path: string;
+} & (
+	| { status: 'cloned'; commit: string }
+	| { status: 'updated'; from: string; to: string }
+	| { status: 'up_to_date'; commit: string }
+	| { status: 'failed'; message: string }
+);
+
+export interface SearchHit {
+	path: string;
+	name: string;
+	group: string | null;
+	section: string;
+	line: number;
+	score: number;
+	matches: SearchMatch[];
+}
+
+export interface SearchMatch {
+	field: 'name' | 'heading' | 'prose' | 'code';
+	text: string;
+	line: number | null;
+	start: number;
+	end: number;
+}
+
 export interface File {
 	name: string;
 	path: string;
@@ -15,14 +105,37 @@ export interface Directory {
 	sub_directories: Directory[];
 }
 
+export type ChangeEvent =
+	| {
+			type:
+				| 'config_changed'
+				| 'cheatsheet_created'
+				| 'cheatsheet_modified'
+				| 'cheatsheet_removed'
+				| 'directory_changed';
+			path: string;
+	  }
+	| { type: 'cheatsheet_renamed'; from: string; to: string };
+
 export interface FileChangedPayload {
-	path: string;
-	event: string;
+	events: ChangeEvent[];
 }
+
 export interface CognitioConfigChangedPayload {
 	config: CognitioConfig;
 }
 
+export interface CognitioConfigInvalidPayload {
+	errors: ConfigError[];
+}
+
+export interface ConfigError {
+	file: string;
+	line: number | null;
+	column: number | null;
+	message: string;
+}
+
 export interface MenuItem {
 	id: string;
 	title: string;
@@ -39,6 +152,7 @@ export interface MenuSection {
 export interface CheatsheetInfo {
 	title: string;
 	path: string;
+	max_depth?: number;
 }
 
 export interface Styling {
@@ -47,6 +161,9 @@ export interface Styling {
 
 export interface CognitioConfig {
 	editor?: string;
-	cheatsheets: string[] | CheatsheetInfo[];
+	editors?: Record<string, string>;
+	cheatsheets: (string | CheatsheetInfo | GitSource)[];
 	styling?: Styling;
+	runners?: Record<string, string>;
+	watch?: { debounce_ms?: number };
 }
@@ -11,6 +11,25 @@ export function mapFilesToMenuItems(files: File[]): MenuItem[] {
 	});
 }
 
+function mapDirectoryToMenuItems(directory: Directory, titlePrefix: string): MenuItem[] {
+	const title = titlePrefix + directory.name;
+	const menuItems: MenuItem[] = [];
+
+	if (directory.files.length > 0) {
+		menuItems.push({
+			id: directory.path,
+			title,
+			children: mapFilesToMenuItems(directory.files)
+		});
+	}
+
+	directory.sub_directories.forEach((subDirectory) => {
+		menuItems.push(...mapDirectoryToMenuItems(subDirectory, `${title} / `));
+	});
+
+	return menuItems;
+}
+
 export function mapDirectoriesToMenuSections(directories: Directory[]): MenuSection[] {
 	return directories.map((directory) => {
 		const menuSection: MenuSection = {
@@ -21,26 +40,16 @@ export function mapDirectoriesToMenuSections(directories: Directory[]): MenuSect
 		};
 
 		if (directory.files.length > 0) {
-			menuSection.items = mapFilesToMenuItems(directory.files);
+			menuSection.items.push({
+				id: directory.path,
+				title: directory.name,
+				children: mapFilesToMenuItems(directory.files)
+			});
 		}
 
-		if (directory.sub_directories.length > 0) {
-			menuSection.items = menuSection.items.concat(
-				directory.sub_directories.map((subDirectory) => {
-					const subDirectoryMenuItem: MenuItem = {
-						id: subDirectory.path,
-						title: subDirectory.name,
-						children: []
-					};
-
-					if (subDirectory.files.length > 0) {
-						subDirectoryMenuItem.children = mapFilesToMenuItems(subDirectory.files);
-					}
-
-					return subDirectoryMenuItem;
-				})
-			);
-		}
+		directory.sub_directories.forEach((subDirectory) => {
+			menuSection.items.push(...mapDirectoryToMenuItems(subDirectory, ''));
+		});
 
 		return menuSection;
 	});